@@ -0,0 +1,331 @@
+// Decryption failure rate of Kyber parameter sets.
+//
+// The exact rate follows the Kyber specification: the noise left in each
+// decrypted coefficient is e^T r - s^T (e1 + c_u) + e2 + c_v, where c_u and
+// c_v are the rounding errors of ciphertext compression. Its distribution
+// is built by convolving the centered binomial and rounding error laws,
+// a coefficient decodes wrongly when the noise reaches q/4, and the rate is
+// the union bound over the n coefficients.
+//
+// The empirical sampler encrypts random messages with the IND-CPA scheme
+// of the compiled security level, counts message bit errors and compares
+// the noise of every coefficient with the exact distribution.
+//
+// cargo run --release --example dfr --features hazmat
+// cargo run --release --example dfr --features hazmat -- exact <k> <eta1> <eta2> <du> <dv>
+// cargo run --release --example dfr --features "hazmat kyber1024" -- sample <count>
+
+use std::env;
+
+const Q: i64 = 3329;
+const N: usize = 256;
+
+// A decrypted coefficient is wrong once its noise reaches q/4
+const FAILURE: i64 = (Q + 3) / 4;
+
+// Probabilities below this are dropped from the ends of a distribution
+const NEGLIGIBLE: f64 = 1e-300;
+
+#[derive(Clone, Copy)]
+struct Params {
+    name: &'static str,
+    k: usize,
+    eta1: usize,
+    eta2: usize,
+    du: u32,
+    dv: u32,
+}
+
+const PARAMETER_SETS: [Params; 3] = [
+    Params {
+        name: "kyber512",
+        k: 2,
+        eta1: 3,
+        eta2: 2,
+        du: 10,
+        dv: 4,
+    },
+    Params {
+        name: "kyber768",
+        k: 3,
+        eta1: 2,
+        eta2: 2,
+        du: 10,
+        dv: 4,
+    },
+    Params {
+        name: "kyber1024",
+        k: 4,
+        eta1: 2,
+        eta2: 2,
+        du: 11,
+        dv: 5,
+    },
+];
+
+// Probability distribution over the integers from min
+#[derive(Clone)]
+struct Law {
+    min: i64,
+    p: Vec<f64>,
+}
+
+impl Law {
+    fn max(&self) -> i64 {
+        self.min + self.p.len() as i64 - 1
+    }
+
+    fn get(&self, x: i64) -> f64 {
+        if x < self.min || x > self.max() {
+            0.0
+        } else {
+            self.p[(x - self.min) as usize]
+        }
+    }
+
+    fn from_fn(min: i64, max: i64, f: impl Fn(i64) -> f64) -> Self {
+        let mut law = Law {
+            min,
+            p: (min..=max).map(f).collect(),
+        };
+        law.trim();
+        law
+    }
+
+    fn trim(&mut self) {
+        let start = self.p.iter().position(|&p| p > NEGLIGIBLE).unwrap_or(0);
+        let end = self.p.iter().rposition(|&p| p > NEGLIGIBLE).unwrap_or(0);
+        self.p = self.p[start..=end].to_vec();
+        self.min += start as i64;
+    }
+
+    // Centered binomial distribution with parameter eta
+    fn binomial(eta: usize) -> Self {
+        let n = 2 * eta;
+        let total = (1u64 << n) as f64;
+        let choose = |k: usize| (0..k).fold(1.0, |c, i| c * (n - i) as f64 / (i + 1) as f64);
+        Law::from_fn(-(eta as i64), eta as i64, |x| {
+            choose((eta as i64 + x) as usize) / total
+        })
+    }
+
+    // Error of decompress(compress(x, d)) for x uniform mod q
+    fn rounding(d: u32) -> Self {
+        let mut counts = vec![0u32; Q as usize];
+        for x in 0..Q {
+            let y = (((x << d) + Q / 2) / Q) & ((1 << d) - 1);
+            let z = (y * Q + (1 << (d - 1))) >> d;
+            let e = (z - x).rem_euclid(Q);
+            let e = if e > Q / 2 { e - Q } else { e };
+            counts[(e + Q / 2) as usize] += 1;
+        }
+        Law::from_fn(-Q / 2, Q / 2, |e| {
+            counts[(e + Q / 2) as usize] as f64 / Q as f64
+        })
+    }
+
+    // Distribution of the sum of independent samples
+    fn add(&self, other: &Law) -> Self {
+        let mut p = vec![0.0; self.p.len() + other.p.len() - 1];
+        for (i, a) in self.p.iter().enumerate() {
+            for (j, b) in other.p.iter().enumerate() {
+                p[i + j] += a * b;
+            }
+        }
+        let mut law = Law {
+            min: self.min + other.min,
+            p,
+        };
+        law.trim();
+        law
+    }
+
+    // Distribution of the product of independent samples
+    fn mul(&self, other: &Law) -> Self {
+        let bound = [self.min, self.max()]
+            .iter()
+            .flat_map(|a| [other.min, other.max()].map(|b| (a * b).abs()))
+            .max()
+            .unwrap();
+        let mut p = vec![0.0; 2 * bound as usize + 1];
+        for x in self.min..=self.max() {
+            for y in other.min..=other.max() {
+                p[(x * y + bound) as usize] += self.get(x) * other.get(y);
+            }
+        }
+        let mut law = Law { min: -bound, p };
+        law.trim();
+        law
+    }
+
+    // Distribution of the sum of n independent samples
+    fn sum(&self, mut n: usize) -> Self {
+        let mut result = Law {
+            min: 0,
+            p: vec![1.0],
+        };
+        let mut power = self.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.add(&power);
+            }
+            n >>= 1;
+            if n > 0 {
+                power = power.add(&power);
+            }
+        }
+        result
+    }
+
+    // P(|x| >= t)
+    fn tail(&self, t: i64) -> f64 {
+        let zero = if t <= 0 { self.get(0) } else { 0.0 };
+        zero + (t.max(1)..=self.max().max(-self.min))
+            .map(|x| self.get(x) + self.get(-x))
+            .sum::<f64>()
+    }
+}
+
+// Distribution of the noise in one decrypted coefficient
+fn noise(params: &Params) -> Law {
+    let s = Law::binomial(params.eta1);
+    let e2 = Law::binomial(params.eta2);
+    let e1_cu = e2.add(&Law::rounding(params.du));
+    let e2_cv = e2.add(&Law::rounding(params.dv));
+    let er = s.mul(&s).sum(params.k * N);
+    let se1 = s.mul(&e1_cu).sum(params.k * N);
+    er.add(&se1).add(&e2_cv)
+}
+
+fn exact(params: &Params) {
+    let dfr = N as f64 * noise(params).tail(FAILURE);
+    println!(
+        "{:<10} k={} eta1={} eta2={} du={:<2} dv={}  failure rate 2^{:.1}",
+        params.name,
+        params.k,
+        params.eta1,
+        params.eta2,
+        params.du,
+        params.dv,
+        dfr.log2()
+    );
+}
+
+#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
+fn sample(count: usize) {
+    use pqc_kyber::{indcpa::*, poly::*, polyvec::*, KYBER_K, KYBER_SYMBYTES};
+    use rand::Rng;
+
+    const BUCKETS: usize = 16;
+    const WIDTH: i64 = (FAILURE + BUCKETS as i64 - 1) / BUCKETS as i64;
+    let params = PARAMETER_SETS[KYBER_K - 2];
+    let polyvec_bytes = 384 * KYBER_K;
+    let u_bytes = KYBER_K * N * params.du as usize / 8;
+    let ct_bytes = u_bytes + N * params.dv as usize / 8;
+
+    let mut rng = rand::thread_rng();
+    let mut pk = vec![0u8; polyvec_bytes + KYBER_SYMBYTES];
+    let mut sk = vec![0u8; polyvec_bytes];
+    let mut ct = vec![0u8; ct_bytes];
+    let (mut m, mut decrypted, mut coins) = ([0u8; 32], [0u8; 32], [0u8; 32]);
+    let mut histogram = [0u64; BUCKETS + 1];
+    let (mut bit_errors, mut largest) = (0u64, 0i64);
+
+    for _ in 0..count {
+        indcpa_keypair(&mut pk, &mut sk, None, &mut rng).unwrap();
+        rng.fill(&mut m);
+        rng.fill(&mut coins);
+        indcpa_enc(&mut ct, &m, &pk, &coins);
+        indcpa_dec(&mut decrypted, &ct, &sk);
+        bit_errors += m
+            .iter()
+            .zip(decrypted.iter())
+            .map(|(a, b)| (a ^ b).count_ones() as u64)
+            .sum::<u64>();
+
+        // v - s^T u as in decryption, before decoding to message bits
+        let (mut u, mut s) = (Polyvec::new(), Polyvec::new());
+        let (mut v, mut w) = (Poly::new(), Poly::new());
+        polyvec_decompress(&mut u, &ct[..u_bytes]);
+        poly_decompress(&mut v, &ct[u_bytes..]);
+        polyvec_frombytes(&mut s, &sk);
+        polyvec_ntt(&mut u);
+        polyvec_basemul_acc_montgomery(&mut w, &s, &u);
+        poly_invntt_tomont(&mut w);
+        poly_sub(&mut w, &v);
+        poly_reduce(&mut w);
+        for (i, &c) in w.coeffs.iter().enumerate() {
+            let bit = (m[i / 8] >> (i % 8)) & 1;
+            let e = (c as i64 - bit as i64 * (Q + 1) / 2).rem_euclid(Q);
+            let e = e.min(Q - e);
+            largest = largest.max(e);
+            histogram[((e / WIDTH) as usize).min(BUCKETS)] += 1;
+        }
+    }
+
+    let law = noise(&params);
+    let total = (count * N) as f64;
+    println!(
+        "\n{} samples of {}, {} message bit errors in {} bits",
+        count,
+        params.name,
+        bit_errors,
+        count * N
+    );
+    println!(
+        "Largest noise {}, margin to q/4 of {}\n",
+        largest,
+        FAILURE - 1 - largest
+    );
+    println!(
+        "{:>12} {:>12} {:>12} {:>12}",
+        "|noise|", "count", "observed", "exact"
+    );
+    for (i, &n) in histogram.iter().enumerate() {
+        let lo = i as i64 * WIDTH;
+        let (label, expected) = if i < BUCKETS {
+            let hi = (lo + WIDTH).min(FAILURE);
+            (format!("{}-{}", lo, hi - 1), law.tail(lo) - law.tail(hi))
+        } else {
+            (format!(">={}", FAILURE), law.tail(FAILURE))
+        };
+        println!(
+            "{:>12} {:>12} {:>12.3e} {:>12.3e}",
+            label,
+            n,
+            n as f64 / total,
+            expected
+        );
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+fn sample(_count: usize) {
+    println!("\nThe sampler uses the reference IND-CPA functions, build without avx2");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let numbers: Vec<usize> = args
+        .iter()
+        .skip(1)
+        .map(|a| a.parse().expect("arguments must be numbers"))
+        .collect();
+    match args.first().map(|a| a.as_str()) {
+        None => {
+            PARAMETER_SETS.iter().for_each(exact);
+            sample(10_000);
+        }
+        Some("exact") if numbers.is_empty() => PARAMETER_SETS.iter().for_each(exact),
+        Some("exact") if numbers.len() == 5 => exact(&Params {
+            name: "custom",
+            k: numbers[0],
+            eta1: numbers[1],
+            eta2: numbers[2],
+            du: numbers[3] as u32,
+            dv: numbers[4] as u32,
+        }),
+        Some("sample") => sample(numbers.first().copied().unwrap_or(10_000)),
+        _ => eprintln!("usage: dfr [exact [k eta1 eta2 du dv] | sample [count]]"),
+    }
+}
@@ -10,7 +10,7 @@ fn main() -> Result<(), KyberError> {
             if data.len() != CTBYTES + SKBYTES {
                 return;
             };
-            match crypto_kem_dec(&mut ss, &data[..CTBYTES], &data[CTBYTES..SKBYTES]) {
+            match crypto_kem_dec(&mut ss, &data[..CTBYTES], &data[CTBYTES..]) {
                 Ok(_) => (),
                 Err(_) => (),
             }
@@ -0,0 +1,10 @@
+use honggfuzz::fuzz;
+use kyber_fuzz::diff;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            diff::rej_uniform(data);
+        });
+    }
+}
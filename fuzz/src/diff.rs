@@ -0,0 +1,217 @@
+//! Differential checks between the avx2 backend of pqc_kyber and the
+//! reference copy in this crate. Each takes raw fuzzer input, returns early
+//! if it is too short, and panics on any divergence so the fuzzer reports it
+//! as a crash.
+use crate::{indcpa, kem, params::*, poly, polyvec, symmetric::XOF_BLOCKBYTES};
+use pqc_kyber::{poly as avx, polyvec as avxvec, rejsample};
+
+// 2^16 mod q, invntt_tomont(ntt(a)) is a times this
+const MONT: i32 = 2285;
+
+// Canonical representatives, the backends may leave different ones
+fn canonical(coeffs: &[i16]) -> Vec<i16> {
+    coeffs
+        .iter()
+        .map(|&c| (c as i32).rem_euclid(KYBER_Q as i32) as i16)
+        .collect()
+}
+
+// Packed 12-bit values
+fn unpack12(data: &[u8], n: usize) -> Vec<u16> {
+    (0..n)
+        .map(|i| {
+            let b = &data[3 * (i / 2)..];
+            if i % 2 == 0 {
+                b[0] as u16 | (b[1] as u16 & 0xf) << 8
+            } else {
+                (b[1] >> 4) as u16 | (b[2] as u16) << 4
+            }
+        })
+        .collect()
+}
+
+// Coefficients below q from packed 12-bit values
+fn coeffs(data: &[u8]) -> [i16; KYBER_N] {
+    let mut r = [0i16; KYBER_N];
+    for (c, v) in r.iter_mut().zip(unpack12(data, KYBER_N)) {
+        *c = (v % KYBER_Q as u16) as i16;
+    }
+    r
+}
+
+fn avx_poly(coeffs: [i16; KYBER_N]) -> avx::Poly {
+    avx::Poly { coeffs }
+}
+
+fn avx_coeffs(p: &avx::Poly) -> [i16; KYBER_N] {
+    unsafe { p.coeffs }
+}
+
+/// Decompresses arbitrary ciphertext bytes and compresses the result, then
+/// compresses arbitrary coefficients and decodes them as a message
+pub fn compress(data: &[u8]) {
+    const U: usize = KYBER_POLYVECCOMPRESSEDBYTES;
+    const V: usize = KYBER_POLYCOMPRESSEDBYTES;
+    if data.len() < U + V + KYBER_POLYBYTES {
+        return;
+    }
+    let (u, rest) = data.split_at(U);
+    let (v, rest) = rest.split_at(V);
+
+    let mut r = polyvec::Polyvec::new();
+    let mut a = avxvec::Polyvec::new();
+    polyvec::polyvec_decompress(&mut r, u);
+    unsafe { avxvec::polyvec_decompress(&mut a, u) };
+    for i in 0..KYBER_K {
+        assert_eq!(
+            canonical(&r.vec[i].coeffs),
+            canonical(&avx_coeffs(&a.vec[i])),
+            "polyvec_decompress"
+        );
+    }
+    let (mut rb, mut ab) = ([0u8; U], [0u8; U]);
+    polyvec::polyvec_compress(&mut rb, r);
+    unsafe { avxvec::polyvec_compress(&mut ab, &a) };
+    assert_eq!(rb[..], ab[..], "polyvec_compress");
+
+    let mut r = poly::Poly::new();
+    let mut a = avx::Poly::new();
+    poly::poly_decompress(&mut r, v);
+    unsafe { avx::poly_decompress(&mut a, v) };
+    assert_eq!(
+        canonical(&r.coeffs),
+        canonical(&avx_coeffs(&a)),
+        "poly_decompress"
+    );
+    let (mut rb, mut ab) = ([0u8; V], [0u8; V]);
+    poly::poly_compress(&mut rb, r);
+    unsafe { avx::poly_compress(&mut ab, a) };
+    assert_eq!(rb, ab, "poly_compress");
+
+    let c = coeffs(rest);
+    let r = poly::Poly { coeffs: c };
+    let a = avx_poly(c);
+    let (mut rb, mut ab) = ([0u8; V], [0u8; V]);
+    poly::poly_compress(&mut rb, r);
+    unsafe { avx::poly_compress(&mut ab, a) };
+    assert_eq!(rb, ab, "poly_compress of arbitrary coefficients");
+    let (mut rm, mut am) = ([0u8; KYBER_SYMBYTES], [0u8; KYBER_SYMBYTES]);
+    poly::poly_tomsg(&mut rm, r);
+    avx::poly_tomsg(&mut am, a);
+    assert_eq!(rm, am, "poly_tomsg");
+}
+
+/// Deserializes arbitrary bytes as a polynomial vector, as done for keys,
+/// and serializes it again. Values of q and above are reduced by the avx2
+/// code and kept by the reference code, so the results are compared mod q
+/// and only canonical inputs must round trip exactly.
+pub fn frombytes(data: &[u8]) {
+    if data.len() < KYBER_POLYVECBYTES {
+        return;
+    }
+    let mut r = polyvec::Polyvec::new();
+    let mut a = avxvec::Polyvec::new();
+    polyvec::polyvec_frombytes(&mut r, data);
+    unsafe { avxvec::polyvec_frombytes(&mut a, data) };
+    let (mut rb, mut ab) = ([0u8; KYBER_POLYVECBYTES], [0u8; KYBER_POLYVECBYTES]);
+    polyvec::polyvec_tobytes(&mut rb, &r);
+    avxvec::polyvec_tobytes(&mut ab, &a);
+
+    const N: usize = KYBER_K * KYBER_N;
+    let (rv, av) = (unpack12(&rb, N), unpack12(&ab, N));
+    for (x, y) in rv.iter().zip(&av) {
+        assert_eq!(x % KYBER_Q as u16, y % KYBER_Q as u16, "polyvec_frombytes");
+    }
+    if unpack12(data, N).iter().all(|&v| v < KYBER_Q as u16) {
+        assert_eq!(rb[..], data[..KYBER_POLYVECBYTES], "reference round trip");
+        assert_eq!(ab[..], data[..KYBER_POLYVECBYTES], "avx2 round trip");
+    }
+}
+
+/// Forward and inverse NTT round trips and pointwise multiplication. The
+/// avx2 NTT order differs, serializing undoes it.
+pub fn ntt(data: &[u8]) {
+    if data.len() < 2 * KYBER_POLYBYTES {
+        return;
+    }
+    let (c, d) = (coeffs(data), coeffs(&data[KYBER_POLYBYTES..]));
+    let (mut r, mut s) = (poly::Poly { coeffs: c }, poly::Poly { coeffs: d });
+    let (mut a, mut b) = (avx_poly(c), avx_poly(d));
+    poly::poly_ntt(&mut r);
+    poly::poly_ntt(&mut s);
+    avx::poly_ntt(&mut a);
+    avx::poly_ntt(&mut b);
+    poly::poly_reduce(&mut r);
+    avx::poly_reduce(&mut a);
+    let (mut rb, mut ab) = ([0u8; KYBER_POLYBYTES], [0u8; KYBER_POLYBYTES]);
+    poly::poly_tobytes(&mut rb, r);
+    avx::poly_tobytes(&mut ab, a);
+    assert_eq!(rb[..], ab[..], "poly_ntt");
+
+    let mut rp = poly::Poly::new();
+    let mut ap = avx::Poly::new();
+    poly::poly_basemul(&mut rp, &r, &s);
+    avx::poly_basemul(&mut ap, &a, &b);
+    poly::poly_reduce(&mut rp);
+    avx::poly_reduce(&mut ap);
+    poly::poly_tobytes(&mut rb, rp);
+    avx::poly_tobytes(&mut ab, ap);
+    assert_eq!(rb[..], ab[..], "poly_basemul");
+
+    poly::poly_invntt_tomont(&mut r);
+    avx::poly_invntt_tomont(&mut a);
+    let expected: Vec<i16> = c
+        .iter()
+        .map(|&x| (x as i32 * MONT % KYBER_Q as i32) as i16)
+        .collect();
+    assert_eq!(canonical(&r.coeffs), expected, "reference ntt round trip");
+    assert_eq!(canonical(&avx_coeffs(&a)), expected, "avx2 ntt round trip");
+}
+
+/// Rejection sampling of matrix coefficients from one XOF output buffer
+pub fn rej_uniform(data: &[u8]) {
+    const BUFLEN: usize = rejsample::REJ_UNIFORM_AVX_NBLOCKS * XOF_BLOCKBYTES;
+    if data.len() < BUFLEN {
+        return;
+    }
+    // The avx2 code loads 32 bytes at a time past its read position
+    let mut buf = [0u8; BUFLEN + 32];
+    buf[..BUFLEN].copy_from_slice(&data[..BUFLEN]);
+    let (mut r, mut a) = ([0i16; KYBER_N], [0i16; KYBER_N]);
+    let n = indcpa::rej_uniform(&mut r, KYBER_N, &buf, BUFLEN);
+    let m = unsafe { rejsample::rej_uniform_avx(&mut a, &buf) };
+    assert_eq!(n, m, "rej_uniform count");
+    assert_eq!(r[..n], a[..n], "rej_uniform");
+}
+
+/// Keypair, encapsulation and decapsulation from the same seeds, then
+/// decapsulation of an arbitrary ciphertext
+pub fn kem(data: &[u8]) {
+    const SEEDS: usize = 3 * KYBER_SYMBYTES;
+    if data.len() < SEEDS + KYBER_CIPHERTEXTBYTES {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let (d, z, coins) = (&data[..32], &data[32..64], &data[64..96]);
+    let fuzzed = &data[SEEDS..][..KYBER_CIPHERTEXTBYTES];
+
+    let (mut rpk, mut apk) = ([0u8; KYBER_PUBLICKEYBYTES], [0u8; KYBER_PUBLICKEYBYTES]);
+    let (mut rsk, mut ask) = ([0u8; KYBER_SECRETKEYBYTES], [0u8; KYBER_SECRETKEYBYTES]);
+    kem::crypto_kem_keypair(&mut rpk, &mut rsk, &mut rng, Some((d, z))).unwrap();
+    pqc_kyber::crypto_kem_keypair(&mut apk, &mut ask, &mut rng, Some((d, z))).unwrap();
+    assert_eq!(rpk[..], apk[..], "public key");
+    assert_eq!(rsk[..], ask[..], "secret key");
+
+    let (mut rct, mut act) = ([0u8; KYBER_CIPHERTEXTBYTES], [0u8; KYBER_CIPHERTEXTBYTES]);
+    let (mut rss, mut ass) = ([0u8; KYBER_SSBYTES], [0u8; KYBER_SSBYTES]);
+    kem::crypto_kem_enc(&mut rct, &mut rss, &rpk, &mut rng, Some(coins)).unwrap();
+    pqc_kyber::crypto_kem_enc(&mut act, &mut ass, &apk, &mut rng, Some(coins)).unwrap();
+    assert_eq!(rct[..], act[..], "ciphertext");
+    assert_eq!(rss, ass, "encapsulated secret");
+
+    for ct in [&rct[..], fuzzed] {
+        kem::crypto_kem_dec(&mut rss, ct, &rsk).unwrap();
+        pqc_kyber::crypto_kem_dec(&mut ass, ct, &ask).unwrap();
+        assert_eq!(rss, ass, "decapsulated secret");
+    }
+}
@@ -0,0 +1,13 @@
+//! A second build of the reference backend for the differential targets.
+//!
+//! Only one backend can be compiled into pqc_kyber at a time, so the
+//! reference sources are included from tests/reference, shared with the
+//! avx2 tests, and compared against an avx2 build of pqc_kyber in [diff].
+#![allow(unexpected_cfgs)]
+
+#[path = "../../tests/reference/mod.rs"]
+mod reference;
+use reference::*;
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+pub mod diff;
@@ -0,0 +1,114 @@
+#ifndef PQC_KYBER_H
+#define PQC_KYBER_H
+
+/* Generated by cbindgen from src/ffi.rs and src/oqs.rs, do not edit */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Layout compatible with liboqs' `OQS_KEM`
+typedef struct PQC_KYBER_OQS_KEM {
+  // Printable name of the scheme
+  const char *method_name;
+  // Version of the implementation
+  const char *alg_version;
+  // NIST security category, 1, 3 or 5
+  uint8_t claimed_nist_level;
+  // Whether the scheme is IND-CCA secure
+  bool ind_cca;
+  // Public key length in bytes
+  size_t length_public_key;
+  // Secret key length in bytes
+  size_t length_secret_key;
+  // Ciphertext length in bytes
+  size_t length_ciphertext;
+  // Shared secret length in bytes
+  size_t length_shared_secret;
+  // Keypair generation, [`pqc_kyber_keypair`]
+  int (*keypair)(uint8_t*, uint8_t*);
+  // Encapsulation, [`pqc_kyber_enc`]
+  int (*encaps)(uint8_t*, uint8_t*, const uint8_t*);
+  // Decapsulation, [`pqc_kyber_dec`]
+  int (*decaps)(uint8_t*, const uint8_t*, const uint8_t*);
+} PQC_KYBER_OQS_KEM;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Public key length in bytes
+extern const size_t PQC_KYBER_PUBLICKEYBYTES;
+
+// Secret key length in bytes
+extern const size_t PQC_KYBER_SECRETKEYBYTES;
+
+// Ciphertext length in bytes
+extern const size_t PQC_KYBER_CIPHERTEXTBYTES;
+
+// Shared secret length in bytes
+extern const size_t PQC_KYBER_SSBYTES;
+
+// Seed length in bytes for `pqc_kyber_derive`
+extern const size_t PQC_KYBER_SEEDBYTES;
+
+// Encapsulation coins length in bytes for `pqc_kyber_enc_derand`
+extern const size_t PQC_KYBER_ENCCOINBYTES;
+
+// Generates a keypair.
+//
+// # Safety
+//
+// `pk` and `sk` must be valid for writes of `PQC_KYBER_PUBLICKEYBYTES` and
+// `PQC_KYBER_SECRETKEYBYTES` bytes.
+int pqc_kyber_keypair(uint8_t *pk, uint8_t *sk);
+
+// Deterministically derives a keypair from a 64 byte seed, the first half
+// seeds the key generation and the second half the implicit rejection value.
+//
+// # Safety
+//
+// `seed` must be valid for reads of `PQC_KYBER_SEEDBYTES` bytes, `pk` and
+// `sk` as for [`pqc_kyber_keypair`].
+int pqc_kyber_derive(uint8_t *pk, uint8_t *sk, const uint8_t *seed);
+
+// Encapsulates a shared secret to a public key.
+//
+// # Safety
+//
+// `ct` and `ss` must be valid for writes of `PQC_KYBER_CIPHERTEXTBYTES` and
+// `PQC_KYBER_SSBYTES` bytes, `pk` for reads of `PQC_KYBER_PUBLICKEYBYTES`.
+int pqc_kyber_enc(uint8_t *ct, uint8_t *ss, const uint8_t *pk);
+
+// Encapsulates with caller supplied randomness, for known answer tests.
+//
+// # Safety
+//
+// `coins` must be valid for reads of `PQC_KYBER_ENCCOINBYTES` bytes, the
+// other arguments as for [`pqc_kyber_enc`].
+int pqc_kyber_enc_derand(uint8_t *ct, uint8_t *ss, const uint8_t *pk, const uint8_t *coins);
+
+// Decapsulates a shared secret. Invalid ciphertexts are implicitly rejected,
+// returning 0 with a pseudorandom shared secret.
+// Built with the `fault-hardened` feature a detected fault zeroes `ss` and
+// returns -1.
+//
+// # Safety
+//
+// `ss` must be valid for writes of `PQC_KYBER_SSBYTES` bytes, `ct` and `sk`
+// for reads of `PQC_KYBER_CIPHERTEXTBYTES` and `PQC_KYBER_SECRETKEYBYTES`.
+int pqc_kyber_dec(uint8_t *ss, const uint8_t *ct, const uint8_t *sk);
+
+// Fills `kem` with this library's implementation. Returns 0 on success and
+// -1 if `kem` is null.
+//
+// # Safety
+//
+// `kem` must be valid for writes of an `OQS_KEM`.
+int pqc_kyber_oqs_kem_init(struct PQC_KYBER_OQS_KEM *kem);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PQC_KYBER_H */
@@ -5,6 +5,19 @@
 */
 export function keypair(): Keys;
 /**
+* Deterministically derives a keypair from a 64 byte seed
+* @param {Uint8Array} seed
+* @returns {Keys}
+*/
+export function derive(seed: Uint8Array): Keys;
+/**
+* Extracts the public key from a secret key, exported as `publicKey` as
+* `public` is reserved in JavaScript
+* @param {Uint8Array} sk
+* @returns {Uint8Array}
+*/
+export function publicKey(sk: Uint8Array): Uint8Array;
+/**
 * @param {Uint8Array} pk
 * @returns {Kex}
 */
@@ -16,6 +29,86 @@ export function encapsulate(pk: Uint8Array): Kex;
 */
 export function decapsulate(ct: Uint8Array, sk: Uint8Array): Uint8Array;
 /**
+* Generates a keypair, resolving to a [`KyberKeyPair`]. `algorithm` is a
+* name or `{ name }` dictionary and `keyUsages` is split between the two keys.
+* @param {any} algorithm
+* @param {boolean} extractable
+* @param {Array<any>} key_usages
+* @returns {Promise<any>}
+*/
+export function generateKey(algorithm: any, extractable: boolean, key_usages: Array<any>): Promise<any>;
+/**
+* Encapsulates a shared secret to a public key, resolving to
+* `{ sharedKey, ciphertext }` as `ArrayBuffer`s
+* @param {any} algorithm
+* @param {KyberKey} key
+* @returns {Promise<any>}
+*/
+export function encapsulateBits(algorithm: any, key: KyberKey): Promise<any>;
+/**
+* Decapsulates the shared secret from a ciphertext, resolving to an
+* `ArrayBuffer`
+* @param {any} algorithm
+* @param {KyberKey} key
+* @param {any} ciphertext
+* @returns {Promise<any>}
+*/
+export function decapsulateBits(algorithm: any, key: KyberKey, ciphertext: any): Promise<any>;
+/**
+* Exports a key as `"raw"` (public keys) or `"jwk"` (either). Raw keys
+* resolve to an `ArrayBuffer`, jwk to an object.
+* @param {string} format
+* @param {KyberKey} key
+* @returns {Promise<any>}
+*/
+export function exportKey(format: string, key: KyberKey): Promise<any>;
+/**
+* Imports a key exported by [`export_key`], resolving to a [`KyberKey`].
+* `keyData` is a `BufferSource` for raw keys or an object for jwk.
+* @param {string} format
+* @param {any} key_data
+* @param {any} algorithm
+* @param {boolean} extractable
+* @param {Array<any>} key_usages
+* @returns {Promise<any>}
+*/
+export function importKey(format: string, key_data: any, algorithm: any, extractable: boolean, key_usages: Array<any>): Promise<any>;
+/**
+* Mutually authenticated key exchange
+*/
+export class Ake {
+  free(): void;
+/**
+*/
+  constructor();
+/**
+* Starts the exchange with the server's public key
+* @param {Uint8Array} serverPublicKey
+* @returns {Uint8Array}
+*/
+  clientInit(serverPublicKey: Uint8Array): Uint8Array;
+/**
+* Handles the client's initiation with the client's public key and the
+* server's secret key
+* @param {Uint8Array} clientInit
+* @param {Uint8Array} clientPublicKey
+* @param {Uint8Array} serverSecretKey
+* @returns {Uint8Array}
+*/
+  serverReceive(clientInit: Uint8Array, clientPublicKey: Uint8Array, serverSecretKey: Uint8Array): Uint8Array;
+/**
+* Completes the exchange with the server's response and the client's
+* secret key
+* @param {Uint8Array} serverResponse
+* @param {Uint8Array} clientSecretKey
+*/
+  clientConfirm(serverResponse: Uint8Array, clientSecretKey: Uint8Array): void;
+/**
+* The shared secret, set once the exchange completes
+*/
+  readonly sharedSecret: Uint8Array;
+}
+/**
 */
 export class Kex {
   free(): void;
@@ -45,10 +138,48 @@ export class Keys {
   readonly secret: Uint8Array;
 }
 /**
+* Public or private half of a keypair, shaped like a WebCrypto `CryptoKey`
+*/
+export class KyberKey {
+  free(): void;
+/**
+* A `{ name }` algorithm dictionary
+*/
+  readonly algorithm: object;
+/**
+* Whether the key may be exported, public keys always are
+*/
+  readonly extractable: boolean;
+/**
+* Either `"public"` or `"private"`
+*/
+  readonly type: string;
+/**
+* Operations the key may be used for
+*/
+  readonly usages: Array<any>;
+}
+/**
+* The result of [`generate_key`], shaped like a WebCrypto `CryptoKeyPair`
+*/
+export class KyberKeyPair {
+  free(): void;
+/**
+*/
+  readonly privateKey: KyberKey;
+/**
+*/
+  readonly publicKey: KyberKey;
+}
+/**
 */
 export class Params {
   free(): void;
 /**
+* Algorithm name for `generateKey` and the other promise functions
+*/
+  static readonly algorithm: string;
+/**
 */
   static readonly ciphertextBytes: number;
 /**
@@ -61,3 +192,34 @@ export class Params {
 */
   static readonly sharedSecretBytes: number;
 }
+/**
+* Unilaterally authenticated key exchange
+*/
+export class Uake {
+  free(): void;
+/**
+*/
+  constructor();
+/**
+* Starts the exchange with the server's public key
+* @param {Uint8Array} serverPublicKey
+* @returns {Uint8Array}
+*/
+  clientInit(serverPublicKey: Uint8Array): Uint8Array;
+/**
+* Handles the client's initiation with the server's secret key
+* @param {Uint8Array} clientInit
+* @param {Uint8Array} serverSecretKey
+* @returns {Uint8Array}
+*/
+  serverReceive(clientInit: Uint8Array, serverSecretKey: Uint8Array): Uint8Array;
+/**
+* Completes the exchange with the server's response
+* @param {Uint8Array} serverResponse
+*/
+  clientConfirm(serverResponse: Uint8Array): void;
+/**
+* The shared secret, set once the exchange completes
+*/
+  readonly sharedSecret: Uint8Array;
+}
@@ -0,0 +1,99 @@
+import pytest
+
+import pqc_kyber as kyber
+
+
+def test_keypair_sizes():
+    pk, sk = kyber.keypair()
+    assert isinstance(pk, bytes) and isinstance(sk, bytes)
+    assert len(pk) == kyber.PUBLICKEYBYTES
+    assert len(sk) == kyber.SECRETKEYBYTES
+    assert kyber.public(sk) == pk
+
+
+def test_encapsulate_decapsulate():
+    pk, sk = kyber.keypair()
+    ct, ss = kyber.encapsulate(pk)
+    assert len(ct) == kyber.CIPHERTEXTBYTES
+    assert len(ss) == kyber.SSBYTES
+    assert kyber.decapsulate(ct, sk) == ss
+
+
+# Modified ciphertexts are implicitly rejected
+def test_decapsulate_modified_ciphertext():
+    pk, sk = kyber.keypair()
+    ct, ss = kyber.encapsulate(pk)
+    bad = bytes([ct[0] ^ 1]) + ct[1:]
+    assert kyber.decapsulate(bad, sk) != ss
+
+
+def test_derive_is_deterministic():
+    seed = bytes(range(64))
+    assert kyber.derive(seed) == kyber.derive(seed)
+    assert kyber.derive(seed) != kyber.derive(bytes(64))
+
+
+def test_invalid_input():
+    pk, sk = kyber.keypair()
+    with pytest.raises(kyber.InvalidInput):
+        kyber.encapsulate(pk[:-1])
+    with pytest.raises(kyber.InvalidInput):
+        kyber.decapsulate(b"", sk)
+    with pytest.raises(kyber.InvalidInput):
+        kyber.derive(b"short seed")
+    with pytest.raises(kyber.InvalidInput):
+        kyber.public(pk)
+
+
+def test_exception_hierarchy():
+    for name in [
+        "InvalidInput",
+        "Decapsulation",
+        "RandomBytesGeneration",
+        "ParameterMismatch",
+        "PrekeyUnavailable",
+        "InvalidState",
+        "StateExpired",
+        "FaultDetected",
+    ]:
+        assert issubclass(getattr(kyber, name), kyber.KyberError)
+    assert issubclass(kyber.KyberError, Exception)
+
+
+def test_uake():
+    server_pk, server_sk = kyber.keypair()
+    alice, bob = kyber.Uake(), kyber.Uake()
+    client_init = alice.client_init(server_pk)
+    assert len(client_init) == kyber.UAKE_INIT_BYTES
+    server_send = bob.server_receive(client_init, server_sk)
+    alice.client_confirm(server_send)
+    assert alice.shared_secret == bob.shared_secret
+    assert alice.shared_secret != bytes(kyber.SSBYTES)
+
+
+def test_ake():
+    client_pk, client_sk = kyber.keypair()
+    server_pk, server_sk = kyber.keypair()
+    alice, bob = kyber.Ake(), kyber.Ake()
+    client_init = alice.client_init(server_pk)
+    assert len(client_init) == kyber.AKE_INIT_BYTES
+    server_send = bob.server_receive(client_init, client_pk, server_sk)
+    alice.client_confirm(server_send, client_sk)
+    assert alice.shared_secret == bob.shared_secret
+
+
+# The initiation header carries the version and security level
+def test_parameter_mismatch():
+    server_pk, server_sk = kyber.keypair()
+    client_init = bytearray(kyber.Uake().client_init(server_pk))
+    client_init[1] ^= 0xFF
+    with pytest.raises(kyber.ParameterMismatch):
+        kyber.Uake().server_receive(bytes(client_init), server_sk)
+
+
+def test_wrong_lengths_in_key_exchange():
+    server_pk, server_sk = kyber.keypair()
+    with pytest.raises(kyber.InvalidInput):
+        kyber.Uake().client_init(server_pk + b"\x00")
+    with pytest.raises(kyber.InvalidInput):
+        kyber.Ake().server_receive(b"\x00", server_pk, server_sk)
@@ -3,6 +3,8 @@ use crate::{
     kem::*,
     kex::{Decapsulated, Encapsulated, PublicKey, SecretKey},
     params::*,
+    symmetric::hash_h,
+    verify::verify,
     CryptoRng, RngCore,
 };
 
@@ -70,19 +72,29 @@ pub fn decapsulate(ct: &[u8], sk: &[u8]) -> Decapsulated {
         return Err(KyberError::InvalidInput);
     }
     let mut ss = [0u8; KYBER_SSBYTES];
-    crypto_kem_dec(&mut ss, ct, sk);
+    crypto_kem_dec(&mut ss, ct, sk)?;
     Ok(ss)
 }
 
 /// A public/secret keypair for use with Kyber.
 ///
 /// Byte lengths of the keys are determined by the security level chosen.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// The secret key is omitted from the `Debug` output.
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Keypair {
     pub public: PublicKey,
     pub secret: SecretKey,
 }
 
+impl core::fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Keypair")
+            .field("public", &self.public)
+            .field("secret", &"[REDACTED]")
+            .finish()
+    }
+}
+
 impl Keypair {
     /// Securely generates a new keypair`
     /// ```
@@ -144,3 +156,68 @@ pub fn public(sk: &[u8]) -> PublicKey {
     );
     pk
 }
+
+/// Checks a public key from an untrusted source before encapsulating to it.
+///
+/// Fails with `InvalidInput` if the key is the wrong length or any encoded
+/// coefficient is q or above, the modulus check of FIPS 203. Keys from
+/// [keypair] always pass.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let mut keys = keypair(&mut rng)?;
+/// check_public_key(&keys.public)?;
+/// keys.public[..2].copy_from_slice(&[0xff, 0x0f]);
+/// assert_eq!(check_public_key(&keys.public), Err(KyberError::InvalidInput));
+/// # Ok(())}
+/// ```
+pub fn check_public_key(pk: &[u8]) -> Result<(), KyberError> {
+    if pk.len() != KYBER_PUBLICKEYBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    for b in pk[..KYBER_POLYVECBYTES].chunks_exact(3) {
+        let c0 = b[0] as usize | (b[1] as usize & 0xf) << 8;
+        let c1 = (b[1] >> 4) as usize | (b[2] as usize) << 4;
+        if c0 >= KYBER_Q || c1 >= KYBER_Q {
+            return Err(KyberError::InvalidInput);
+        }
+    }
+    Ok(())
+}
+
+/// Checks a secret key from an untrusted source before decapsulating with it.
+///
+/// Fails with `InvalidInput` if the key is the wrong length or the stored
+/// hash of the public key doesn't match the public key it contains, the
+/// hash check of FIPS 203.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let mut keys = keypair(&mut rng)?;
+/// check_secret_key(&keys.secret)?;
+/// keys.secret[KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES] ^= 1;
+/// assert_eq!(check_secret_key(&keys.secret), Err(KyberError::InvalidInput));
+/// # Ok(())}
+/// ```
+pub fn check_secret_key(sk: &[u8]) -> Result<(), KyberError> {
+    const PK_END: usize = KYBER_INDCPA_SECRETKEYBYTES + KYBER_PUBLICKEYBYTES;
+    if sk.len() != KYBER_SECRETKEYBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut hpk = [0u8; KYBER_SYMBYTES];
+    hash_h(
+        &mut hpk,
+        &sk[KYBER_INDCPA_SECRETKEYBYTES..],
+        KYBER_PUBLICKEYBYTES,
+    );
+    if verify(&hpk, &sk[PK_END..][..KYBER_SYMBYTES], KYBER_SYMBYTES) != 0 {
+        return Err(KyberError::InvalidInput);
+    }
+    Ok(())
+}
@@ -19,6 +19,7 @@ impl Polyvec {
 pub unsafe fn poly_compress10(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1, mut f2);
     let (mut t0, mut t1);
+    let mut tmp;
 
     let v = _mm256_load_si256(QDATA.vec[_16XV / 16..].as_ptr());
     let v8 = _mm256_slli_epi16(v, 3);
@@ -52,7 +53,8 @@ pub unsafe fn poly_compress10(r: &mut [u8], a: &Poly) {
         t1 = _mm256_extracti128_si256(f0, 1);
         t0 = _mm_blend_epi16(t0, t1, 0xE0);
         _mm_storeu_si128(r[20 * i..].as_mut_ptr() as *mut __m128i, t0);
-        _mm_storeu_si128(r[20 * i + 16..].as_mut_ptr() as *mut __m128i, t1);
+        tmp = _mm_cvtsi128_si32(t1);
+        r[20 * i + 16..20 * i + 20].copy_from_slice(&tmp.to_le_bytes());
     }
 }
 
@@ -80,6 +82,7 @@ pub unsafe fn poly_decompress10(r: &mut Poly, a: &[u8]) {
 pub unsafe fn poly_compress11(r: &mut [u8], a: &Poly) {
     let (mut f0, mut f1, mut f2);
     let (mut t0, mut t1);
+    let mut tmp;
     let v = _mm256_load_si256(QDATA.vec[_16XV / 16..].as_ptr());
     let v8 = _mm256_slli_epi16(v, 3);
     let off = _mm256_set1_epi16(36);
@@ -116,7 +119,8 @@ pub unsafe fn poly_compress11(r: &mut [u8], a: &Poly) {
         t1 = _mm256_extracti128_si256(f0, 1);
         t0 = _mm_blendv_epi8(t0, t1, _mm256_castsi256_si128(shufbidx));
         _mm_storeu_si128(r[22 * i + 0..].as_mut_ptr() as *mut __m128i, t0);
-        _mm_storel_epi64(r[22 * i + 16..].as_mut_ptr() as *mut __m128i, t1);
+        tmp = _mm_cvtsi128_si64(t1);
+        r[22 * i + 16..22 * i + 22].copy_from_slice(&tmp.to_le_bytes()[..6]);
     }
 }
 
@@ -24,7 +24,7 @@ pub fn verify(a: &[u8], b: &[u8], mut len: usize) -> u8 {
 pub fn cmov(r: &mut [u8], x: &[u8], mut len: usize, mut b: u8) {
     let (mut xvec, mut rvec);
     unsafe {
-        let bvec = _mm256_set1_epi64x(b as i64);
+        let bvec = _mm256_set1_epi64x(-(b as i64));
         for i in 0..(len / 32) {
             rvec = _mm256_loadu_si256(r[32 * i..].as_ptr() as *const __m256i);
             xvec = _mm256_loadu_si256(x[32 * i..].as_ptr() as *const __m256i);
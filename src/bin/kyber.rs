@@ -0,0 +1,510 @@
+//! Command-line key management for Kyber.
+//!
+//! Keys, ciphertexts and shared secrets are read and written as raw bytes,
+//! hex, base64 or PEM. The security level is fixed at compile time, `--level`
+//! is checked against it so scripts fail loudly when run against the wrong
+//! build.
+//!
+//! Files are encrypted by encapsulating to the recipient's public key and
+//! using the shared secret as a ChaCha20-Poly1305 key. Each file gets a fresh
+//! key so a fixed nonce is used. The encrypted layout is:
+//!
+//! `MAGIC || KEX_HEADER || ciphertext || AEAD ciphertext and tag`
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pqc_kyber::*;
+use rand::rngs::OsRng;
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+const MAGIC: &[u8; 4] = b"KYBR";
+const FILE_HEADER_BYTES: usize = MAGIC.len() + KEX_HEADER_BYTES;
+const SEED_BYTES: usize = 64;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "kyber",
+    version,
+    about = "Kyber key generation, encapsulation and file encryption"
+)]
+struct Cli {
+    /// Security level, must match the level this binary was built with
+    #[arg(long, global = true)]
+    level: Option<u16>,
+    /// Encoding of keys, ciphertexts and shared secrets
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Pem)]
+    format: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Raw,
+    Hex,
+    Base64,
+    Pem,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new keypair
+    Keygen(KeyOut),
+    /// Deterministically derive a keypair from a 64 byte seed
+    Derive {
+        /// Seed file
+        #[arg(long)]
+        seed: PathBuf,
+        #[command(flatten)]
+        out: KeyOut,
+    },
+    /// Extract the public key from a secret key
+    Public {
+        /// Secret key file
+        #[arg(long)]
+        secret: PathBuf,
+        /// Public key output, `-` for stdout
+        #[arg(long, default_value = "-")]
+        out: PathBuf,
+    },
+    /// Encapsulate a shared secret to a public key
+    Encapsulate {
+        /// Public key file
+        #[arg(long)]
+        public: PathBuf,
+        /// Ciphertext output
+        #[arg(long)]
+        ciphertext: PathBuf,
+        /// Shared secret output, `-` for stdout
+        #[arg(long, default_value = "-")]
+        shared_secret: PathBuf,
+    },
+    /// Decapsulate a shared secret from a ciphertext
+    Decapsulate {
+        /// Secret key file
+        #[arg(long)]
+        secret: PathBuf,
+        /// Ciphertext file
+        #[arg(long)]
+        ciphertext: PathBuf,
+        /// Shared secret output, `-` for stdout
+        #[arg(long, default_value = "-")]
+        shared_secret: PathBuf,
+    },
+    /// Encrypt a file to a public key
+    Encrypt {
+        /// Recipient's public key file
+        #[arg(long)]
+        public: PathBuf,
+        #[command(flatten)]
+        io: FileIo,
+    },
+    /// Decrypt a file with a secret key
+    Decrypt {
+        /// Secret key file
+        #[arg(long)]
+        secret: PathBuf,
+        #[command(flatten)]
+        io: FileIo,
+    },
+    /// Check this build against a test vector file from the C reference implementation
+    VerifyKat {
+        /// Test vector file, eg. tests/KAT/tvecs768
+        file: PathBuf,
+        /// Only check the first COUNT vectors
+        #[arg(long)]
+        count: Option<usize>,
+    },
+}
+
+#[derive(Args)]
+struct KeyOut {
+    /// Public key output
+    #[arg(long)]
+    public: PathBuf,
+    /// Secret key output
+    #[arg(long)]
+    secret: PathBuf,
+}
+
+#[derive(Args)]
+struct FileIo {
+    /// Input file, `-` for stdin
+    #[arg(long = "in", default_value = "-")]
+    input: PathBuf,
+    /// Output file, `-` for stdout
+    #[arg(long = "out", default_value = "-")]
+    output: PathBuf,
+}
+
+#[derive(Debug)]
+struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for CliError {}
+
+fn fail<T>(msg: impl Into<String>) -> CliResult<T> {
+    Err(Box::new(CliError(msg.into())))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let built = 256 * KYBER_K as u16;
+    if let Some(level) = cli.level {
+        if level != built {
+            return fail(format!(
+                "this build only supports Kyber{}, rebuild with the `kyber{}` feature",
+                built, level
+            ));
+        }
+    }
+    let fmt = cli.format;
+    match cli.command {
+        Command::Keygen(out) => write_keys(fmt, &out, &keypair(&mut OsRng)?),
+        Command::Derive { seed, out } => {
+            let seed = read(fmt, &seed, Kind::Seed)?;
+            write_keys(fmt, &out, &derive(&seed)?)
+        }
+        Command::Public { secret, out } => {
+            let sk = read(fmt, &secret, Kind::SecretKey)?;
+            write(fmt, &out, Kind::PublicKey, &public(&sk))
+        }
+        Command::Encapsulate {
+            public,
+            ciphertext,
+            shared_secret,
+        } => {
+            let pk = read(fmt, &public, Kind::PublicKey)?;
+            let (ct, ss) = encapsulate(&pk, &mut OsRng)?;
+            write(fmt, &ciphertext, Kind::Ciphertext, &ct)?;
+            write(fmt, &shared_secret, Kind::SharedSecret, &ss)
+        }
+        Command::Decapsulate {
+            secret,
+            ciphertext,
+            shared_secret,
+        } => {
+            let sk = read(fmt, &secret, Kind::SecretKey)?;
+            let ct = read(fmt, &ciphertext, Kind::Ciphertext)?;
+            write(
+                fmt,
+                &shared_secret,
+                Kind::SharedSecret,
+                &decapsulate(&ct, &sk)?,
+            )
+        }
+        Command::Encrypt { public, io } => {
+            let pk = read(fmt, &public, Kind::PublicKey)?;
+            let plaintext = read_input(&io.input)?;
+            write_output(&io.output, &encrypt(&pk, &plaintext)?)
+        }
+        Command::Decrypt { secret, io } => {
+            let sk = read(fmt, &secret, Kind::SecretKey)?;
+            let file = read_input(&io.input)?;
+            write_output(&io.output, &decrypt(&sk, &file)?)
+        }
+        Command::VerifyKat { file, count } => verify_kat(&file, count),
+    }
+}
+
+fn write_keys(fmt: Format, out: &KeyOut, keys: &Keypair) -> CliResult<()> {
+    write(fmt, &out.public, Kind::PublicKey, &keys.public)?;
+    write(fmt, &out.secret, Kind::SecretKey, &keys.secret)
+}
+
+// File encryption
+
+fn encrypt(pk: &[u8], plaintext: &[u8]) -> CliResult<Vec<u8>> {
+    let (ct, ss) = encapsulate(pk, &mut OsRng)?;
+    let mut out = Vec::with_capacity(FILE_HEADER_BYTES + ct.len() + plaintext.len() + 16);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&KEX_HEADER);
+    out.extend_from_slice(&ct);
+    let sealed = ChaCha20Poly1305::new(&ss.into())
+        .encrypt(
+            &Nonce::default(),
+            Payload {
+                msg: plaintext,
+                aad: &out,
+            },
+        )
+        .map_err(|_| CliError("encryption failed".into()))?;
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+fn decrypt(sk: &[u8], file: &[u8]) -> CliResult<Vec<u8>> {
+    const BODY: usize = FILE_HEADER_BYTES + KYBER_CIPHERTEXTBYTES;
+    if file.len() < BODY + 16 || &file[..MAGIC.len()] != MAGIC {
+        return fail("not a kyber encrypted file");
+    }
+    if file[MAGIC.len()..FILE_HEADER_BYTES] != KEX_HEADER {
+        return Err(Box::new(KyberError::ParameterMismatch));
+    }
+    let ss = decapsulate(&file[FILE_HEADER_BYTES..BODY], sk)?;
+    let plaintext = ChaCha20Poly1305::new(&ss.into())
+        .decrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &file[BODY..],
+                aad: &file[..BODY],
+            },
+        )
+        .map_err(|_| CliError("decryption failed, wrong key or corrupted file".into()))?;
+    Ok(plaintext)
+}
+
+// Known answer tests
+
+// Replays the encapsulation randomness recorded in a test vector
+struct ReplayRng<'a>(&'a [u8]);
+
+impl RngCore for ReplayRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+    // Kyber only calls try_fill_bytes, an exhausted vector is reported as
+    // KyberError::RandomBytesGeneration
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("{}", e);
+        }
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        if dest.len() > self.0.len() {
+            return Err(rand::Error::new(CliError("test vector exhausted".into())));
+        }
+        let (head, rest) = self.0.split_at(dest.len());
+        dest.copy_from_slice(head);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for ReplayRng<'_> {}
+
+// Test vector files hold 8 line records: two keygen buffers, pk, sk,
+// encapsulation buffer, ct, ss and a blank line
+fn verify_kat(path: &Path, count: Option<usize>) -> CliResult<()> {
+    let file = BufReader::new(fs::File::open(path)?);
+    let mut lines = file.lines();
+    let count = count.unwrap_or(usize::MAX);
+    let mut checked = 0;
+    while checked < count {
+        let mut record = Vec::with_capacity(7);
+        for line in lines.by_ref().take(8) {
+            let line = line?;
+            let value = line.rsplit(": ").next().unwrap_or_default();
+            if !value.is_empty() {
+                record.push(decode_hex(value.trim())?);
+            }
+        }
+        if record.is_empty() {
+            break;
+        }
+        if record.len() != 7 {
+            return fail(format!("malformed test vector {}", checked));
+        }
+        check_kat(&record).map_err(|e| CliError(format!("test vector {}: {}", checked, e)))?;
+        checked += 1;
+    }
+    if checked == 0 {
+        return fail("no test vectors found");
+    }
+    println!("{} test vectors passed", checked);
+    Ok(())
+}
+
+fn check_kat(kat: &[Vec<u8>]) -> CliResult<()> {
+    let (pk, sk, ct, ss) = (&kat[2], &kat[3], &kat[5], &kat[6]);
+    let seed = [&kat[0][..], &kat[1][..]].concat();
+    let keys = derive(&seed)?;
+    if keys.public[..] != pk[..] || keys.secret[..] != sk[..] {
+        return fail("keypair mismatch");
+    }
+    let (enc_ct, enc_ss) = encapsulate(pk, &mut ReplayRng(&kat[4]))?;
+    if enc_ct[..] != ct[..] || enc_ss[..] != ss[..] {
+        return fail("encapsulation mismatch");
+    }
+    if decapsulate(ct, sk)?[..] != ss[..] {
+        return fail("decapsulation mismatch");
+    }
+    Ok(())
+}
+
+// Encodings
+
+#[derive(Clone, Copy)]
+enum Kind {
+    PublicKey,
+    SecretKey,
+    Ciphertext,
+    SharedSecret,
+    Seed,
+}
+
+impl Kind {
+    fn len(self) -> usize {
+        match self {
+            Kind::PublicKey => KYBER_PUBLICKEYBYTES,
+            Kind::SecretKey => KYBER_SECRETKEYBYTES,
+            Kind::Ciphertext => KYBER_CIPHERTEXTBYTES,
+            Kind::SharedSecret => KYBER_SSBYTES,
+            Kind::Seed => SEED_BYTES,
+        }
+    }
+
+    // PEM labels include the parameter set so keys can't be mixed up
+    fn label(self) -> String {
+        let name = match self {
+            Kind::PublicKey => "PUBLIC KEY",
+            Kind::SecretKey => "SECRET KEY",
+            Kind::Ciphertext => "CIPHERTEXT",
+            Kind::SharedSecret => "SHARED SECRET",
+            Kind::Seed => "SEED",
+        };
+        let mode = if KYBER_90S { "-90S" } else { "" };
+        format!("KYBER{}{} {}", 256 * KYBER_K, mode, name)
+    }
+}
+
+fn read(fmt: Format, path: &Path, kind: Kind) -> CliResult<Vec<u8>> {
+    let data = read_input(path)?;
+    let bytes = match fmt {
+        Format::Raw => data,
+        Format::Hex => decode_hex(std::str::from_utf8(&data)?.trim())?,
+        Format::Base64 => STANDARD.decode(std::str::from_utf8(&data)?.trim())?,
+        Format::Pem => decode_pem(std::str::from_utf8(&data)?, &kind.label())?,
+    };
+    if bytes.len() != kind.len() {
+        return fail(format!(
+            "{}: expected {} bytes, found {}",
+            path.display(),
+            kind.len(),
+            bytes.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+fn write(fmt: Format, path: &Path, kind: Kind, bytes: &[u8]) -> CliResult<()> {
+    let data = match fmt {
+        Format::Raw => bytes.to_vec(),
+        Format::Hex => format!("{}\n", encode_hex(bytes)).into_bytes(),
+        Format::Base64 => format!("{}\n", STANDARD.encode(bytes)).into_bytes(),
+        Format::Pem => encode_pem(bytes, &kind.label()).into_bytes(),
+    };
+    match kind {
+        Kind::SecretKey | Kind::SharedSecret => write_secret(path, &data),
+        _ => write_output(path, &data),
+    }
+}
+
+fn read_input(path: &Path) -> CliResult<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        Ok(buf)
+    } else {
+        Ok(fs::read(path)?)
+    }
+}
+
+fn write_output(path: &Path, data: &[u8]) -> CliResult<()> {
+    if path == Path::new("-") {
+        io::stdout().write_all(data)?;
+    } else {
+        fs::write(path, data)?;
+    }
+    Ok(())
+}
+
+// Secret keys and shared secrets are only readable by the owner on unix
+fn write_secret(path: &Path, data: &[u8]) -> CliResult<()> {
+    if path == Path::new("-") {
+        return write_output(path, data);
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(data)?;
+    Ok(())
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> CliResult<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .filter(|pair| pair.len() == 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .map_or_else(|| fail("invalid hex"), Ok)
+        })
+        .collect()
+}
+
+fn encode_pem(bytes: &[u8], label: &str) -> String {
+    let b64 = STANDARD.encode(bytes);
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for line in b64.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+fn decode_pem(pem: &str, label: &str) -> CliResult<Vec<u8>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let mut lines = pem.lines().map(str::trim).skip_while(|l| l.is_empty());
+    if lines.next() != Some(begin.as_str()) {
+        return fail(format!("expected PEM block `{}`", label));
+    }
+    let mut b64 = String::new();
+    for line in lines {
+        if line == end {
+            return Ok(STANDARD.decode(b64)?);
+        }
+        b64.push_str(line);
+    }
+    fail(format!("missing `{}`", end))
+}
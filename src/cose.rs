@@ -0,0 +1,147 @@
+//! COSE_Key representations of Kyber keys.
+//!
+//! Keys are CBOR maps using the AKP key type, with the public key under label
+//! `-1` and the secret key under `-2`. As with the JWK encoding the private
+//! key is always the expanded secret key, ML-KEM seeds expand to a different
+//! keypair under round 3 Kyber and are rejected.
+//!
+//! The [`COSE_ALG`] values are private use identifiers for round 3 Kyber.
+//! They are not ML-KEM algorithms and won't be replaced by the ML-KEM
+//! identifiers once IANA assigns them, keys with any other `alg` are rejected
+//! with `KyberError::ParameterMismatch`.
+extern crate std;
+
+use crate::{api::*, kex::*, params::*, KyberError};
+use ciborium::value::{Integer, Value};
+use core::convert::TryFrom;
+use sha2::{Digest, Sha256};
+use std::vec::Vec;
+
+/// COSE key type value for AKP keys
+pub const COSE_KTY_AKP: i64 = 7;
+
+/// Private use COSE `alg` value for the selected security level and mode
+pub const COSE_ALG: i64 = match (KYBER_K, KYBER_90S) {
+    (2, false) => -65601,
+    (4, false) => -65603,
+    (_, false) => -65602,
+    (2, true) => -65611,
+    (4, true) => -65613,
+    (_, true) => -65612,
+};
+
+const LABEL_KTY: i64 = 1;
+const LABEL_ALG: i64 = 3;
+const LABEL_PUB: i64 = -1;
+const LABEL_PRIV: i64 = -2;
+
+/// Encodes a public key as a COSE_Key.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let cose = public_to_cose(&keys.public);
+/// assert_eq!(public_from_cose(&cose)?, keys.public);
+/// # Ok(()) }
+/// ```
+pub fn public_to_cose(pk: &PublicKey) -> Vec<u8> {
+    encode(required(pk))
+}
+
+/// Encodes a secret key as a COSE_Key, including its public key.
+pub fn secret_to_cose(sk: &SecretKey) -> Vec<u8> {
+    let mut map = required(&public(sk));
+    map.push((LABEL_PRIV.into(), Value::Bytes(sk.to_vec())));
+    encode(map)
+}
+
+/// Decodes a public key from a COSE_Key.
+///
+/// Returns `KyberError::ParameterMismatch` if the `alg` is for a different
+/// security level or mode.
+pub fn public_from_cose(cose: &[u8]) -> Result<PublicKey, KyberError> {
+    let map = parse(cose)?;
+    let bytes = field(&map, LABEL_PUB).ok_or(KyberError::InvalidInput)?;
+    if bytes.len() != KYBER_PUBLICKEYBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+    pk.copy_from_slice(bytes);
+    Ok(pk)
+}
+
+/// Decodes a keypair from a COSE_Key, the private key must be the expanded
+/// secret key. If present the public key must match.
+///
+/// Seed-only private keys are rejected with `KyberError::InvalidInput`.
+pub fn secret_from_cose(cose: &[u8]) -> Result<Keypair, KyberError> {
+    let map = parse(cose)?;
+    let private = field(&map, LABEL_PRIV).ok_or(KyberError::InvalidInput)?;
+    if private.len() != KYBER_SECRETKEYBYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let mut secret = [0u8; KYBER_SECRETKEYBYTES];
+    secret.copy_from_slice(private);
+    let keys = Keypair {
+        public: public(&secret),
+        secret,
+    };
+    match field(&map, LABEL_PUB) {
+        Some(pk) if pk != keys.public => Err(KyberError::InvalidInput),
+        _ => Ok(keys),
+    }
+}
+
+/// RFC 9679 thumbprint of a public key: the SHA-256 digest of the
+/// deterministically encoded `kty`, `alg` and public key.
+pub fn cose_thumbprint(pk: &PublicKey) -> [u8; 32] {
+    Sha256::digest(public_to_cose(pk)).into()
+}
+
+// Required members in deterministic encoding order
+fn required(pk: &PublicKey) -> Vec<(Value, Value)> {
+    std::vec![
+        (LABEL_KTY.into(), COSE_KTY_AKP.into()),
+        (LABEL_ALG.into(), COSE_ALG.into()),
+        (LABEL_PUB.into(), Value::Bytes(pk.to_vec())),
+    ]
+}
+
+fn encode(map: Vec<(Value, Value)>) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::ser::into_writer(&Value::Map(map), &mut out).expect("writing to a Vec cannot fail");
+    out
+}
+
+fn parse(cose: &[u8]) -> Result<Vec<(Value, Value)>, KyberError> {
+    let map = match ciborium::de::from_reader(cose) {
+        Ok(Value::Map(map)) => map,
+        _ => return Err(KyberError::InvalidInput),
+    };
+    if integer(&map, LABEL_KTY) != Some(COSE_KTY_AKP) {
+        return Err(KyberError::InvalidInput);
+    }
+    match integer(&map, LABEL_ALG) {
+        Some(COSE_ALG) => Ok(map),
+        Some(_) => Err(KyberError::ParameterMismatch),
+        None => Err(KyberError::InvalidInput),
+    }
+}
+
+fn get(map: &[(Value, Value)], label: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| k.as_integer() == Some(Integer::from(label)))
+        .map(|(_, v)| v)
+}
+
+fn integer(map: &[(Value, Value)], label: i64) -> Option<i64> {
+    get(map, label)?
+        .as_integer()
+        .and_then(|i| i64::try_from(i).ok())
+}
+
+fn field(map: &[(Value, Value)], label: i64) -> Option<&[u8]> {
+    get(map, label)?.as_bytes().map(Vec::as_slice)
+}
@@ -1,14 +1,25 @@
 #[derive(Debug, PartialEq)]
 /// Error types for the failure modes
 pub enum KyberError {
-    /// One or more inputs to a function are incorrectly sized. A likely cause of this is two parties using different security
-    /// levels while trying to negotiate a key exchange.
+    /// One or more inputs to a function are incorrectly sized.
     InvalidInput,
     /// The ciphertext was unable to be authenticated.
     /// The shared secret was not decapsulated.
     Decapsulation,
     /// Error trying to fill random bytes (i.e external (hardware) RNG modules can fail).
     RandomBytesGeneration,
+    /// The peer initiated a key exchange with a different protocol version, security level or mode.
+    ParameterMismatch,
+    /// The one-time prekey is unknown or has already been consumed.
+    PrekeyUnavailable,
+    /// Sealed key exchange state failed to authenticate, or was sealed for a
+    /// different key exchange, version or parameter set.
+    InvalidState,
+    /// Sealed key exchange state is past its expiry.
+    StateExpired,
+    /// Redundant computations in a `fault-hardened` decapsulation disagreed.
+    /// No shared secret was returned.
+    FaultDetected,
 }
 
 impl core::fmt::Display for KyberError {
@@ -22,6 +33,16 @@ impl core::fmt::Display for KyberError {
             KyberError::RandomBytesGeneration => {
                 write!(f, "Random bytes generation function failed")
             }
+            KyberError::ParameterMismatch => write!(
+                f,
+                "Key exchange peer uses a different protocol version, security level or mode"
+            ),
+            KyberError::PrekeyUnavailable => {
+                write!(f, "One-time prekey is unknown or has already been consumed")
+            }
+            KyberError::InvalidState => write!(f, "Sealed key exchange state is invalid"),
+            KyberError::StateExpired => write!(f, "Sealed key exchange state has expired"),
+            KyberError::FaultDetected => write!(f, "Fault detected during decapsulation"),
         }
     }
 }
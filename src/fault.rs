@@ -0,0 +1,37 @@
+//! Fault simulation for testing the `fault-hardened` decapsulation.
+//!
+//! Decapsulation passes its intermediate state through fault points after
+//! each step. With the `fault-simulation` feature the point chosen with
+//! [`inject_fault`] gets its lowest bit flipped, standing in for a glitch
+//! that corrupts a value or skips an instruction. Without the feature the
+//! points do nothing.
+
+/// Number of fault points in a `fault-hardened` decapsulation
+#[cfg(feature = "fault-simulation")]
+pub const FAULT_POINTS: usize = 2 * LANE_POINTS + 1;
+
+// Points in each of the two redundant decapsulation lanes
+pub(crate) const LANE_POINTS: usize = 8;
+
+#[cfg(feature = "fault-simulation")]
+std::thread_local! {
+    static TARGET: core::cell::Cell<Option<usize>> = const { core::cell::Cell::new(None) };
+}
+
+/// Flips a bit at the given fault point in every following decapsulation
+/// on this thread, `None` turns injection off
+#[cfg(feature = "fault-simulation")]
+pub fn inject_fault(point: Option<usize>) {
+    TARGET.with(|t| t.set(point));
+}
+
+#[cfg(feature = "fault-simulation")]
+pub(crate) fn point(n: usize, state: &mut [u8]) {
+    if TARGET.with(|t| t.get()) == Some(n) {
+        state[0] ^= 1;
+    }
+}
+
+#[cfg(not(feature = "fault-simulation"))]
+#[inline(always)]
+pub(crate) fn point(_n: usize, _state: &mut [u8]) {}
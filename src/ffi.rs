@@ -0,0 +1,155 @@
+//! C ABI for the key encapsulation mechanism.
+//!
+//! Signatures and return codes follow PQClean and liboqs: buffers are caller
+//! allocated with the sizes exported below, functions return 0 on success and
+//! -1 on failure. Randomness comes from the operating system.
+//!
+//! The security level is fixed when the library is built, check the exported
+//! sizes at runtime rather than hardcoding them. A header generated with
+//! cbindgen is in `include/pqc_kyber.h`.
+use crate::{api::derive, kem::*, params::*};
+use core::{ffi::c_int, slice};
+use rand::rngs::OsRng;
+
+/// Public key length in bytes
+#[no_mangle]
+pub static PQC_KYBER_PUBLICKEYBYTES: usize = KYBER_PUBLICKEYBYTES;
+/// Secret key length in bytes
+#[no_mangle]
+pub static PQC_KYBER_SECRETKEYBYTES: usize = KYBER_SECRETKEYBYTES;
+/// Ciphertext length in bytes
+#[no_mangle]
+pub static PQC_KYBER_CIPHERTEXTBYTES: usize = KYBER_CIPHERTEXTBYTES;
+/// Shared secret length in bytes
+#[no_mangle]
+pub static PQC_KYBER_SSBYTES: usize = KYBER_SSBYTES;
+/// Seed length in bytes for `pqc_kyber_derive`
+#[no_mangle]
+pub static PQC_KYBER_SEEDBYTES: usize = 2 * KYBER_SYMBYTES;
+/// Encapsulation coins length in bytes for `pqc_kyber_enc_derand`
+#[no_mangle]
+pub static PQC_KYBER_ENCCOINBYTES: usize = KYBER_SYMBYTES;
+
+const OK: c_int = 0;
+const ERR: c_int = -1;
+
+/// Generates a keypair.
+///
+/// # Safety
+///
+/// `pk` and `sk` must be valid for writes of `PQC_KYBER_PUBLICKEYBYTES` and
+/// `PQC_KYBER_SECRETKEYBYTES` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_keypair(pk: *mut u8, sk: *mut u8) -> c_int {
+    if pk.is_null() || sk.is_null() {
+        return ERR;
+    }
+    let pk = slice::from_raw_parts_mut(pk, KYBER_PUBLICKEYBYTES);
+    let sk = slice::from_raw_parts_mut(sk, KYBER_SECRETKEYBYTES);
+    status(crypto_kem_keypair(pk, sk, &mut OsRng, None).is_ok())
+}
+
+/// Deterministically derives a keypair from a 64 byte seed, the first half
+/// seeds the key generation and the second half the implicit rejection value.
+///
+/// # Safety
+///
+/// `seed` must be valid for reads of `PQC_KYBER_SEEDBYTES` bytes, `pk` and
+/// `sk` as for [`pqc_kyber_keypair`].
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_derive(pk: *mut u8, sk: *mut u8, seed: *const u8) -> c_int {
+    if pk.is_null() || sk.is_null() || seed.is_null() {
+        return ERR;
+    }
+    match derive(slice::from_raw_parts(seed, 2 * KYBER_SYMBYTES)) {
+        Ok(keys) => {
+            slice::from_raw_parts_mut(pk, KYBER_PUBLICKEYBYTES).copy_from_slice(&keys.public);
+            slice::from_raw_parts_mut(sk, KYBER_SECRETKEYBYTES).copy_from_slice(&keys.secret);
+            OK
+        }
+        Err(_) => ERR,
+    }
+}
+
+/// Encapsulates a shared secret to a public key.
+///
+/// # Safety
+///
+/// `ct` and `ss` must be valid for writes of `PQC_KYBER_CIPHERTEXTBYTES` and
+/// `PQC_KYBER_SSBYTES` bytes, `pk` for reads of `PQC_KYBER_PUBLICKEYBYTES`.
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int {
+    enc(ct, ss, pk, None)
+}
+
+/// Encapsulates with caller supplied randomness, for known answer tests.
+///
+/// # Safety
+///
+/// `coins` must be valid for reads of `PQC_KYBER_ENCCOINBYTES` bytes, the
+/// other arguments as for [`pqc_kyber_enc`].
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_enc_derand(
+    ct: *mut u8,
+    ss: *mut u8,
+    pk: *const u8,
+    coins: *const u8,
+) -> c_int {
+    if coins.is_null() {
+        return ERR;
+    }
+    enc(
+        ct,
+        ss,
+        pk,
+        Some(slice::from_raw_parts(coins, KYBER_SYMBYTES)),
+    )
+}
+
+/// Decapsulates a shared secret. Invalid ciphertexts are implicitly rejected,
+/// returning 0 with a pseudorandom shared secret.
+/// Built with the `fault-hardened` feature a detected fault zeroes `ss` and
+/// returns -1.
+///
+/// # Safety
+///
+/// `ss` must be valid for writes of `PQC_KYBER_SSBYTES` bytes, `ct` and `sk`
+/// for reads of `PQC_KYBER_CIPHERTEXTBYTES` and `PQC_KYBER_SECRETKEYBYTES`.
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_dec(ss: *mut u8, ct: *const u8, sk: *const u8) -> c_int {
+    if ss.is_null() || ct.is_null() || sk.is_null() {
+        return ERR;
+    }
+    status(
+        crypto_kem_dec(
+            slice::from_raw_parts_mut(ss, KYBER_SSBYTES),
+            slice::from_raw_parts(ct, KYBER_CIPHERTEXTBYTES),
+            slice::from_raw_parts(sk, KYBER_SECRETKEYBYTES),
+        )
+        .is_ok(),
+    )
+}
+
+unsafe fn enc(ct: *mut u8, ss: *mut u8, pk: *const u8, coins: Option<&[u8]>) -> c_int {
+    if ct.is_null() || ss.is_null() || pk.is_null() {
+        return ERR;
+    }
+    status(
+        crypto_kem_enc(
+            slice::from_raw_parts_mut(ct, KYBER_CIPHERTEXTBYTES),
+            slice::from_raw_parts_mut(ss, KYBER_SSBYTES),
+            slice::from_raw_parts(pk, KYBER_PUBLICKEYBYTES),
+            &mut OsRng,
+            coins,
+        )
+        .is_ok(),
+    )
+}
+
+fn status(ok: bool) -> c_int {
+    if ok {
+        OK
+    } else {
+        ERR
+    }
+}
@@ -0,0 +1,215 @@
+//! Framed UAKE handshakes over tokio streams.
+//!
+//! Each message is sent as a frame: a 4 byte big-endian length followed by
+//! the payload, then a unilaterally authenticated key exchange is run over
+//! them. The server checks the key exchange header at the start of the
+//! client's initiation before its length, and answers a mismatch with its own
+//! header in place of the response, so it fails with
+//! `KyberError::ParameterMismatch` on both ends instead of an incorrectly
+//! sized frame.
+extern crate std;
+
+use crate::{kex::*, CryptoRng, KyberError, RngCore};
+use core::time::Duration;
+use std::{boxed::Box, io, vec, vec::Vec};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Time allowed for each read or write before the handshake is abandoned
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Largest initiation of any security level, Kyber1024's public key and
+// ciphertext are both 1568 bytes
+const MAX_FRAME_BYTES: usize = KEX_HEADER_BYTES + 2 * 1568;
+
+/// Error types for a failed handshake
+#[derive(Debug)]
+pub enum HandshakeError {
+    /// The underlying stream failed or was closed early.
+    Io(io::Error),
+    /// The peer took longer than the allowed timeout to respond.
+    Timeout,
+    /// A frame had an unexpected length.
+    InvalidFrame,
+    /// The key exchange itself failed.
+    Kyber(KyberError),
+}
+
+impl core::fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            HandshakeError::Io(e) => write!(f, "Handshake stream error: {}", e),
+            HandshakeError::Timeout => write!(f, "Handshake timed out"),
+            HandshakeError::InvalidFrame => write!(f, "Handshake frame is of incorrect length"),
+            HandshakeError::Kyber(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HandshakeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HandshakeError {
+    fn from(e: io::Error) -> Self {
+        HandshakeError::Io(e)
+    }
+}
+
+impl From<KyberError> for HandshakeError {
+    fn from(e: KyberError) -> Self {
+        HandshakeError::Kyber(e)
+    }
+}
+
+/// Runs the client side of a framed UAKE handshake, returning the shared secret.
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), HandshakeError> {
+/// let mut rng = rand::rngs::OsRng;
+/// let server_keys = keypair(&mut rng)?;
+/// let (mut client, mut server) = tokio::io::duplex(4096);
+///
+/// let server_task = tokio::spawn(async move {
+///     server_handshake(&mut server, &server_keys.secret, &mut rand::rngs::OsRng).await
+/// });
+/// let client_secret = client_handshake(&mut client, &server_keys.public, &mut rng).await?;
+///
+/// assert_eq!(client_secret, server_task.await.unwrap()?);
+/// # Ok(()) }
+/// ```
+pub async fn client_handshake<S, R>(
+    stream: &mut S,
+    server_pk: &PublicKey,
+    rng: &mut R,
+) -> Result<SharedSecret, HandshakeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    R: CryptoRng + RngCore,
+{
+    client_handshake_with_timeout(stream, server_pk, rng, HANDSHAKE_TIMEOUT).await
+}
+
+/// Runs the server side of a framed UAKE handshake, returning the shared secret.
+pub async fn server_handshake<S, R>(
+    stream: &mut S,
+    server_sk: &SecretKey,
+    rng: &mut R,
+) -> Result<SharedSecret, HandshakeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    R: CryptoRng + RngCore,
+{
+    server_handshake_with_timeout(stream, server_sk, rng, HANDSHAKE_TIMEOUT).await
+}
+
+/// Same as [`client_handshake`] with a custom timeout for each read and write.
+pub async fn client_handshake_with_timeout<S, R>(
+    stream: &mut S,
+    server_pk: &PublicKey,
+    rng: &mut R,
+    timeout: Duration,
+) -> Result<SharedSecret, HandshakeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    R: CryptoRng + RngCore,
+{
+    let mut uake = Box::new(Uake::new());
+    let send_a = uake.client_init(server_pk, rng)?;
+    write_frame(stream, &send_a, timeout).await?;
+
+    let response = read_frame(stream, timeout).await?;
+    // The server replies with its header when it rejects ours
+    if response.len() == KEX_HEADER_BYTES {
+        check_kex_header(&response)?;
+    }
+    let mut send_b = [0u8; UAKE_RESPONSE_BYTES];
+    copy_frame(&mut send_b, &response)?;
+    uake.client_confirm(send_b)?;
+    Ok(uake.shared_secret)
+}
+
+/// Same as [`server_handshake`] with a custom timeout for each read and write.
+pub async fn server_handshake_with_timeout<S, R>(
+    stream: &mut S,
+    server_sk: &SecretKey,
+    rng: &mut R,
+    timeout: Duration,
+) -> Result<SharedSecret, HandshakeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    R: CryptoRng + RngCore,
+{
+    let init = read_frame(stream, timeout).await?;
+    if init.len() < KEX_HEADER_BYTES {
+        return Err(HandshakeError::InvalidFrame);
+    }
+    if let Err(e) = check_kex_header(&init[..KEX_HEADER_BYTES]) {
+        // Reply so the client can report the mismatch as well
+        write_frame(stream, &KEX_HEADER, timeout).await?;
+        return Err(e.into());
+    }
+    let mut send_a = [0u8; UAKE_INIT_BYTES];
+    copy_frame(&mut send_a, &init)?;
+
+    let mut uake = Box::new(Uake::new());
+    let send_b = uake.server_receive(send_a, server_sk, rng)?;
+    write_frame(stream, &send_b, timeout).await?;
+    Ok(uake.shared_secret)
+}
+
+fn copy_frame(out: &mut [u8], frame: &[u8]) -> Result<(), HandshakeError> {
+    if frame.len() != out.len() {
+        return Err(HandshakeError::InvalidFrame);
+    }
+    out.copy_from_slice(frame);
+    Ok(())
+}
+
+async fn write_frame<S>(
+    stream: &mut S,
+    payload: &[u8],
+    timeout: Duration,
+) -> Result<(), HandshakeError>
+where
+    S: AsyncWrite + Unpin,
+{
+    let len = (payload.len() as u32).to_be_bytes();
+    let write = async {
+        stream.write_all(&len).await?;
+        stream.write_all(payload).await?;
+        stream.flush().await
+    };
+    match tokio::time::timeout(timeout, write).await {
+        Ok(res) => Ok(res?),
+        Err(_) => Err(HandshakeError::Timeout),
+    }
+}
+
+async fn read_frame<S>(stream: &mut S, timeout: Duration) -> Result<Vec<u8>, HandshakeError>
+where
+    S: AsyncRead + Unpin,
+{
+    let read = async {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_BYTES {
+            return Err(HandshakeError::InvalidFrame);
+        }
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await?;
+        Ok(payload)
+    };
+    match tokio::time::timeout(timeout, read).await {
+        Ok(res) => res,
+        Err(_) => Err(HandshakeError::Timeout),
+    }
+}
@@ -0,0 +1,134 @@
+//! JSON Web Key representations of Kyber keys.
+//!
+//! Keys use the `"kty": "AKP"` (Algorithm Key Pair) type from the JOSE
+//! post-quantum drafts, with the public key in `pub` and the secret key in
+//! `priv`, both base64url encoded.
+//!
+//! This crate implements round 3 Kyber, not FIPS 203 ML-KEM, so keys carry
+//! Kyber `alg` values and ML-KEM keys are rejected with
+//! `KyberError::ParameterMismatch`. The ML-KEM drafts store the 64 byte KeyGen
+//! seed in `priv`, which expands to a different keypair under round 3 Kyber,
+//! so `priv` always holds the expanded secret key and seeds are rejected.
+extern crate std;
+
+use crate::{api::*, kex::*, params::*, KyberError};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::{string::String, vec::Vec};
+
+/// Key type of Kyber JWKs and COSE keys
+pub const JWK_KTY: &str = "AKP";
+
+/// JWK `alg` value for the selected security level and mode
+pub const JWK_ALG: &str = KYBER_ALGORITHM;
+
+/// Encodes a public key as a JWK.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// # let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let jwk = public_to_jwk(&keys.public);
+/// assert_eq!(public_from_jwk(&jwk)?, keys.public);
+/// # Ok(()) }
+/// ```
+pub fn public_to_jwk(pk: &PublicKey) -> String {
+    json!({
+        "kty": JWK_KTY,
+        "alg": JWK_ALG,
+        "kid": jwk_thumbprint_base64(pk),
+        "pub": URL_SAFE_NO_PAD.encode(pk),
+    })
+    .to_string()
+}
+
+/// Encodes a secret key as a JWK, including its public key.
+pub fn secret_to_jwk(sk: &SecretKey) -> String {
+    let pk = public(sk);
+    json!({
+        "kty": JWK_KTY,
+        "alg": JWK_ALG,
+        "kid": jwk_thumbprint_base64(&pk),
+        "pub": URL_SAFE_NO_PAD.encode(pk),
+        "priv": URL_SAFE_NO_PAD.encode(sk),
+    })
+    .to_string()
+}
+
+/// Decodes a public key from a JWK.
+///
+/// Returns `KyberError::ParameterMismatch` if the `alg` is for a different
+/// security level or mode.
+pub fn public_from_jwk(jwk: &str) -> Result<PublicKey, KyberError> {
+    let map = parse(jwk)?;
+    let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+    copy_exact(&mut pk, &field(&map, "pub")?)?;
+    Ok(pk)
+}
+
+/// Decodes a keypair from a JWK, `priv` must be the expanded secret key.
+/// If present the `pub` member must match the secret key.
+///
+/// Seed-only `priv` values are rejected with `KyberError::InvalidInput`.
+pub fn secret_from_jwk(jwk: &str) -> Result<Keypair, KyberError> {
+    let map = parse(jwk)?;
+    let mut secret = [0u8; KYBER_SECRETKEYBYTES];
+    copy_exact(&mut secret, &field(&map, "priv")?)?;
+    let keys = Keypair {
+        public: public(&secret),
+        secret,
+    };
+    if map.contains_key("pub") && field(&map, "pub")? != keys.public {
+        return Err(KyberError::InvalidInput);
+    }
+    Ok(keys)
+}
+
+/// RFC 7638 thumbprint of a public key: the SHA-256 digest of the required
+/// members `alg`, `kty` and `pub` in lexicographic order.
+pub fn jwk_thumbprint(pk: &PublicKey) -> [u8; 32] {
+    let canonical = std::format!(
+        r#"{{"alg":"{}","kty":"{}","pub":"{}"}}"#,
+        JWK_ALG,
+        JWK_KTY,
+        URL_SAFE_NO_PAD.encode(pk)
+    );
+    Sha256::digest(canonical.as_bytes()).into()
+}
+
+/// Base64url encoded [`jwk_thumbprint`], as used for the `kid` member
+pub fn jwk_thumbprint_base64(pk: &PublicKey) -> String {
+    URL_SAFE_NO_PAD.encode(jwk_thumbprint(pk))
+}
+
+fn parse(jwk: &str) -> Result<Map<String, Value>, KyberError> {
+    let map = match serde_json::from_str(jwk) {
+        Ok(Value::Object(map)) => map,
+        _ => return Err(KyberError::InvalidInput),
+    };
+    if map.get("kty").and_then(Value::as_str) != Some(JWK_KTY) {
+        return Err(KyberError::InvalidInput);
+    }
+    match map.get("alg").and_then(Value::as_str) {
+        Some(JWK_ALG) => Ok(map),
+        Some(_) => Err(KyberError::ParameterMismatch),
+        None => Err(KyberError::InvalidInput),
+    }
+}
+
+fn field(map: &Map<String, Value>, name: &str) -> Result<Vec<u8>, KyberError> {
+    map.get(name)
+        .and_then(Value::as_str)
+        .and_then(|s| URL_SAFE_NO_PAD.decode(s).ok())
+        .ok_or(KyberError::InvalidInput)
+}
+
+fn copy_exact(out: &mut [u8], bytes: &[u8]) -> Result<(), KyberError> {
+    if bytes.len() != out.len() {
+        return Err(KyberError::InvalidInput);
+    }
+    out.copy_from_slice(bytes);
+    Ok(())
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "fault-hardened")]
+use crate::fault;
 use crate::rng::randombytes;
 use crate::{error::KyberError, indcpa::*, params::*, symmetric::*, verify::*};
 use rand_core::{CryptoRng, RngCore};
@@ -92,7 +94,8 @@ where
 ///  - const [u8] sk: input private key (an already allocated array of CRYPTO_SECRETKEYBYTES bytes)
 ///
 /// On failure, ss will contain a pseudo-random value.
-pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> () {
+#[cfg(not(feature = "fault-hardened"))]
+pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> Result<(), KyberError> {
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
     let mut kr = [0u8; 2 * KYBER_SYMBYTES];
     let mut cmp = [0u8; KYBER_CIPHERTEXTBYTES];
@@ -117,4 +120,77 @@ pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> () {
     cmov(&mut kr, &sk[END..], KYBER_SYMBYTES, fail);
     // hash concatenation of pre-k and H(c) to k
     kdf(ss, &kr, 2 * KYBER_SYMBYTES);
+    Ok(())
+}
+
+/// Name:  crypto_kem_dec
+///
+/// Description: Fault hardened decapsulation. Runs two independent
+///  decapsulations, each with its own decryption, re-encryption, comparison
+///  and choice between pre-k and z, then checks they agree and that the
+///  stored H(pk) matches the public key in sk.
+///
+/// Arguments:   - [u8] ss:   output shared secret (an already allocated array of CRYPTO_BYTES bytes)
+///  - const [u8] ct: input cipher text (an already allocated array of CRYPTO_CIPHERTEXTBYTES bytes)
+///  - const [u8] sk: input private key (an already allocated array of CRYPTO_SECRETKEYBYTES bytes)
+///
+/// On failure, ss will contain a pseudo-random value. If a fault is detected
+/// ss is zeroed and `KyberError::FaultDetected` returned.
+#[cfg(feature = "fault-hardened")]
+pub fn crypto_kem_dec(ss: &mut [u8], ct: &[u8], sk: &[u8]) -> Result<(), KyberError> {
+    const START: usize = KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    const END: usize = KYBER_SECRETKEYBYTES - KYBER_SYMBYTES;
+    let mut buf = [[0u8; 2 * KYBER_SYMBYTES]; 2];
+    let mut kr = [[0u8; 2 * KYBER_SYMBYTES]; 2];
+    let mut cmp = [[0u8; KYBER_CIPHERTEXTBYTES]; 2];
+    let mut key = [[0u8; KYBER_SSBYTES]; 2];
+    let mut fail = [0u8; 2];
+    let mut pk = [0u8; KYBER_INDCPA_PUBLICKEYBYTES];
+    let mut hpk = [0u8; KYBER_SYMBYTES];
+
+    pk.copy_from_slice(&sk[KYBER_INDCPA_SECRETKEYBYTES..][..KYBER_INDCPA_PUBLICKEYBYTES]);
+    fault::point(2 * fault::LANE_POINTS, &mut pk);
+
+    for i in 0..2 {
+        let p = i * fault::LANE_POINTS;
+        indcpa_dec(&mut buf[i], ct, sk);
+        fault::point(p, &mut buf[i]);
+
+        buf[i][KYBER_SYMBYTES..].copy_from_slice(&sk[START..END]);
+        fault::point(p + 1, &mut buf[i][KYBER_SYMBYTES..]);
+        hash_g(&mut kr[i], &buf[i], 2 * KYBER_SYMBYTES);
+        fault::point(p + 2, &mut kr[i]);
+        fault::point(p + 3, &mut kr[i][KYBER_SYMBYTES..]);
+
+        indcpa_enc(&mut cmp[i], &buf[i], &pk, &kr[i][KYBER_SYMBYTES..]);
+        fault::point(p + 4, &mut cmp[i]);
+        fail[i] = verify(ct, &cmp[i], KYBER_CIPHERTEXTBYTES);
+        fault::point(p + 5, &mut fail[i..=i]);
+
+        hash_h(&mut kr[i][KYBER_SYMBYTES..], ct, KYBER_CIPHERTEXTBYTES);
+        cmov(&mut kr[i], &sk[END..], KYBER_SYMBYTES, fail[i]);
+        fault::point(p + 6, &mut kr[i]);
+        kdf(&mut key[i], &kr[i], 2 * KYBER_SYMBYTES);
+        fault::point(p + 7, &mut key[i]);
+    }
+
+    // The stored H(pk) catches corruption of the public key in sk or its copy
+    hash_h(&mut hpk, &pk, KYBER_INDCPA_PUBLICKEYBYTES);
+    let mut fault = verify(&hpk, &buf[0][KYBER_SYMBYTES..], KYBER_SYMBYTES);
+    fault |= verify(&hpk, &buf[1][KYBER_SYMBYTES..], KYBER_SYMBYTES);
+    fault |= verify(&buf[0], &buf[1], KYBER_SYMBYTES);
+    fault |= verify(&key[0], &key[1], KYBER_SSBYTES);
+    fault |= fail[0] ^ fail[1];
+    if fault != 0 {
+        ss[..KYBER_SSBYTES].fill(0);
+        return Err(KyberError::FaultDetected);
+    }
+
+    // Checked again after the copy so skipping the branch above fails closed
+    ss[..KYBER_SSBYTES].copy_from_slice(&key[0]);
+    if verify(ss, &key[1], KYBER_SSBYTES) | (fail[0] ^ fail[1]) != 0 {
+        ss[..KYBER_SSBYTES].fill(0);
+        return Err(KyberError::FaultDetected);
+    }
+    Ok(())
 }
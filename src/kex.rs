@@ -1,14 +1,30 @@
-use crate::{kem::*, params::*, symmetric::kdf, KyberError};
+use crate::{kem::*, params::*, seal::*, symmetric::kdf, KyberError};
 use rand_core::{CryptoRng, RngCore};
 #[cfg(feature = "zeroize")]
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
+/// Version of the key exchange wire format
+pub const KEX_VERSION: u8 = 1;
+/// Key Exchange Header Byte Length
+pub const KEX_HEADER_BYTES: usize = 3;
+/// Header flag set when 90s mode is active
+pub const KEX_FLAG_90S: u8 = 0x01;
+/// Header flag reserved for ML-KEM, never set by this version
+pub const KEX_FLAG_MLKEM: u8 = 0x02;
+/// Header prefixed to every key exchange initiation: the wire format version,
+/// the security level `KYBER_K` and the mode flags.
+pub const KEX_HEADER: [u8; KEX_HEADER_BYTES] = [
+    KEX_VERSION,
+    KYBER_K as u8,
+    if KYBER_90S { KEX_FLAG_90S } else { 0 },
+];
+
 /// Unilateral Key Exchange Initiation Byte Length
-pub const UAKE_INIT_BYTES: usize = KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
+pub const UAKE_INIT_BYTES: usize = KEX_HEADER_BYTES + KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
 /// Unilateral Key Exchange Response Byte Length
 pub const UAKE_RESPONSE_BYTES: usize = KYBER_CIPHERTEXTBYTES;
 /// Mutual Key Exchange Initiation Byte Length
-pub const AKE_INIT_BYTES: usize = KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
+pub const AKE_INIT_BYTES: usize = KEX_HEADER_BYTES + KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
 /// Mutual Key Exchange Response Byte Length
 pub const AKE_RESPONSE_BYTES: usize = 2 * KYBER_CIPHERTEXTBYTES;
 
@@ -118,6 +134,9 @@ impl Uake {
     }
 
     /// Handles the output of a `client_init()` request
+    ///
+    /// Returns `KyberError::ParameterMismatch` if the initiation header
+    /// specifies a different version, security level or mode.
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -165,6 +184,68 @@ impl Uake {
         uake_shared_a(&mut self.shared_secret, &send_b, &self.temp_key, &self.eska)?;
         Ok(())
     }
+
+    /// Encrypts the ephemeral keys of a client between `client_init()` and
+    /// `client_confirm()` so it can be stored outside the process, for example
+    /// in a cookie. The state can be restored with `unseal()` until `expires`,
+    /// in the same units as the `now` passed when unsealing.
+    ///
+    /// Each sealed state should only be restored and confirmed once.
+    /// ```
+    /// # use pqc_kyber::*;
+    /// # fn main() -> Result<(),KyberError> {
+    /// # let mut rng = rand::thread_rng();
+    /// # let mut bob = Uake::new();
+    /// # let bob_keys = keypair(&mut rng)?;
+    /// let seal_key = [42u8; SEAL_KEY_BYTES];
+    /// let mut alice = Uake::new();
+    /// let client_init = alice.client_init(&bob_keys.public, &mut rng)?;
+    /// let sealed = alice.seal(&seal_key, 1_700_000_300, &mut rng)?;
+    ///
+    /// // Later, possibly in another process
+    /// let server_send = bob.server_receive(client_init, &bob_keys.secret, &mut rng)?;
+    /// let mut alice = Uake::unseal(&sealed, &seal_key, 1_700_000_100)?;
+    /// alice.client_confirm(server_send)?;
+    /// assert_eq!(alice.shared_secret, bob.shared_secret);
+    /// # Ok(()) }
+    /// ```
+    pub fn seal<R>(
+        &self,
+        key: &SealKey,
+        expires: u64,
+        rng: &mut R,
+    ) -> Result<SealedState, KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut sealed = [0u8; SEALED_STATE_BYTES];
+        seal_state(
+            &mut sealed,
+            SEAL_UAKE,
+            &self.temp_key,
+            &self.eska,
+            key,
+            expires,
+            rng,
+        )?;
+        Ok(sealed)
+    }
+
+    /// Restores a client sealed with `seal()`. Returns `KyberError::InvalidState`
+    /// if the state fails to authenticate and `KyberError::StateExpired` if `now`
+    /// is past its expiry.
+    pub fn unseal(sealed: &[u8], key: &SealKey, now: u64) -> Result<Self, KyberError> {
+        let mut uake = Self::default();
+        unseal_state(
+            &mut uake.temp_key,
+            &mut uake.eska,
+            SEAL_UAKE,
+            sealed,
+            key,
+            now,
+        )?;
+        Ok(uake)
+    }
 }
 
 /// Used for mutually authenticated key exchange between two parties.
@@ -253,6 +334,9 @@ impl Ake {
     }
 
     /// Handles and authenticates the output of a `client_init()` request
+    ///
+    /// Returns `KyberError::ParameterMismatch` if the initiation header
+    /// specifies a different version, security level or mode.
     /// ```
     /// # use pqc_kyber::*;
     /// # fn main() -> Result<(),KyberError> {
@@ -314,6 +398,46 @@ impl Ake {
         )?;
         Ok(())
     }
+
+    /// Encrypts the ephemeral keys of a client between `client_init()` and
+    /// `client_confirm()`, see [`Uake::seal`].
+    pub fn seal<R>(
+        &self,
+        key: &SealKey,
+        expires: u64,
+        rng: &mut R,
+    ) -> Result<SealedState, KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut sealed = [0u8; SEALED_STATE_BYTES];
+        seal_state(
+            &mut sealed,
+            SEAL_AKE,
+            &self.temp_key,
+            &self.eska,
+            key,
+            expires,
+            rng,
+        )?;
+        Ok(sealed)
+    }
+
+    /// Restores a client sealed with `seal()`, see [`Uake::unseal`].
+    pub fn unseal(sealed: &[u8], key: &SealKey, now: u64) -> Result<Self, KyberError> {
+        let mut ake = Self::default();
+        unseal_state(&mut ake.temp_key, &mut ake.eska, SEAL_AKE, sealed, key, now)?;
+        Ok(ake)
+    }
+}
+
+// Rejects initiations from peers using a different wire format version,
+// security level or mode
+pub(crate) fn check_kex_header(header: &[u8]) -> Result<(), KyberError> {
+    if header != KEX_HEADER {
+        return Err(KyberError::ParameterMismatch);
+    }
+    Ok(())
 }
 
 // Unilaterally Authenticated Key Exchange initiation
@@ -327,6 +451,8 @@ fn uake_init_a<R>(
 where
     R: CryptoRng + RngCore,
 {
+    let (header, send) = send.split_at_mut(KEX_HEADER_BYTES);
+    header.copy_from_slice(&KEX_HEADER);
     crypto_kem_keypair(send, sk, rng, None)?;
     crypto_kem_enc(&mut send[KYBER_PUBLICKEYBYTES..], tk, pkb, rng, None)?;
     Ok(())
@@ -343,13 +469,15 @@ fn uake_shared_b<R>(
 where
     R: CryptoRng + RngCore,
 {
+    check_kex_header(&recv[..KEX_HEADER_BYTES])?;
+    let recv = &recv[KEX_HEADER_BYTES..];
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
     crypto_kem_enc(send, &mut buf, recv, rng, None)?;
     crypto_kem_dec(
         &mut buf[KYBER_SYMBYTES..],
         &recv[KYBER_PUBLICKEYBYTES..],
         skb,
-    );
+    )?;
     kdf(k, &buf, 2 * KYBER_SYMBYTES);
     Ok(())
 }
@@ -357,7 +485,7 @@ where
 // Unilaterally authenticated key exchange computation by Alice
 fn uake_shared_a(k: &mut [u8], recv: &[u8], tk: &[u8], sk: &[u8]) -> Result<(), KyberError> {
     let mut buf = [0u8; 2 * KYBER_SYMBYTES];
-    crypto_kem_dec(&mut buf, recv, sk);
+    crypto_kem_dec(&mut buf, recv, sk)?;
     buf[KYBER_SYMBYTES..].copy_from_slice(&tk[..]);
     kdf(k, &buf, 2 * KYBER_SYMBYTES);
     Ok(())
@@ -374,6 +502,8 @@ fn ake_init_a<R>(
 where
     R: CryptoRng + RngCore,
 {
+    let (header, send) = send.split_at_mut(KEX_HEADER_BYTES);
+    header.copy_from_slice(&KEX_HEADER);
     crypto_kem_keypair(send, sk, rng, None)?;
     crypto_kem_enc(&mut send[KYBER_PUBLICKEYBYTES..], tk, pkb, rng, None)?;
     Ok(())
@@ -391,6 +521,8 @@ fn ake_shared_b<R>(
 where
     R: CryptoRng + RngCore,
 {
+    check_kex_header(&recv[..KEX_HEADER_BYTES])?;
+    let recv = &recv[KEX_HEADER_BYTES..];
     let mut buf = [0u8; 3 * KYBER_SYMBYTES];
     crypto_kem_enc(send, &mut buf, recv, rng, None)?;
     crypto_kem_enc(
@@ -404,7 +536,7 @@ where
         &mut buf[2 * KYBER_SYMBYTES..],
         &recv[KYBER_PUBLICKEYBYTES..],
         skb,
-    );
+    )?;
     kdf(k, &buf, 3 * KYBER_SYMBYTES);
     Ok(())
 }
@@ -418,12 +550,12 @@ fn ake_shared_a(
     ska: &[u8],
 ) -> Result<(), KyberError> {
     let mut buf = [0u8; 3 * KYBER_SYMBYTES];
-    crypto_kem_dec(&mut buf, recv, sk);
+    crypto_kem_dec(&mut buf, recv, sk)?;
     crypto_kem_dec(
         &mut buf[KYBER_SYMBYTES..],
         &recv[KYBER_CIPHERTEXTBYTES..],
         ska,
-    );
+    )?;
     buf[2 * KYBER_SYMBYTES..].copy_from_slice(&tk[..]);
     kdf(k, &buf, 3 * KYBER_SYMBYTES);
     Ok(())
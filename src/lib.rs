@@ -4,7 +4,7 @@
 //!
 //! This library:
 //! * Is no_std compatible and uses no allocations, suitable for embedded devices.
-//! * The reference files contain no unsafe code.
+//! * The reference files contain no unsafe code, apart from the vector loads and stores of the wasm SIMD128 backend.
 //! * On x86_64 platforms uses an optimized avx2 version by default.
 //! * Compiles to WASM using wasm-bindgen.
 //!
@@ -18,10 +18,21 @@
 //! | kyber1024 | Enables kyber1024 mode, with a security level roughly equivalent to AES-256.                   |
 //! | 90s       | 90's mode uses SHA2 and AES-CTR as a replacement for SHAKE. This may provide hardware speedups on certain architectures.                                                           |
 //! | avx2      | On x86_64 platforms enable the optimized version. This flag is will cause a compile error on other architectures. |
-//! | wasm      | For compiling to WASM targets. |
+//! | wasm      | For compiling to WASM targets, includes a WebCrypto-style promise API. |
 //! | nasm | Uses Netwide Assembler avx2 code instead of GAS for portability. Requires a nasm compiler: https://www.nasm.us/ |
 //! | zeroize | This will zero out the key exchange structs on drop using the [zeroize](https://docs.rs/zeroize/latest/zeroize/) crate |
 //! | std | Enable the standard library |
+//! | tokio | Framed, timed-out UAKE handshakes over tokio streams |
+//! | serde | Serialize/Deserialize for `Keypair`, plus the [serialize](serialize/index.html) helpers for the key and message arrays |
+//! | jwk | Convert keys to and from JWK with RFC 7638 thumbprints |
+//! | cose | Convert keys to and from COSE_Key with RFC 9679 thumbprints |
+//! | ffi | Exports a C ABI, see the [ffi](ffi/index.html) module |
+//! | oqs | liboqs `OQS_KEM` provider shim, see the [oqs](oqs/index.html) module |
+//! | python | Python bindings through PyO3, see `python/` |
+//! | cli | Builds the `kyber` command line tool |
+//! | fault-hardened | Decapsulation runs twice with redundant checks and returns `FaultDetected` instead of a shared secret if they disagree |
+//! | masked | First-order masked decapsulation with [MaskedSecretKey](struct.MaskedSecretKey.html), not available with `90s` or `avx2` |
+//! | hazmat | Exports the IND-CPA functions and polynomial arithmetic of the compiled backend, use with caution |
 //!
 //! ## Usage
 //!
@@ -31,6 +42,12 @@
 //! export RUSTFLAGS="-C target-feature=+aes,+avx2,+sse2,+sse4.1,+bmi2,+popcnt"
 //! ```
 //!
+//! On wasm32 targets the NTT, basemul, noise sampling and Keccak use SIMD128 instructions when the target feature is enabled:
+//!
+//! ```shell
+//! export RUSTFLAGS="-C target-feature=+simd128"
+//! ```
+//!
 //! ```
 //! use pqc_kyber::*;
 //! ```
@@ -111,13 +128,35 @@
 //! ```
 //!
 //!
+//! #### Ratchet
+//! [Ratchet](struct.Ratchet.html) builds forward secret message keys on top of an initial
+//! shared secret, each sending epoch carries a fresh public key and ciphertext.
+//!
+//! #### Prekeys
+//! [PrekeyStore](struct.PrekeyStore.html) holds one-time prekeys so a key agreement can be
+//! initiated with [prekey_initiate](fn.prekey_initiate.html) while the recipient is offline.
+//!
 //! ## Errors
-//! The [KyberError](enum.KyberError.html) enum handles errors. It has two variants:
+//! The [KyberError](enum.KyberError.html) enum handles errors. It has eight variants:
+//!
+//! * **InvalidInput** - One or more byte inputs to a function are incorrectly sized.
 //!
-//! * **InvalidInput** - One or more byte inputs to a function are incorrectly sized. A likely cause of
-//! this is two parties using different security levels while trying to negotiate a key exchange.
+//! * **Decapsulation** - The ciphertext was unable to be authenticated. The shared secret was not decapsulated
 //!
-//! * **Decapsulation** - The ciphertext was unable to be authenticated. The shared secret was not decapsulated  
+//! * **RandomBytesGeneration** - Error trying to fill random bytes (i.e external (hardware) RNG modules can fail).
+//!
+//! * **ParameterMismatch** - A key exchange was initiated by a peer using a different protocol version,
+//!   security level or mode. Every UAKE/AKE initiation starts with a small header carrying these values.
+//!
+//! * **PrekeyUnavailable** - A one-time prekey is unknown or has already been consumed.
+//!
+//! * **InvalidState** - Sealed key exchange state failed to authenticate or belongs to a different
+//!   key exchange, version or parameter set.
+//!
+//! * **StateExpired** - Sealed key exchange state is past its expiry.
+//!
+//! * **FaultDetected** - A `fault-hardened` decapsulation found its redundant computations disagree.
+//!   No shared secret is returned.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::many_single_char_names)]
@@ -126,6 +165,12 @@
 #[cfg(all(feature = "kyber1024", feature = "kyber512"))]
 compile_error!("Only one security level can be specified");
 
+#[cfg(all(feature = "masked", feature = "90s"))]
+compile_error!("Masked decapsulation is only implemented for SHAKE, not 90s mode");
+
+#[cfg(all(feature = "masked", target_arch = "x86_64", feature = "avx2"))]
+compile_error!("Masked decapsulation uses the reference backend and can't be combined with avx2");
+
 #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
 mod avx2;
 #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
@@ -138,27 +183,79 @@ use reference::*;
 
 #[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
 #[cfg(feature = "hazmat")]
-pub use reference::indcpa;
+pub use reference::{indcpa, ntt, poly, polyvec, reduce};
+
+// Polynomial arithmetic of the avx2 backend, for the property tests and the
+// differential fuzz targets, which compare it against a separate build of
+// the reference code
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+#[cfg(any(fuzzing, feature = "hazmat"))]
+pub use avx2::{poly, polyvec, rejsample};
 
 #[cfg(feature = "wasm")]
 mod wasm;
+#[cfg(feature = "wasm")]
+mod webcrypto;
+
+#[cfg(feature = "tokio")]
+mod handshake;
+
+#[cfg(feature = "serde")]
+pub mod serialize;
+
+#[cfg(feature = "jwk")]
+mod jwk;
+
+#[cfg(feature = "cose")]
+mod cose;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "oqs")]
+pub mod oqs;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "masked")]
+mod masked;
+
+#[cfg(feature = "fault-simulation")]
+pub mod fault;
+#[cfg(all(feature = "fault-hardened", not(feature = "fault-simulation")))]
+mod fault;
 
 mod api;
 mod error;
 mod kem;
 mod kex;
 mod params;
+mod prekey;
+mod ratchet;
 mod rng;
+mod seal;
 mod symmetric;
 
 pub use api::*;
+#[cfg(feature = "cose")]
+pub use cose::*;
 pub use error::KyberError;
+#[cfg(feature = "tokio")]
+pub use handshake::*;
+#[cfg(feature = "jwk")]
+pub use jwk::*;
 pub use kex::*;
+#[cfg(feature = "masked")]
+pub use masked::MaskedSecretKey;
 pub use params::{
     KYBER_90S, KYBER_CIPHERTEXTBYTES, KYBER_K, KYBER_PUBLICKEYBYTES, KYBER_SECRETKEYBYTES,
     KYBER_SSBYTES, KYBER_SYMBYTES,
 };
+pub use prekey::*;
 pub use rand_core::{CryptoRng, RngCore};
+pub use ratchet::*;
+pub use seal::{SealKey, SealedState, SEALED_STATE_BYTES, SEAL_KEY_BYTES};
 
 // Feature hack to expose private functions for the Known Answer Tests
 // and fuzzing. Will fail to compile if used outside `cargo test` or
@@ -0,0 +1,153 @@
+//! First-order masking gadgets on two shares.
+//!
+//! Boolean shares `[x0, x1]` hold `x = x0 ^ x1`, arithmetic shares hold
+//! `x = x0 + x1 mod q` with each share in `0..q`. No gadget combines the two
+//! shares of a value without fresh randomness in between.
+use crate::{
+    fips202::{load64, shake128_absorb_once, shake128_squeezeblocks, SHAKE128_RATE},
+    params::*,
+    reduce::{barrett_reduce, montgomery_reduce},
+    rng::randombytes,
+    symmetric::KeccakState,
+    CryptoRng, KyberError, RngCore,
+};
+use core::ops::{BitAnd, BitXor};
+
+const Q: i16 = KYBER_Q as i16;
+
+// 2^32 mod q, takes a Montgomery product back to a plain one
+const MONT_SQ: i32 = 1353;
+
+/// Source of masking randomness, SHAKE128 seeded from the caller's RNG
+pub struct Prng {
+    state: KeccakState,
+    buf: [u8; SHAKE128_RATE],
+    pos: usize,
+}
+
+impl Prng {
+    pub fn new<R>(rng: &mut R) -> Result<Self, KyberError>
+    where
+        R: RngCore + CryptoRng,
+    {
+        let mut seed = [0u8; KYBER_SYMBYTES];
+        randombytes(&mut seed, KYBER_SYMBYTES, rng)?;
+        let mut state = KeccakState::new();
+        shake128_absorb_once(&mut state, &seed, KYBER_SYMBYTES);
+        Ok(Prng {
+            state,
+            buf: [0u8; SHAKE128_RATE],
+            pos: SHAKE128_RATE,
+        })
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        if self.pos + 8 > SHAKE128_RATE {
+            shake128_squeezeblocks(&mut self.buf, 1, &mut self.state);
+            self.pos = 0;
+        }
+        let r = load64(&self.buf[self.pos..]);
+        self.pos += 8;
+        r
+    }
+
+    pub fn u16(&mut self) -> u16 {
+        self.u64() as u16
+    }
+
+    /// Uniform in `0..q`, the bias from a 32-bit sample is below 2^-19
+    pub fn modq(&mut self) -> i16 {
+        ((self.u64() as u32 as u64 * KYBER_Q as u64) >> 32) as i16
+    }
+}
+
+/// Representative of `a` in `0..q`
+pub fn canonical(a: i16) -> i16 {
+    let r = barrett_reduce(a);
+    r + ((r >> 15) & Q)
+}
+
+/// `a + b mod q` for `a, b` in `0..q`
+pub fn addq(a: i16, b: i16) -> i16 {
+    let r = a + b - Q;
+    r + ((r >> 15) & Q)
+}
+
+/// `a - b mod q` for `a, b` in `0..q`
+pub fn subq(a: i16, b: i16) -> i16 {
+    let r = a - b;
+    r + ((r >> 15) & Q)
+}
+
+/// `a * b mod q` for `a, b` in `0..q`
+pub fn mulq(a: i16, b: i16) -> i16 {
+    let t = montgomery_reduce(a as i32 * b as i32);
+    let r = montgomery_reduce(t as i32 * MONT_SQ);
+    r + ((r >> 15) & Q)
+}
+
+/// ISW multiplication of Boolean shares, `r` is fresh randomness
+pub fn sec_and<T>(a: [T; 2], b: [T; 2], r: T) -> [T; 2]
+where
+    T: Copy + BitAnd<Output = T> + BitXor<Output = T>,
+{
+    let mut t = r ^ (a[0] & b[1]);
+    t = t ^ (a[1] & b[0]);
+    [(a[0] & b[0]) ^ r, (a[1] & b[1]) ^ t]
+}
+
+fn shl(a: [u16; 2], s: u32) -> [u16; 2] {
+    [a[0] << s, a[1] << s]
+}
+
+fn xor<T: BitXor<Output = T> + Copy>(a: [T; 2], b: [T; 2]) -> [T; 2] {
+    [a[0] ^ b[0], a[1] ^ b[1]]
+}
+
+/// Kogge-Stone addition mod 2^16 of Boolean shares
+pub fn sec_add(x: [u16; 2], y: [u16; 2], prng: &mut Prng) -> [u16; 2] {
+    let mut p = xor(x, y);
+    let mut g = sec_and(x, y, prng.u16());
+    for s in [1, 2, 4] {
+        let h = sec_and(p, shl(g, s), prng.u16());
+        g = xor(g, h);
+        p = sec_and(p, shl(p, s), prng.u16());
+    }
+    let h = sec_and(p, shl(g, 8), prng.u16());
+    g = xor(g, h);
+    xor(xor(x, y), shl(g, 1))
+}
+
+/// Boolean shares of the bit `(a - lo mod q) < len`, with `lo` in `0..q`
+/// and `len` in `1..q`. Writing `s = (a0 - lo mod q) + a1`, the value is in
+/// the interval when exactly one or three of `s < len`, `s < q` and
+/// `s < q + len` hold, each is the sign of a masked 16-bit addition.
+pub fn in_interval(a: [i16; 2], lo: i16, len: i16, prng: &mut Prng) -> [u8; 2] {
+    let r = prng.u16();
+    let x = [subq(a[0], lo) as u16 ^ r, r];
+    let mut bit = [0u16; 2];
+    for c in [len, Q, Q + len] {
+        let r = prng.u16();
+        let y = [(a[1] as u16).wrapping_sub(c as u16) ^ r, r];
+        let s = sec_add(x, y, prng);
+        bit = xor(bit, [s[0] >> 15, s[1] >> 15]);
+    }
+    [bit[0] as u8, bit[1] as u8]
+}
+
+/// Arithmetic shares of the bit `b0 ^ b1`. Each bit is shared on its own,
+/// then `b0 ^ b1 = b0 + b1 - 2 b0 b1` with an ISW product mod q.
+pub fn b2a_bit(b: [u8; 2], prng: &mut Prng) -> [i16; 2] {
+    let (r1, r2, r3) = (prng.modq(), prng.modq(), prng.modq());
+    let x = [subq(b[0] as i16, r1), r1];
+    let y = [r2, subq(b[1] as i16, r2)];
+
+    let mut t = subq(mulq(x[0], y[1]), r3);
+    t = addq(t, mulq(x[1], y[0]));
+    let p = [addq(mulq(x[0], y[0]), r3), addq(mulq(x[1], y[1]), t)];
+
+    [
+        subq(addq(x[0], y[0]), addq(p[0], p[0])),
+        subq(addq(x[1], y[1]), addq(p[1], p[1])),
+    ]
+}
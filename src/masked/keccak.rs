@@ -0,0 +1,91 @@
+//! Keccak on Boolean shares.
+//!
+//! Theta, rho, pi and iota are linear and applied to each share, chi's AND
+//! is an ISW multiplication. Inputs are at most one block, which covers every
+//! hash of a secret value in decapsulation.
+use super::gadgets::{sec_and, Prng};
+use crate::fips202::KECCAKF_ROUNDCONSTANTS;
+
+pub const SHAKE256_RATE: usize = 136;
+pub const SHA3_512_RATE: usize = 72;
+
+// Rotation offsets and lane order of the combined rho and pi steps
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+pub type State = [[u64; 25]; 2];
+
+pub fn permute(s: &mut State, prng: &mut Prng) {
+    for rc in KECCAKF_ROUNDCONSTANTS.iter() {
+        for a in s.iter_mut() {
+            // Theta
+            let mut c = [0u64; 5];
+            for x in 0..5 {
+                c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+            }
+            for x in 0..5 {
+                let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+                for y in 0..5 {
+                    a[x + 5 * y] ^= d;
+                }
+            }
+
+            // Rho and pi
+            let mut last = a[1];
+            for i in 0..24 {
+                let tmp = a[PI[i]];
+                a[PI[i]] = last.rotate_left(RHO[i]);
+                last = tmp;
+            }
+        }
+
+        // Chi, a[x] ^= !a[x + 1] & a[x + 2] with the NOT on one share
+        for y in 0..5 {
+            let mut row = [[0u64; 5]; 2];
+            for (i, a) in s.iter().enumerate() {
+                row[i].copy_from_slice(&a[5 * y..5 * y + 5]);
+            }
+            for x in 0..5 {
+                let b = [!row[0][(x + 1) % 5], row[1][(x + 1) % 5]];
+                let c = [row[0][(x + 2) % 5], row[1][(x + 2) % 5]];
+                let t = sec_and(b, c, prng.u64());
+                s[0][x + 5 * y] ^= t[0];
+                s[1][x + 5 * y] ^= t[1];
+            }
+        }
+
+        // Iota
+        s[0][0] ^= rc;
+    }
+}
+
+/// Absorbs one block shorter than the rate with the domain separation byte
+/// `p`, the padding is public so only goes on the first share
+pub fn absorb(s: &mut State, rate: usize, input: [&[u8]; 2], p: u8) {
+    debug_assert!(input[0].len() == input[1].len() && input[0].len() < rate);
+    *s = [[0u64; 25]; 2];
+    for (a, input) in s.iter_mut().zip(input.iter()) {
+        for (i, byte) in input.iter().enumerate() {
+            a[i / 8] ^= (*byte as u64) << (8 * (i % 8));
+        }
+    }
+    let len = input[0].len();
+    s[0][len / 8] ^= (p as u64) << (8 * (len % 8));
+    s[0][(rate - 1) / 8] ^= 1u64 << 63;
+}
+
+/// Squeezes shares of the output, permuting before each block
+pub fn squeeze(s: &mut State, rate: usize, out: [&mut [u8]; 2], prng: &mut Prng) {
+    let [out0, out1] = out;
+    for (block0, block1) in out0.chunks_mut(rate).zip(out1.chunks_mut(rate)) {
+        permute(s, prng);
+        for (i, (b0, b1)) in block0.iter_mut().zip(block1.iter_mut()).enumerate() {
+            *b0 = (s[0][i / 8] >> (8 * (i % 8))) as u8;
+            *b1 = (s[1][i / 8] >> (8 * (i % 8))) as u8;
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! First-order masked decapsulation.
+//!
+//! [`MaskedSecretKey`] keeps the secret key as two arithmetic shares mod q
+//! and the implicit rejection value as two Boolean shares. Decapsulation
+//! decrypts, decodes the message, hashes it, re-encrypts and compares the
+//! result with the ciphertext on shares, only the comparison result and the
+//! final shared secret are unmasked.
+//!
+//! Masks come from SHAKE128 seeded with 32 bytes from the caller's RNG on
+//! each call, and the key shares are refreshed before every decapsulation.
+//! The sharing is meant to resist first-order power analysis, it does nothing
+//! against higher-order attacks or fault injection.
+mod gadgets;
+mod keccak;
+
+use self::{gadgets::*, keccak::*};
+use crate::{
+    fips202::sha3_256,
+    indcpa::{gen_at, unpack_ciphertext, unpack_pk},
+    kex::Decapsulated,
+    params::*,
+    poly::*,
+    polyvec::*,
+    verify::cmov,
+    CryptoRng, KyberError, RngCore,
+};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+// Bits per compressed coefficient of u and v
+const KYBER_DU: usize = KYBER_POLYVECCOMPRESSEDBYTES / (KYBER_K * KYBER_N / 8);
+const KYBER_DV: usize = KYBER_POLYCOMPRESSEDBYTES / (KYBER_N / 8);
+
+type Shares<T> = [T; 2];
+
+/// A secret key split into shares for masked decapsulation
+///
+/// ### Example
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let keys = keypair(&mut rng)?;
+/// let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng)?;
+/// let (ciphertext, shared_secret) = encapsulate(&keys.public, &mut rng)?;
+/// assert_eq!(masked.decapsulate(&ciphertext, &mut rng)?, shared_secret);
+/// # Ok(())}
+/// ```
+pub struct MaskedSecretKey {
+    s: Shares<Polyvec>,
+    z: Shares<[u8; KYBER_SYMBYTES]>,
+    pk: [u8; KYBER_PUBLICKEYBYTES],
+    hpk: [u8; KYBER_SYMBYTES],
+}
+
+impl MaskedSecretKey {
+    /// Splits a secret key into shares. The unmasked key is only read here,
+    /// it should be loaded into the masked form once and then discarded.
+    pub fn new<R>(sk: &[u8], rng: &mut R) -> Result<Self, KyberError>
+    where
+        R: RngCore + CryptoRng,
+    {
+        if sk.len() != KYBER_SECRETKEYBYTES {
+            return Err(KyberError::InvalidInput);
+        }
+        const PK: usize = KYBER_INDCPA_SECRETKEYBYTES;
+        const HPK: usize = PK + KYBER_PUBLICKEYBYTES;
+        const Z: usize = HPK + KYBER_SYMBYTES;
+
+        let mut prng = Prng::new(rng)?;
+        let mut s = Polyvec::new();
+        polyvec_frombytes(&mut s, sk);
+        let mut shares = [Polyvec::new(); 2];
+        for i in 0..KYBER_K {
+            for j in 0..KYBER_N {
+                let r = prng.modq();
+                shares[0].vec[i].coeffs[j] = r;
+                shares[1].vec[i].coeffs[j] = subq(canonical(s.vec[i].coeffs[j]), r);
+            }
+        }
+
+        let mut z = [[0u8; KYBER_SYMBYTES]; 2];
+        for i in 0..KYBER_SYMBYTES {
+            z[0][i] = prng.u64() as u8;
+            z[1][i] = sk[Z + i] ^ z[0][i];
+        }
+
+        let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+        let mut hpk = [0u8; KYBER_SYMBYTES];
+        pk.copy_from_slice(&sk[PK..HPK]);
+        hpk.copy_from_slice(&sk[HPK..Z]);
+        Ok(MaskedSecretKey {
+            s: shares,
+            z,
+            pk,
+            hpk,
+        })
+    }
+
+    /// Re-randomises the shares without changing the key
+    pub fn refresh<R>(&mut self, rng: &mut R) -> Result<(), KyberError>
+    where
+        R: RngCore + CryptoRng,
+    {
+        let mut prng = Prng::new(rng)?;
+        self.remask(&mut prng);
+        Ok(())
+    }
+
+    /// Decapsulates a ciphertext on shares, giving the same shared secret as
+    /// [`decapsulate`](crate::decapsulate)
+    pub fn decapsulate<R>(&mut self, ct: &[u8], rng: &mut R) -> Decapsulated
+    where
+        R: RngCore + CryptoRng,
+    {
+        if ct.len() != KYBER_CIPHERTEXTBYTES {
+            return Err(KyberError::InvalidInput);
+        }
+        let mut prng = Prng::new(rng)?;
+        self.remask(&mut prng);
+
+        let m = self.decrypt(ct, &mut prng);
+
+        // (K, coins) = G(m || H(pk))
+        let mut buf = [[0u8; 2 * KYBER_SYMBYTES]; 2];
+        let mut kr = [[0u8; 2 * KYBER_SYMBYTES]; 2];
+        buf[0][..KYBER_SYMBYTES].copy_from_slice(&m[0]);
+        buf[0][KYBER_SYMBYTES..].copy_from_slice(&self.hpk);
+        buf[1][..KYBER_SYMBYTES].copy_from_slice(&m[1]);
+        let mut state = [[0u64; 25]; 2];
+        absorb(&mut state, SHA3_512_RATE, [&buf[0], &buf[1]], 0x06);
+        let [kr0, kr1] = &mut kr;
+        squeeze(&mut state, SHA3_512_RATE, [kr0, kr1], &mut prng);
+
+        let coins = [&kr[0][KYBER_SYMBYTES..], &kr[1][KYBER_SYMBYTES..]];
+        let fail = !self.reencrypts_to(ct, &m, coins, &mut prng) as u8;
+
+        // KDF(K || H(c)), with K replaced by z on failure
+        for i in 0..2 {
+            buf[i][..KYBER_SYMBYTES].copy_from_slice(&kr[i][..KYBER_SYMBYTES]);
+            cmov(&mut buf[i], &self.z[i], KYBER_SYMBYTES, fail);
+            buf[i][KYBER_SYMBYTES..].fill(0);
+        }
+        sha3_256(&mut buf[0][KYBER_SYMBYTES..], ct, KYBER_CIPHERTEXTBYTES);
+        let mut ss = [[0u8; KYBER_SSBYTES]; 2];
+        absorb(&mut state, SHAKE256_RATE, [&buf[0], &buf[1]], 0x1F);
+        let [ss0, ss1] = &mut ss;
+        squeeze(&mut state, SHAKE256_RATE, [ss0, ss1], &mut prng);
+
+        let mut out = [0u8; KYBER_SSBYTES];
+        for i in 0..KYBER_SSBYTES {
+            out[i] = ss[0][i] ^ ss[1][i];
+        }
+        Ok(out)
+    }
+
+    fn remask(&mut self, prng: &mut Prng) {
+        for i in 0..KYBER_K {
+            for j in 0..KYBER_N {
+                let r = prng.modq();
+                let [s0, s1] = &mut self.s;
+                s0.vec[i].coeffs[j] = addq(s0.vec[i].coeffs[j], r);
+                s1.vec[i].coeffs[j] = subq(s1.vec[i].coeffs[j], r);
+            }
+        }
+        for i in 0..KYBER_SYMBYTES {
+            let r = prng.u64() as u8;
+            self.z[0][i] ^= r;
+            self.z[1][i] ^= r;
+        }
+    }
+
+    // Boolean shares of the message, Decode(v - s^T u)
+    fn decrypt(&self, ct: &[u8], prng: &mut Prng) -> Shares<[u8; KYBER_SYMBYTES]> {
+        let (mut b, mut v) = (Polyvec::new(), Poly::new());
+        unpack_ciphertext(&mut b, &mut v, ct);
+        polyvec_ntt(&mut b);
+
+        let mut mp = [Poly::new(); 2];
+        for (mp, s) in mp.iter_mut().zip(self.s.iter()) {
+            polyvec_basemul_acc_montgomery(mp, s, &b);
+            poly_invntt_tomont(mp);
+        }
+        // v - mp0 and 0 - mp1
+        poly_sub(&mut mp[0], &v);
+        poly_sub(&mut mp[1], &Poly::new());
+
+        let (lo, len) = interval(1, 1);
+        let mut m = [[0u8; KYBER_SYMBYTES]; 2];
+        for j in 0..KYBER_N {
+            let a = [canonical(mp[0].coeffs[j]), canonical(mp[1].coeffs[j])];
+            let bit = in_interval(a, lo, len, prng);
+            m[0][j / 8] |= bit[0] << (j % 8);
+            m[1][j / 8] |= bit[1] << (j % 8);
+        }
+        m
+    }
+
+    // Re-encrypts the message shares and checks the result against ct,
+    // unmasking only whether every coefficient matched
+    fn reencrypts_to(
+        &self,
+        ct: &[u8],
+        m: &Shares<[u8; KYBER_SYMBYTES]>,
+        coins: Shares<&[u8]>,
+        prng: &mut Prng,
+    ) -> bool {
+        let mut at = [Polyvec::new(); KYBER_K];
+        let mut pkpv = Polyvec::new();
+        let mut seed = [0u8; KYBER_SYMBYTES];
+        unpack_pk(&mut pkpv, &mut seed, &self.pk);
+        gen_at(&mut at, &seed);
+
+        let mut nonce = 0u8;
+        let mut sp = [Polyvec::new(); 2];
+        let mut ep = [Polyvec::new(); 2];
+        for i in 0..KYBER_K {
+            let r = getnoise(coins, nonce, KYBER_ETA1, prng);
+            sp[0].vec[i] = r[0];
+            sp[1].vec[i] = r[1];
+            nonce += 1;
+        }
+        for i in 0..KYBER_K {
+            let r = getnoise(coins, nonce, KYBER_ETA2, prng);
+            ep[0].vec[i] = r[0];
+            ep[1].vec[i] = r[1];
+            nonce += 1;
+        }
+        let epp = getnoise(coins, nonce, KYBER_ETA2, prng);
+
+        // Decompress(m), each bit times (q + 1) / 2
+        let mut k = [Poly::new(); 2];
+        for j in 0..KYBER_N {
+            let bit = [(m[0][j / 8] >> (j % 8)) & 1, (m[1][j / 8] >> (j % 8)) & 1];
+            let a = b2a_bit(bit, prng);
+            k[0].coeffs[j] = mulq(a[0], (KYBER_Q as i16 + 1) / 2);
+            k[1].coeffs[j] = mulq(a[1], (KYBER_Q as i16 + 1) / 2);
+        }
+
+        let mut b = [Polyvec::new(); 2];
+        let mut v = [Poly::new(); 2];
+        for i in 0..2 {
+            polyvec_ntt(&mut sp[i]);
+            for (bj, atj) in b[i].vec.iter_mut().zip(at.iter()) {
+                polyvec_basemul_acc_montgomery(bj, atj, &sp[i]);
+            }
+            polyvec_basemul_acc_montgomery(&mut v[i], &pkpv, &sp[i]);
+            polyvec_invntt_tomont(&mut b[i]);
+            poly_invntt_tomont(&mut v[i]);
+
+            polyvec_add(&mut b[i], &ep[i]);
+            poly_add(&mut v[i], &epp[i]);
+            poly_add(&mut v[i], &k[i]);
+            polyvec_reduce(&mut b[i]);
+            poly_reduce(&mut v[i]);
+        }
+
+        // AND of the per coefficient matches, gathered 64 bits at a time
+        let mut acc = [!0u64, 0];
+        let mut word = [0u64; 2];
+        let mut n = 0;
+        let coeffs = (0..KYBER_K)
+            .flat_map(|i| (0..KYBER_N).map(move |j| (i, j)))
+            .map(|(i, j)| ([b[0].vec[i].coeffs[j], b[1].vec[i].coeffs[j]], KYBER_DU))
+            .chain((0..KYBER_N).map(|j| ([v[0].coeffs[j], v[1].coeffs[j]], KYBER_DV)));
+        // u and v are packed back to back, so the bit position just advances
+        let mut pos = 0;
+        for (x, d) in coeffs {
+            let c = unpack_bits(ct, pos, d);
+            pos += d;
+
+            let (lo, len) = interval(c, d);
+            let x = [canonical(x[0]), canonical(x[1])];
+            let eq = in_interval(x, lo, len, prng);
+            word[0] |= (eq[0] as u64) << n;
+            word[1] |= (eq[1] as u64) << n;
+            n += 1;
+            if n == 64 {
+                acc = sec_and(acc, word, prng.u64());
+                word = [0u64; 2];
+                n = 0;
+            }
+        }
+        for s in [32, 16, 8, 4, 2, 1] {
+            acc = sec_and(acc, [acc[0] >> s, acc[1] >> s], prng.u64());
+        }
+        (acc[0] ^ acc[1]) & 1 == 1
+    }
+}
+
+// Shares of a noise polynomial from the PRF on coin shares
+fn getnoise(coins: Shares<&[u8]>, nonce: u8, eta: usize, prng: &mut Prng) -> Shares<Poly> {
+    const MAX: usize = 3 * KYBER_N / 4;
+    let len = eta * KYBER_N / 4;
+    let mut input = [[0u8; KYBER_SYMBYTES + 1]; 2];
+    input[0][..KYBER_SYMBYTES].copy_from_slice(coins[0]);
+    input[0][KYBER_SYMBYTES] = nonce;
+    input[1][..KYBER_SYMBYTES].copy_from_slice(coins[1]);
+
+    let mut state = [[0u64; 25]; 2];
+    let mut buf = [[0u8; MAX]; 2];
+    absorb(&mut state, SHAKE256_RATE, [&input[0], &input[1]], 0x1F);
+    let [buf0, buf1] = &mut buf;
+    squeeze(
+        &mut state,
+        SHAKE256_RATE,
+        [&mut buf0[..len], &mut buf1[..len]],
+        prng,
+    );
+
+    // Centered binomial, the sum of eta bits minus the sum of the next eta
+    let mut r = [Poly::new(); 2];
+    for j in 0..KYBER_N {
+        let mut c = [0i16; 2];
+        for k in 0..2 * eta {
+            let pos = 2 * eta * j + k;
+            let bit = [
+                (buf[0][pos / 8] >> (pos % 8)) & 1,
+                (buf[1][pos / 8] >> (pos % 8)) & 1,
+            ];
+            let a = b2a_bit(bit, prng);
+            if k < eta {
+                c = [addq(c[0], a[0]), addq(c[1], a[1])];
+            } else {
+                c = [subq(c[0], a[0]), subq(c[1], a[1])];
+            }
+        }
+        r[0].coeffs[j] = c[0];
+        r[1].coeffs[j] = c[1];
+    }
+    r
+}
+
+// Inputs in 0..q that compress to c with d bits are [lo, lo + len) mod q
+fn interval(c: usize, d: usize) -> (i16, i16) {
+    let (c, q) = (c as i32, KYBER_Q as i32);
+    let round = (1 << d) - 1;
+    let lo = (c * q - q / 2 + round) >> d;
+    let hi = ((c + 1) * q - q / 2 + round) >> d;
+    let len = hi - lo;
+    ((lo + ((lo >> 31) & q)) as i16, len as i16)
+}
+
+// The d bit value starting at bit `pos` of a little endian bit string
+fn unpack_bits(a: &[u8], pos: usize, d: usize) -> usize {
+    let mut x = 0;
+    for k in 0..d {
+        x |= (((a[(pos + k) / 8] >> ((pos + k) % 8)) & 1) as usize) << k;
+    }
+    x
+}
+
+impl core::fmt::Debug for MaskedSecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("MaskedSecretKey")
+            .field("shares", &"[REDACTED]")
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for MaskedSecretKey {
+    fn drop(&mut self) {
+        for share in self.s.iter_mut() {
+            for poly in share.vec.iter_mut() {
+                poly.coeffs.zeroize();
+            }
+        }
+        self.z.zeroize();
+    }
+}
@@ -0,0 +1,85 @@
+//! liboqs `OQS_KEM` provider shim.
+//!
+//! [`OqsKem`] has the layout of liboqs' `OQS_KEM` struct from `oqs/kem.h`
+//! (liboqs 0.7 to 0.9), so a C or C++ caller can fill one with
+//! [`pqc_kyber_oqs_kem_init`] and use it wherever an `OQS_KEM *` is expected.
+//! The function pointers are the [ffi](crate::ffi) functions, their return
+//! codes match `OQS_SUCCESS` and `OQS_ERROR`.
+//!
+//! The struct is filled in place rather than allocated, it must not be passed
+//! to `OQS_KEM_free`.
+use crate::{ffi::*, params::*};
+use core::ffi::{c_char, c_int};
+
+/// Layout compatible with liboqs' `OQS_KEM`
+#[repr(C)]
+pub struct OqsKem {
+    /// Printable name of the scheme
+    pub method_name: *const c_char,
+    /// Version of the implementation
+    pub alg_version: *const c_char,
+    /// NIST security category, 1, 3 or 5
+    pub claimed_nist_level: u8,
+    /// Whether the scheme is IND-CCA secure
+    pub ind_cca: bool,
+    /// Public key length in bytes
+    pub length_public_key: usize,
+    /// Secret key length in bytes
+    pub length_secret_key: usize,
+    /// Ciphertext length in bytes
+    pub length_ciphertext: usize,
+    /// Shared secret length in bytes
+    pub length_shared_secret: usize,
+    /// Keypair generation, [`pqc_kyber_keypair`]
+    pub keypair: unsafe extern "C" fn(*mut u8, *mut u8) -> c_int,
+    /// Encapsulation, [`pqc_kyber_enc`]
+    pub encaps: unsafe extern "C" fn(*mut u8, *mut u8, *const u8) -> c_int,
+    /// Decapsulation, [`pqc_kyber_dec`]
+    pub decaps: unsafe extern "C" fn(*mut u8, *const u8, *const u8) -> c_int,
+}
+
+// liboqs method name for the selected security level and mode
+const METHOD_NAME: &str = match (KYBER_K, KYBER_90S) {
+    (2, false) => "Kyber512\0",
+    (4, false) => "Kyber1024\0",
+    (_, false) => "Kyber768\0",
+    (2, true) => "Kyber512-90s\0",
+    (4, true) => "Kyber1024-90s\0",
+    (_, true) => "Kyber768-90s\0",
+};
+
+const ALG_VERSION: &str = concat!("pqc_kyber ", env!("CARGO_PKG_VERSION"), "\0");
+
+// NIST security category of each level
+const NIST_LEVEL: u8 = match KYBER_K {
+    2 => 1,
+    4 => 5,
+    _ => 3,
+};
+
+/// Fills `kem` with this library's implementation. Returns 0 on success and
+/// -1 if `kem` is null.
+///
+/// # Safety
+///
+/// `kem` must be valid for writes of an `OQS_KEM`.
+#[no_mangle]
+pub unsafe extern "C" fn pqc_kyber_oqs_kem_init(kem: *mut OqsKem) -> c_int {
+    if kem.is_null() {
+        return -1;
+    }
+    kem.write(OqsKem {
+        method_name: METHOD_NAME.as_ptr() as *const c_char,
+        alg_version: ALG_VERSION.as_ptr() as *const c_char,
+        claimed_nist_level: NIST_LEVEL,
+        ind_cca: true,
+        length_public_key: KYBER_PUBLICKEYBYTES,
+        length_secret_key: KYBER_SECRETKEYBYTES,
+        length_ciphertext: KYBER_CIPHERTEXTBYTES,
+        length_shared_secret: KYBER_SSBYTES,
+        keypair: pqc_kyber_keypair,
+        encaps: pqc_kyber_enc,
+        decaps: pqc_kyber_dec,
+    });
+    0
+}
@@ -22,6 +22,19 @@ pub const KYBER_K: usize = if cfg!(feature = "kyber512") {
 /// Defaults to false, set`features = ["90s"]` in Cargo.toml to enable.
 pub const KYBER_90S: bool = cfg!(feature = "90s");
 
+// Name of the selected security level and mode, used as the JWK `alg` and
+// the WebCrypto algorithm name. These are round 3 Kyber keys, ML-KEM names
+// would have ML-KEM peers derive different shared secrets.
+#[cfg(any(feature = "jwk", feature = "wasm"))]
+pub(crate) const KYBER_ALGORITHM: &str = match (KYBER_K, KYBER_90S) {
+    (2, false) => "Kyber512",
+    (4, false) => "Kyber1024",
+    (_, false) => "Kyber768",
+    (2, true) => "Kyber512-90s",
+    (4, true) => "Kyber1024-90s",
+    (_, true) => "Kyber768-90s",
+};
+
 pub const KYBER_N: usize = 256;
 pub const KYBER_Q: usize = 3329;
 
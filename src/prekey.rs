@@ -0,0 +1,339 @@
+//! Asynchronous key agreement with one-time prekeys, in the style of PQXDH.
+//!
+//! The recipient keeps a [`PrekeyStore`] of one-time keypairs and publishes a
+//! [`PrekeyBundle`] for each of them, usually through a server. An initiator
+//! takes a bundle, encapsulates to both the recipient's identity key and the
+//! prekey, and sends the result while the recipient is offline. The recipient
+//! consumes the prekey when accepting so it can never be used twice.
+//!
+//! This crate has no signature scheme, bundles should be signed by the
+//! recipient over [`PrekeyBundle::to_bytes`] and verified by the initiator
+//! before use. The initiator is not authenticated by the first message.
+use crate::{
+    api::*, kem::*, kex::*, params::*, rng::randombytes, symmetric::*, CryptoRng, KyberError,
+    RngCore,
+};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Number of one-time prekeys held by a [`PrekeyStore`]
+pub const MAX_PREKEYS: usize = 32;
+/// Prekey Bundle Byte Length
+pub const PREKEY_BUNDLE_BYTES: usize = 4 + 2 * KYBER_PUBLICKEYBYTES;
+/// Prekey Initiation Byte Length
+pub const PREKEY_INIT_BYTES: usize = 4 + KYBER_PUBLICKEYBYTES + 2 * KYBER_CIPHERTEXTBYTES;
+
+/// Serialized Prekey Store Byte Length
+pub const PREKEY_STORE_BYTES: usize = 5 + MAX_PREKEYS * PREKEY_BYTES;
+
+/// Bytes to send when initiating a key agreement against a prekey bundle
+pub type PrekeySendInit = [u8; PREKEY_INIT_BYTES];
+/// Serialized prekey store, this contains secret key seeds
+pub type PrekeyStoreState = [u8; PREKEY_STORE_BYTES];
+
+const SEED_BYTES: usize = 2 * KYBER_SYMBYTES;
+const PREKEY_BYTES: usize = 5 + SEED_BYTES;
+const PREKEY_STORE_VERSION: u8 = 1;
+
+/// A recipient's identity public key and a single one-time prekey.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PrekeyBundle {
+    /// The recipient's long term public key
+    pub identity: PublicKey,
+    /// Identifier of the one-time prekey
+    pub prekey_id: u32,
+    /// The one-time prekey
+    pub prekey: PublicKey,
+}
+
+impl PrekeyBundle {
+    /// Serializes the bundle, this is the message to sign.
+    pub fn to_bytes(&self) -> [u8; PREKEY_BUNDLE_BYTES] {
+        let mut out = [0u8; PREKEY_BUNDLE_BYTES];
+        out[..KYBER_PUBLICKEYBYTES].copy_from_slice(&self.identity);
+        out[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&self.prekey_id.to_be_bytes());
+        out[KYBER_PUBLICKEYBYTES + 4..].copy_from_slice(&self.prekey);
+        out
+    }
+
+    /// Parses a bundle from the output of [`PrekeyBundle::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        if bytes.len() != PREKEY_BUNDLE_BYTES {
+            return Err(KyberError::InvalidInput);
+        }
+        let mut bundle = PrekeyBundle {
+            identity: [0u8; KYBER_PUBLICKEYBYTES],
+            prekey_id: read_u32(&bytes[KYBER_PUBLICKEYBYTES..]),
+            prekey: [0u8; KYBER_PUBLICKEYBYTES],
+        };
+        bundle
+            .identity
+            .copy_from_slice(&bytes[..KYBER_PUBLICKEYBYTES]);
+        bundle
+            .prekey
+            .copy_from_slice(&bytes[KYBER_PUBLICKEYBYTES + 4..]);
+        Ok(bundle)
+    }
+}
+
+#[cfg_attr(feature = "zeroize", derive(Zeroize))]
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct Prekey {
+    valid: bool,
+    id: u32,
+    // Keypairs are rederived from the seed when needed
+    seed: [u8; SEED_BYTES],
+}
+
+/// The recipient's one-time prekeys.
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), KyberError> {
+/// let mut rng = rand::thread_rng();
+/// let alice_keys = keypair(&mut rng)?;
+/// let bob_keys = keypair(&mut rng)?;
+///
+/// // Bob generates prekeys and publishes the bundles
+/// let mut bob_prekeys = PrekeyStore::new();
+/// bob_prekeys.replenish(&mut rng)?;
+/// let bundle = bob_prekeys.bundle(&bob_keys.public, bob_prekeys.ids().next().unwrap())?;
+///
+/// // Alice fetches a bundle while Bob is offline
+/// let (init, alice_secret) = prekey_initiate(&bundle, &alice_keys.public, &mut rng)?;
+///
+/// // Bob later accepts, consuming the prekey
+/// let bob_secret = bob_prekeys.accept(&init, &bob_keys.secret)?;
+/// assert_eq!(alice_secret, bob_secret);
+/// # Ok(()) }
+/// ```
+#[cfg_attr(feature = "zeroize", derive(Zeroize, ZeroizeOnDrop))]
+#[derive(Clone, Eq, PartialEq)]
+pub struct PrekeyStore {
+    next_id: u32,
+    prekeys: [Prekey; MAX_PREKEYS],
+}
+
+impl core::fmt::Debug for PrekeyStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("PrekeyStore")
+            .field("next_id", &self.next_id)
+            .field("len", &self.len())
+            .field("seeds", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Default for PrekeyStore {
+    fn default() -> Self {
+        PrekeyStore {
+            next_id: 0,
+            prekeys: [Prekey {
+                valid: false,
+                id: 0,
+                seed: [0u8; SEED_BYTES],
+            }; MAX_PREKEYS],
+        }
+    }
+}
+
+impl PrekeyStore {
+    /// Builds an empty prekey store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates prekeys for every empty slot, returning how many were added.
+    /// Identifiers increase monotonically and are never reused.
+    pub fn replenish<R>(&mut self, rng: &mut R) -> Result<usize, KyberError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut added = 0;
+        for prekey in self.prekeys.iter_mut().filter(|p| !p.valid) {
+            let mut seed = [0u8; SEED_BYTES];
+            randombytes(&mut seed, SEED_BYTES, rng)?;
+            *prekey = Prekey {
+                valid: true,
+                id: self.next_id,
+                seed,
+            };
+            self.next_id = self.next_id.wrapping_add(1);
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Number of unconsumed prekeys
+    pub fn len(&self) -> usize {
+        self.prekeys.iter().filter(|p| p.valid).count()
+    }
+
+    /// Returns true if every prekey has been consumed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Identifiers of the unconsumed prekeys
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.prekeys.iter().filter(|p| p.valid).map(|p| p.id)
+    }
+
+    /// Returns true if the prekey exists and has not been consumed
+    pub fn contains(&self, id: u32) -> bool {
+        self.find(id).is_some()
+    }
+
+    /// Builds the bundle to publish for a prekey
+    pub fn bundle(&self, identity: &PublicKey, id: u32) -> Result<PrekeyBundle, KyberError> {
+        let prekey = self.find(id).ok_or(KyberError::PrekeyUnavailable)?;
+        Ok(PrekeyBundle {
+            identity: *identity,
+            prekey_id: id,
+            prekey: derive(&prekey.seed)?.public,
+        })
+    }
+
+    /// Removes a prekey from the store, returning its keypair
+    pub fn consume(&mut self, id: u32) -> Result<Keypair, KyberError> {
+        let prekey = self
+            .prekeys
+            .iter_mut()
+            .find(|p| p.valid && p.id == id)
+            .ok_or(KyberError::PrekeyUnavailable)?;
+        let keys = derive(&prekey.seed);
+        prekey.valid = false;
+        prekey.seed = [0u8; SEED_BYTES];
+        keys
+    }
+
+    /// Completes a key agreement started with [`prekey_initiate`], consuming
+    /// the prekey it was made against.
+    pub fn accept(
+        &mut self,
+        init: &[u8],
+        identity: &SecretKey,
+    ) -> Result<SharedSecret, KyberError> {
+        if init.len() != PREKEY_INIT_BYTES {
+            return Err(KyberError::InvalidInput);
+        }
+        let id = read_u32(init);
+        let prekey = self.consume(id)?;
+        let bundle = PrekeyBundle {
+            identity: public(identity),
+            prekey_id: id,
+            prekey: prekey.public,
+        };
+
+        const CT_START: usize = 4 + KYBER_PUBLICKEYBYTES;
+        let mut buf = [0u8; 3 * KYBER_SYMBYTES];
+        crypto_kem_dec(
+            &mut buf,
+            &init[CT_START..][..KYBER_CIPHERTEXTBYTES],
+            identity,
+        )?;
+        crypto_kem_dec(
+            &mut buf[KYBER_SYMBYTES..],
+            &init[CT_START + KYBER_CIPHERTEXTBYTES..],
+            &prekey.secret,
+        )?;
+        transcript_hash(&mut buf[2 * KYBER_SYMBYTES..], init, &bundle);
+
+        let mut ss = [0u8; KYBER_SSBYTES];
+        kdf(&mut ss, &buf, 3 * KYBER_SYMBYTES);
+        Ok(ss)
+    }
+
+    /// Serializes the store including the prekey seeds, callers are
+    /// responsible for encrypting it at rest.
+    pub fn to_bytes(&self) -> PrekeyStoreState {
+        let mut out = [0u8; PREKEY_STORE_BYTES];
+        out[0] = PREKEY_STORE_VERSION;
+        out[1..5].copy_from_slice(&self.next_id.to_be_bytes());
+        for (chunk, prekey) in out[5..]
+            .chunks_exact_mut(PREKEY_BYTES)
+            .zip(self.prekeys.iter())
+        {
+            chunk[0] = prekey.valid as u8;
+            chunk[1..5].copy_from_slice(&prekey.id.to_be_bytes());
+            chunk[5..].copy_from_slice(&prekey.seed);
+        }
+        out
+    }
+
+    /// Restores a store from the output of [`PrekeyStore::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        if bytes.len() != PREKEY_STORE_BYTES || bytes[0] != PREKEY_STORE_VERSION {
+            return Err(KyberError::InvalidInput);
+        }
+        let mut store = Self::new();
+        store.next_id = read_u32(&bytes[1..]);
+        for (chunk, prekey) in bytes[5..]
+            .chunks_exact(PREKEY_BYTES)
+            .zip(store.prekeys.iter_mut())
+        {
+            prekey.valid = match chunk[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(KyberError::InvalidInput),
+            };
+            prekey.id = read_u32(&chunk[1..]);
+            prekey.seed.copy_from_slice(&chunk[5..]);
+        }
+        Ok(store)
+    }
+
+    fn find(&self, id: u32) -> Option<&Prekey> {
+        self.prekeys.iter().find(|p| p.valid && p.id == id)
+    }
+}
+
+/// Starts a key agreement against a recipient's prekey bundle.
+///
+/// Encapsulates to both the identity key and the one-time prekey, the
+/// initiator's static public key is bound into the shared secret. Returns the
+/// bytes to send and the shared secret.
+///
+/// `initiator_public` is not authenticated, the recipient only learns which
+/// public key the initiator claimed. Nothing is encapsulated to it, so anyone
+/// can initiate with someone else's public key.
+pub fn prekey_initiate<R>(
+    bundle: &PrekeyBundle,
+    initiator_public: &PublicKey,
+    rng: &mut R,
+) -> Result<(PrekeySendInit, SharedSecret), KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    const CT_START: usize = 4 + KYBER_PUBLICKEYBYTES;
+    let mut send = [0u8; PREKEY_INIT_BYTES];
+    let mut buf = [0u8; 3 * KYBER_SYMBYTES];
+    send[..4].copy_from_slice(&bundle.prekey_id.to_be_bytes());
+    send[4..CT_START].copy_from_slice(initiator_public);
+    crypto_kem_enc(&mut send[CT_START..], &mut buf, &bundle.identity, rng, None)?;
+    crypto_kem_enc(
+        &mut send[CT_START + KYBER_CIPHERTEXTBYTES..],
+        &mut buf[KYBER_SYMBYTES..],
+        &bundle.prekey,
+        rng,
+        None,
+    )?;
+    transcript_hash(&mut buf[2 * KYBER_SYMBYTES..], &send, bundle);
+
+    let mut ss = [0u8; KYBER_SSBYTES];
+    kdf(&mut ss, &buf, 3 * KYBER_SYMBYTES);
+    Ok((send, ss))
+}
+
+// Hash of the initiation message and the bundle it was made against
+fn transcript_hash(out: &mut [u8], init: &[u8], bundle: &PrekeyBundle) {
+    const LEN: usize = PREKEY_INIT_BYTES + PREKEY_BUNDLE_BYTES;
+    let mut buf = [0u8; LEN];
+    buf[..PREKEY_INIT_BYTES].copy_from_slice(init);
+    buf[PREKEY_INIT_BYTES..].copy_from_slice(&bundle.to_bytes());
+    hash_h(out, &buf, LEN);
+}
+
+fn read_u32(x: &[u8]) -> u32 {
+    u32::from_be_bytes([x[0], x[1], x[2], x[3]])
+}
@@ -0,0 +1,285 @@
+//! Python bindings.
+//!
+//! Keys, ciphertexts and shared secrets are passed as `bytes`. Errors are
+//! raised as subclasses of `pqc_kyber.KyberError`, one for each variant of the
+//! Rust error.
+use crate::{api, kex, params::*, KyberError};
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+use rand::rngs::OsRng;
+
+mod errors {
+    use super::*;
+
+    create_exception!(
+        pqc_kyber,
+        KyberError,
+        PyException,
+        "Base class of Kyber errors"
+    );
+    create_exception!(
+        pqc_kyber,
+        InvalidInput,
+        KyberError,
+        "An input is incorrectly sized"
+    );
+    create_exception!(
+        pqc_kyber,
+        Decapsulation,
+        KyberError,
+        "The ciphertext was invalid"
+    );
+    create_exception!(
+        pqc_kyber,
+        RandomBytesGeneration,
+        KyberError,
+        "The random number generator failed"
+    );
+    create_exception!(
+        pqc_kyber,
+        ParameterMismatch,
+        KyberError,
+        "The message was made with a different version, security level or mode"
+    );
+    create_exception!(
+        pqc_kyber,
+        PrekeyUnavailable,
+        KyberError,
+        "The prekey does not exist or was already consumed"
+    );
+    create_exception!(
+        pqc_kyber,
+        InvalidState,
+        KyberError,
+        "Sealed state failed authentication"
+    );
+    create_exception!(
+        pqc_kyber,
+        StateExpired,
+        KyberError,
+        "Sealed state has expired"
+    );
+    create_exception!(
+        pqc_kyber,
+        FaultDetected,
+        KyberError,
+        "A fault was detected during decapsulation"
+    );
+}
+
+impl From<KyberError> for PyErr {
+    fn from(err: KyberError) -> PyErr {
+        let msg = err.to_string();
+        match err {
+            KyberError::InvalidInput => errors::InvalidInput::new_err(msg),
+            KyberError::Decapsulation => errors::Decapsulation::new_err(msg),
+            KyberError::RandomBytesGeneration => errors::RandomBytesGeneration::new_err(msg),
+            KyberError::ParameterMismatch => errors::ParameterMismatch::new_err(msg),
+            KyberError::PrekeyUnavailable => errors::PrekeyUnavailable::new_err(msg),
+            KyberError::InvalidState => errors::InvalidState::new_err(msg),
+            KyberError::StateExpired => errors::StateExpired::new_err(msg),
+            KyberError::FaultDetected => errors::FaultDetected::new_err(msg),
+        }
+    }
+}
+
+type Pair<'py> = (Bound<'py, PyBytes>, Bound<'py, PyBytes>);
+
+// Copies a Python bytes argument into a fixed size array
+fn array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], KyberError> {
+    let mut out = [0u8; N];
+    if bytes.len() != N {
+        return Err(KyberError::InvalidInput);
+    }
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+/// Generates a keypair, returning `(public_key, secret_key)`
+#[pyfunction]
+fn keypair(py: Python<'_>) -> PyResult<Pair<'_>> {
+    let keys = api::keypair(&mut OsRng)?;
+    Ok((
+        PyBytes::new(py, &keys.public),
+        PyBytes::new(py, &keys.secret),
+    ))
+}
+
+/// Deterministically derives a keypair from a 64 byte seed
+#[pyfunction]
+fn derive<'py>(py: Python<'py>, seed: &[u8]) -> PyResult<Pair<'py>> {
+    let keys = api::derive(seed)?;
+    Ok((
+        PyBytes::new(py, &keys.public),
+        PyBytes::new(py, &keys.secret),
+    ))
+}
+
+/// Extracts the public key from a secret key
+#[pyfunction]
+fn public<'py>(py: Python<'py>, secret_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let sk = array::<KYBER_SECRETKEYBYTES>(secret_key)?;
+    Ok(PyBytes::new(py, &api::public(&sk)))
+}
+
+/// Encapsulates a shared secret to a public key, returning
+/// `(ciphertext, shared_secret)`
+#[pyfunction]
+fn encapsulate<'py>(py: Python<'py>, public_key: &[u8]) -> PyResult<Pair<'py>> {
+    let (ct, ss) = api::encapsulate(public_key, &mut OsRng)?;
+    Ok((PyBytes::new(py, &ct), PyBytes::new(py, &ss)))
+}
+
+/// Decapsulates the shared secret from a ciphertext
+#[pyfunction]
+fn decapsulate<'py>(
+    py: Python<'py>,
+    ciphertext: &[u8],
+    secret_key: &[u8],
+) -> PyResult<Bound<'py, PyBytes>> {
+    let ss = api::decapsulate(ciphertext, secret_key)?;
+    Ok(PyBytes::new(py, &ss))
+}
+
+/// Unilaterally authenticated key exchange
+#[pyclass(name = "Uake")]
+#[derive(Default)]
+struct PyUake(kex::Uake);
+
+#[pymethods]
+impl PyUake {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared secret, set once the exchange completes
+    #[getter]
+    fn shared_secret<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.shared_secret)
+    }
+
+    /// Starts the exchange with the server's public key
+    fn client_init<'py>(
+        &mut self,
+        py: Python<'py>,
+        server_public_key: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let pk = array(server_public_key)?;
+        let init = self.0.client_init(&pk, &mut OsRng)?;
+        Ok(PyBytes::new(py, &init))
+    }
+
+    /// Handles the client's initiation with the server's secret key
+    fn server_receive<'py>(
+        &mut self,
+        py: Python<'py>,
+        client_init: &[u8],
+        server_secret_key: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let init = array(client_init)?;
+        let sk = array(server_secret_key)?;
+        let response = self.0.server_receive(init, &sk, &mut OsRng)?;
+        Ok(PyBytes::new(py, &response))
+    }
+
+    /// Completes the exchange with the server's response
+    fn client_confirm(&mut self, server_response: &[u8]) -> PyResult<()> {
+        self.0.client_confirm(array(server_response)?)?;
+        Ok(())
+    }
+}
+
+/// Mutually authenticated key exchange
+#[pyclass(name = "Ake")]
+#[derive(Default)]
+struct PyAke(kex::Ake);
+
+#[pymethods]
+impl PyAke {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared secret, set once the exchange completes
+    #[getter]
+    fn shared_secret<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.shared_secret)
+    }
+
+    /// Starts the exchange with the server's public key
+    fn client_init<'py>(
+        &mut self,
+        py: Python<'py>,
+        server_public_key: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let pk = array(server_public_key)?;
+        let init = self.0.client_init(&pk, &mut OsRng)?;
+        Ok(PyBytes::new(py, &init))
+    }
+
+    /// Handles the client's initiation with the client's public key and the
+    /// server's secret key
+    fn server_receive<'py>(
+        &mut self,
+        py: Python<'py>,
+        client_init: &[u8],
+        client_public_key: &[u8],
+        server_secret_key: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let init = array(client_init)?;
+        let pk = array(client_public_key)?;
+        let sk = array(server_secret_key)?;
+        let response = self.0.server_receive(init, &pk, &sk, &mut OsRng)?;
+        Ok(PyBytes::new(py, &response))
+    }
+
+    /// Completes the exchange with the server's response and the client's
+    /// secret key
+    fn client_confirm(&mut self, server_response: &[u8], client_secret_key: &[u8]) -> PyResult<()> {
+        let sk = array(client_secret_key)?;
+        self.0.client_confirm(array(server_response)?, &sk)?;
+        Ok(())
+    }
+}
+
+#[pymodule]
+fn pqc_kyber(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_function(wrap_pyfunction!(keypair, m)?)?;
+    m.add_function(wrap_pyfunction!(derive, m)?)?;
+    m.add_function(wrap_pyfunction!(public, m)?)?;
+    m.add_function(wrap_pyfunction!(encapsulate, m)?)?;
+    m.add_function(wrap_pyfunction!(decapsulate, m)?)?;
+    m.add_class::<PyUake>()?;
+    m.add_class::<PyAke>()?;
+
+    m.add("KyberError", py.get_type::<errors::KyberError>())?;
+    m.add("InvalidInput", py.get_type::<errors::InvalidInput>())?;
+    m.add("Decapsulation", py.get_type::<errors::Decapsulation>())?;
+    m.add(
+        "RandomBytesGeneration",
+        py.get_type::<errors::RandomBytesGeneration>(),
+    )?;
+    m.add(
+        "ParameterMismatch",
+        py.get_type::<errors::ParameterMismatch>(),
+    )?;
+    m.add(
+        "PrekeyUnavailable",
+        py.get_type::<errors::PrekeyUnavailable>(),
+    )?;
+    m.add("InvalidState", py.get_type::<errors::InvalidState>())?;
+    m.add("StateExpired", py.get_type::<errors::StateExpired>())?;
+    m.add("FaultDetected", py.get_type::<errors::FaultDetected>())?;
+
+    m.add("KYBER_K", KYBER_K)?;
+    m.add("KYBER_90S", KYBER_90S)?;
+    m.add("PUBLICKEYBYTES", KYBER_PUBLICKEYBYTES)?;
+    m.add("SECRETKEYBYTES", KYBER_SECRETKEYBYTES)?;
+    m.add("CIPHERTEXTBYTES", KYBER_CIPHERTEXTBYTES)?;
+    m.add("SSBYTES", KYBER_SSBYTES)?;
+    m.add("UAKE_INIT_BYTES", kex::UAKE_INIT_BYTES)?;
+    m.add("AKE_INIT_BYTES", kex::AKE_INIT_BYTES)?;
+    Ok(())
+}
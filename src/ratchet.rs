@@ -0,0 +1,441 @@
+//! A KEM based ratchet for forward secret messaging.
+//!
+//! Each sending epoch carries a fresh public key along with a ciphertext
+//! encapsulated to the peer's latest public key. The encapsulated secret is
+//! mixed into the root key which seeds a new chain, chain keys then advance
+//! with SHAKE256 once per message. Message keys are handed to the caller to use
+//! with an AEAD, the header should be used as associated data.
+use crate::{api::*, fips202::shake256, kem::*, kex::*, params::*, CryptoRng, KyberError, RngCore};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Maximum number of message keys stored for out-of-order messages. Once full
+/// the oldest keys are evicted.
+pub const RATCHET_MAX_SKIP: usize = 32;
+/// Ratchet Header Byte Length
+pub const RATCHET_HEADER_BYTES: usize = 12 + KYBER_PUBLICKEYBYTES + KYBER_CIPHERTEXTBYTES;
+/// Serialized Ratchet State Byte Length
+pub const RATCHET_STATE_BYTES: usize = 2
+    + 3 * KYBER_SYMBYTES
+    + 5 * 4
+    + KYBER_SECRETKEYBYTES
+    + KYBER_PUBLICKEYBYTES
+    + KYBER_CIPHERTEXTBYTES
+    + RATCHET_MAX_SKIP * SKIPPED_BYTES
+    + 4;
+
+/// Header to send alongside each ratchet message
+pub type RatchetHeader = [u8; RATCHET_HEADER_BYTES];
+/// Serialized ratchet state, this contains secret keys
+pub type RatchetState = [u8; RATCHET_STATE_BYTES];
+/// Key for encrypting or decrypting a single message
+pub type MessageKey = [u8; KYBER_SYMBYTES];
+
+const RATCHET_STATE_VERSION: u8 = 1;
+const SKIPPED_BYTES: usize = 9 + KYBER_SYMBYTES;
+const ROOT_LABEL: &[u8] = b"pqc_kyber ratchet root";
+
+/// Error types for the ratchet
+#[derive(Debug, PartialEq)]
+pub enum RatchetError {
+    /// The underlying KEM operation failed.
+    Kyber(KyberError),
+    /// The peer's public key is unknown, a message must be received before
+    /// the responder can send.
+    NotReady,
+    /// A header skips more messages than `RATCHET_MAX_SKIP`.
+    TooManySkipped,
+    /// The message key was already used or evicted from the skipped key store.
+    MessageKeyUnavailable,
+    /// The serialized state is of the wrong length or version.
+    InvalidState,
+}
+
+impl core::fmt::Display for RatchetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RatchetError::Kyber(e) => write!(f, "{}", e),
+            RatchetError::NotReady => write!(f, "A message must be received before sending"),
+            RatchetError::TooManySkipped => write!(f, "Too many skipped messages"),
+            RatchetError::MessageKeyUnavailable => {
+                write!(f, "Message key was already used or has been evicted")
+            }
+            RatchetError::InvalidState => write!(f, "Invalid serialized ratchet state"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RatchetError {}
+
+impl From<KyberError> for RatchetError {
+    fn from(e: KyberError) -> Self {
+        RatchetError::Kyber(e)
+    }
+}
+
+#[cfg_attr(feature = "zeroize", derive(Zeroize))]
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+struct Skipped {
+    valid: bool,
+    epoch: u32,
+    n: u32,
+    key: MessageKey,
+}
+
+/// One side of a KEM ratchet session.
+///
+/// Both parties start from a shared secret agreed out of band, for example
+/// with [`Uake`]. The initiator also needs the responder's public key, the
+/// responder uses the matching keypair and can only send once it has
+/// received the first message.
+///
+/// Headers are not authenticated by the ratchet. If a message fails to
+/// decrypt restore a clone taken before calling [`Ratchet::receive`].
+///
+/// ```
+/// # use pqc_kyber::*;
+/// # fn main() -> Result<(), RatchetError> {
+/// let mut rng = rand::thread_rng();
+/// let bob_keys = keypair(&mut rng)?;
+/// let shared_secret = [7u8; KYBER_SSBYTES];
+///
+/// let mut alice = Ratchet::new_initiator(&shared_secret, &bob_keys.public, &mut rng)?;
+/// let mut bob = Ratchet::new_responder(&shared_secret, &bob_keys);
+///
+/// let (header, alice_key) = alice.send(&mut rng)?;
+/// let bob_key = bob.receive(&header)?;
+/// assert_eq!(alice_key, bob_key);
+///
+/// let (header, bob_key) = bob.send(&mut rng)?;
+/// let alice_key = alice.receive(&header)?;
+/// assert_eq!(alice_key, bob_key);
+/// # Ok(()) }
+/// ```
+#[cfg_attr(feature = "zeroize", derive(Zeroize, ZeroizeOnDrop))]
+#[derive(Clone, Eq, PartialEq)]
+pub struct Ratchet {
+    root_key: [u8; KYBER_SYMBYTES],
+    send_chain: [u8; KYBER_SYMBYTES],
+    recv_chain: [u8; KYBER_SYMBYTES],
+    // Sending epoch, message number and length of the previous sending chain
+    send_epoch: u32,
+    send_n: u32,
+    prev_n: u32,
+    // Latest receiving epoch and message number
+    recv_epoch: u32,
+    recv_n: u32,
+    // Set when the peer has sent a new public key we have not ratcheted to
+    needs_ratchet: bool,
+    has_remote: bool,
+    secret: SecretKey,
+    remote: PublicKey,
+    // Ciphertext of the current sending epoch, repeated in each header
+    ciphertext: [u8; KYBER_CIPHERTEXTBYTES],
+    skipped: [Skipped; RATCHET_MAX_SKIP],
+    skipped_next: u32,
+}
+
+// Only the epochs and counters, the chain keys, secret key and skipped
+// message keys are redacted
+impl core::fmt::Debug for Ratchet {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Ratchet")
+            .field("send_epoch", &self.send_epoch)
+            .field("send_n", &self.send_n)
+            .field("prev_n", &self.prev_n)
+            .field("recv_epoch", &self.recv_epoch)
+            .field("recv_n", &self.recv_n)
+            .field("keys", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Ratchet {
+    fn empty(shared_secret: &SharedSecret) -> Self {
+        Ratchet {
+            root_key: *shared_secret,
+            send_chain: [0u8; KYBER_SYMBYTES],
+            recv_chain: [0u8; KYBER_SYMBYTES],
+            send_epoch: 0,
+            send_n: 0,
+            prev_n: 0,
+            recv_epoch: 0,
+            recv_n: 0,
+            needs_ratchet: false,
+            has_remote: false,
+            secret: [0u8; KYBER_SECRETKEYBYTES],
+            remote: [0u8; KYBER_PUBLICKEYBYTES],
+            ciphertext: [0u8; KYBER_CIPHERTEXTBYTES],
+            skipped: [Skipped::default(); RATCHET_MAX_SKIP],
+            skipped_next: 0,
+        }
+    }
+
+    /// Starts a session as the initiator, encapsulating to the responder's public key.
+    pub fn new_initiator<R>(
+        shared_secret: &SharedSecret,
+        remote: &PublicKey,
+        rng: &mut R,
+    ) -> Result<Self, RatchetError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut ratchet = Self::empty(shared_secret);
+        ratchet.remote = *remote;
+        ratchet.has_remote = true;
+        ratchet.ratchet_send(rng)?;
+        Ok(ratchet)
+    }
+
+    /// Starts a session as the responder with the keypair the initiator encapsulated to.
+    pub fn new_responder(shared_secret: &SharedSecret, keys: &Keypair) -> Self {
+        let mut ratchet = Self::empty(shared_secret);
+        ratchet.secret = keys.secret;
+        ratchet
+    }
+
+    /// Derives the next sending message key, returning it with the header to
+    /// send alongside the encrypted message.
+    pub fn send<R>(&mut self, rng: &mut R) -> Result<(RatchetHeader, MessageKey), RatchetError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        if !self.has_remote {
+            return Err(RatchetError::NotReady);
+        }
+        if self.needs_ratchet {
+            self.ratchet_send(rng)?;
+        }
+        let mut header = [0u8; RATCHET_HEADER_BYTES];
+        header[..4].copy_from_slice(&self.send_epoch.to_be_bytes());
+        header[4..8].copy_from_slice(&self.send_n.to_be_bytes());
+        header[8..12].copy_from_slice(&self.prev_n.to_be_bytes());
+        header[12..][..KYBER_PUBLICKEYBYTES].copy_from_slice(&public(&self.secret));
+        header[12 + KYBER_PUBLICKEYBYTES..].copy_from_slice(&self.ciphertext);
+        let key = chain_step(&mut self.send_chain);
+        self.send_n += 1;
+        Ok((header, key))
+    }
+
+    /// Derives the message key for a received header, advancing the receiving
+    /// chain and ratcheting to the peer's new public key as needed.
+    ///
+    /// Messages may arrive out of order, keys for skipped messages are kept
+    /// until used or evicted.
+    pub fn receive(&mut self, header: &[u8]) -> Result<MessageKey, RatchetError> {
+        if header.len() != RATCHET_HEADER_BYTES {
+            return Err(KyberError::InvalidInput.into());
+        }
+        let epoch = read_u32(&header[..4]);
+        let n = read_u32(&header[4..8]);
+        let prev_n = read_u32(&header[8..12]);
+
+        if epoch < self.recv_epoch || (epoch == self.recv_epoch && n < self.recv_n) {
+            return self.take_skipped(epoch, n);
+        }
+        if epoch > self.recv_epoch.saturating_add(1) {
+            return Err(RatchetError::MessageKeyUnavailable);
+        }
+
+        // Work on a copy so a bad header leaves the state untouched
+        let mut next = self.clone();
+        if epoch > next.recv_epoch {
+            if next.recv_epoch > 0 {
+                next.skip_until(prev_n)?;
+            }
+            let mut ss = [0u8; KYBER_SSBYTES];
+            crypto_kem_dec(&mut ss, &header[12 + KYBER_PUBLICKEYBYTES..], &next.secret)?;
+            kdf_root(&mut next.root_key, &mut next.recv_chain, &ss);
+            next.remote
+                .copy_from_slice(&header[12..][..KYBER_PUBLICKEYBYTES]);
+            next.has_remote = true;
+            next.needs_ratchet = true;
+            next.recv_epoch = epoch;
+            next.recv_n = 0;
+        }
+        next.skip_until(n)?;
+        let key = chain_step(&mut next.recv_chain);
+        next.recv_n += 1;
+        *self = next;
+        Ok(key)
+    }
+
+    /// Serializes the ratchet state including all secret keys, callers are
+    /// responsible for encrypting it at rest.
+    pub fn to_bytes(&self) -> RatchetState {
+        let mut out = [0u8; RATCHET_STATE_BYTES];
+        let mut w = Writer {
+            buf: &mut out,
+            pos: 0,
+        };
+        w.put(&[RATCHET_STATE_VERSION]);
+        w.put(&[self.needs_ratchet as u8 | (self.has_remote as u8) << 1]);
+        w.put(&self.root_key);
+        w.put(&self.send_chain);
+        w.put(&self.recv_chain);
+        for x in [
+            self.send_epoch,
+            self.send_n,
+            self.prev_n,
+            self.recv_epoch,
+            self.recv_n,
+        ] {
+            w.put(&x.to_be_bytes());
+        }
+        w.put(&self.secret);
+        w.put(&self.remote);
+        w.put(&self.ciphertext);
+        for s in self.skipped.iter() {
+            w.put(&[s.valid as u8]);
+            w.put(&s.epoch.to_be_bytes());
+            w.put(&s.n.to_be_bytes());
+            w.put(&s.key);
+        }
+        w.put(&self.skipped_next.to_be_bytes());
+        out
+    }
+
+    /// Restores a ratchet from the output of [`Ratchet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RatchetError> {
+        if bytes.len() != RATCHET_STATE_BYTES || bytes[0] != RATCHET_STATE_VERSION {
+            return Err(RatchetError::InvalidState);
+        }
+        let mut r = Reader { buf: bytes, pos: 2 };
+        let mut ratchet = Self::empty(&[0u8; KYBER_SSBYTES]);
+        ratchet.needs_ratchet = bytes[1] & 1 == 1;
+        ratchet.has_remote = bytes[1] & 2 == 2;
+        ratchet.root_key.copy_from_slice(r.take(KYBER_SYMBYTES));
+        ratchet.send_chain.copy_from_slice(r.take(KYBER_SYMBYTES));
+        ratchet.recv_chain.copy_from_slice(r.take(KYBER_SYMBYTES));
+        ratchet.send_epoch = read_u32(r.take(4));
+        ratchet.send_n = read_u32(r.take(4));
+        ratchet.prev_n = read_u32(r.take(4));
+        ratchet.recv_epoch = read_u32(r.take(4));
+        ratchet.recv_n = read_u32(r.take(4));
+        ratchet.secret.copy_from_slice(r.take(KYBER_SECRETKEYBYTES));
+        ratchet.remote.copy_from_slice(r.take(KYBER_PUBLICKEYBYTES));
+        ratchet
+            .ciphertext
+            .copy_from_slice(r.take(KYBER_CIPHERTEXTBYTES));
+        for s in ratchet.skipped.iter_mut() {
+            s.valid = r.take(1)[0] == 1;
+            s.epoch = read_u32(r.take(4));
+            s.n = read_u32(r.take(4));
+            s.key.copy_from_slice(r.take(KYBER_SYMBYTES));
+        }
+        ratchet.skipped_next = read_u32(r.take(4));
+        if ratchet.skipped_next as usize >= RATCHET_MAX_SKIP {
+            return Err(RatchetError::InvalidState);
+        }
+        Ok(ratchet)
+    }
+
+    // Starts a new sending epoch with a fresh keypair, encapsulating to the
+    // peer's latest public key
+    fn ratchet_send<R>(&mut self, rng: &mut R) -> Result<(), RatchetError>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+        let mut sk = [0u8; KYBER_SECRETKEYBYTES];
+        let mut ct = [0u8; KYBER_CIPHERTEXTBYTES];
+        let mut ss = [0u8; KYBER_SSBYTES];
+        crypto_kem_keypair(&mut pk, &mut sk, rng, None)?;
+        crypto_kem_enc(&mut ct, &mut ss, &self.remote, rng, None)?;
+        self.secret = sk;
+        self.ciphertext = ct;
+        kdf_root(&mut self.root_key, &mut self.send_chain, &ss);
+        self.prev_n = self.send_n;
+        self.send_n = 0;
+        self.send_epoch += 1;
+        self.needs_ratchet = false;
+        Ok(())
+    }
+
+    // Stores keys for the messages of the current receiving chain up to n
+    fn skip_until(&mut self, n: u32) -> Result<(), RatchetError> {
+        if n < self.recv_n {
+            return Ok(());
+        }
+        if (n - self.recv_n) as usize > RATCHET_MAX_SKIP {
+            return Err(RatchetError::TooManySkipped);
+        }
+        while self.recv_n < n {
+            let key = chain_step(&mut self.recv_chain);
+            self.skipped[self.skipped_next as usize] = Skipped {
+                valid: true,
+                epoch: self.recv_epoch,
+                n: self.recv_n,
+                key,
+            };
+            self.skipped_next = (self.skipped_next + 1) % RATCHET_MAX_SKIP as u32;
+            self.recv_n += 1;
+        }
+        Ok(())
+    }
+
+    fn take_skipped(&mut self, epoch: u32, n: u32) -> Result<MessageKey, RatchetError> {
+        for s in self.skipped.iter_mut() {
+            if s.valid && s.epoch == epoch && s.n == n {
+                let key = s.key;
+                *s = Skipped::default();
+                return Ok(key);
+            }
+        }
+        Err(RatchetError::MessageKeyUnavailable)
+    }
+}
+
+// Mixes a newly encapsulated secret into the root key, outputting the next
+// root key and a fresh chain key
+fn kdf_root(root_key: &mut [u8], chain: &mut [u8], ss: &[u8]) {
+    const LEN: usize = ROOT_LABEL.len() + KYBER_SYMBYTES + KYBER_SSBYTES;
+    let mut buf = [0u8; LEN];
+    let mut out = [0u8; 2 * KYBER_SYMBYTES];
+    buf[..ROOT_LABEL.len()].copy_from_slice(ROOT_LABEL);
+    buf[ROOT_LABEL.len()..][..KYBER_SYMBYTES].copy_from_slice(root_key);
+    buf[LEN - KYBER_SSBYTES..].copy_from_slice(ss);
+    shake256(&mut out, 2 * KYBER_SYMBYTES, &buf, LEN);
+    root_key.copy_from_slice(&out[..KYBER_SYMBYTES]);
+    chain.copy_from_slice(&out[KYBER_SYMBYTES..]);
+}
+
+// Advances a chain key, returning the message key for this step
+fn chain_step(chain: &mut [u8; KYBER_SYMBYTES]) -> MessageKey {
+    let mut out = [0u8; 2 * KYBER_SYMBYTES];
+    shake256(&mut out, 2 * KYBER_SYMBYTES, chain, KYBER_SYMBYTES);
+    chain.copy_from_slice(&out[..KYBER_SYMBYTES]);
+    let mut key = [0u8; KYBER_SYMBYTES];
+    key.copy_from_slice(&out[KYBER_SYMBYTES..]);
+    key
+}
+
+fn read_u32(x: &[u8]) -> u32 {
+    u32::from_be_bytes([x[0], x[1], x[2], x[3]])
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, x: &[u8]) {
+        self.buf[self.pos..][..x.len()].copy_from_slice(x);
+        self.pos += x.len();
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let x = &self.buf[self.pos..][..len];
+        self.pos += len;
+        x
+    }
+}
@@ -1,6 +1,10 @@
-use crate::params::KYBER_N;
 use crate::poly::Poly;
 
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use super::simd128::cbd::{cbd2, cbd3};
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+use crate::params::KYBER_N;
+
 /// Name:  load32_littleendian
 ///
 /// Description: load 4 bytes into a 32-bit integer
@@ -9,6 +13,7 @@ use crate::poly::Poly;
 /// Arguments:   - const [u8] x: input byte array
 ///
 /// Returns 32-bit unsigned integer loaded from x
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 fn load32_littleendian(x: &[u8]) -> u32 {
     let mut r = x[0] as u32;
     r |= (x[1] as u32) << 8;
@@ -26,6 +31,7 @@ fn load32_littleendian(x: &[u8]) -> u32 {
 /// Arguments:   - const [u8] x: input byte array
 ///
 /// Returns 32-bit unsigned integer loaded from x
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 fn load24_littleendian(x: &[u8]) -> u32 {
     let mut r = x[0] as u32;
     r |= (x[1] as u32) << 8;
@@ -41,6 +47,7 @@ fn load24_littleendian(x: &[u8]) -> u32 {
 ///
 /// Arguments:   - poly *r:    output polynomial
 ///  - const [u8] buf: input byte array
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn cbd2(r: &mut Poly, buf: &[u8]) {
     let (mut d, mut t, mut a, mut b);
     for i in 0..(KYBER_N / 8) {
@@ -63,6 +70,7 @@ pub fn cbd2(r: &mut Poly, buf: &[u8]) {
 ///  This function is only needed for Kyber-512
 /// Arguments:   - poly *r:    output polynomial
 ///  - const [u8] buf: input byte array
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn cbd3(r: &mut Poly, buf: &[u8]) {
     let (mut d, mut t, mut a, mut b);
     for i in 0..(KYBER_N / 4) {
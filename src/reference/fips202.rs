@@ -41,7 +41,7 @@ pub fn store64(x: &mut [u8], mut u: u64) {
 }
 
 /// Keccak round constants
-const KECCAKF_ROUNDCONSTANTS: [u64; NROUNDS] = [
+pub const KECCAKF_ROUNDCONSTANTS: [u64; NROUNDS] = [
     0x0000000000000001,
     0x0000000000008082,
     0x800000000000808a,
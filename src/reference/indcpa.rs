@@ -24,7 +24,7 @@ fn pack_pk(r: &mut [u8], pk: &mut Polyvec, seed: &[u8]) {
 /// Arguments:   - Polyvec pk:  output public-key vector of polynomials
 ///  - [u8] seed:   output seed to generate matrix A
 ///  - const [u8] packedpk: input serialized public key
-fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
+pub(crate) fn unpack_pk(pk: &mut Polyvec, seed: &mut [u8], packedpk: &[u8]) {
     const END: usize = KYBER_SYMBYTES + KYBER_POLYVECBYTES;
     polyvec_frombytes(pk, packedpk);
     seed[..KYBER_SYMBYTES].copy_from_slice(&packedpk[KYBER_POLYVECBYTES..END]);
@@ -72,7 +72,7 @@ fn pack_ciphertext(r: &mut [u8], b: &mut Polyvec, v: Poly) {
 /// Arguments:   - Polyvec b:   output vector of polynomials b
 ///  - poly *v:  output polynomial v
 ///  - const [u8] c:   input serialized ciphertext
-fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
+pub(crate) fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
     polyvec_decompress(b, c);
     poly_decompress(v, &c[KYBER_POLYVECCOMPRESSEDBYTES..]);
 }
@@ -88,7 +88,7 @@ fn unpack_ciphertext(b: &mut Polyvec, v: &mut Poly, c: &[u8]) {
 ///  - usize buflen:  length of input buffer in bytes
 ///
 /// Returns number of sampled 16-bit integers (at most len)
-fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
+pub(crate) fn rej_uniform(r: &mut [i16], len: usize, buf: &[u8], buflen: usize) -> usize {
     let (mut ctr, mut pos) = (0usize, 0usize);
     let (mut val0, mut val1);
 
@@ -113,7 +113,7 @@ fn gen_a(a: &mut [Polyvec], b: &[u8]) {
     gen_matrix(a, b, false);
 }
 
-fn gen_at(a: &mut [Polyvec], b: &[u8]) {
+pub(crate) fn gen_at(a: &mut [Polyvec], b: &[u8]) {
     gen_matrix(a, b, true);
 }
 
@@ -127,6 +127,10 @@ fn gen_at(a: &mut [Polyvec], b: &[u8]) {
 /// Arguments:   - Polyvec a:   ouptput matrix A
 ///  - const [u8] seed: input seed
 ///  - bool transposed: boolean deciding whether A or A^T is generated
+#[cfg(any(
+    feature = "90s",
+    not(all(target_arch = "wasm32", target_feature = "simd128"))
+))]
 fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     let mut ctr;
     // 530 is expected number of required bytes
@@ -161,6 +165,78 @@ fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
     }
 }
 
+/// Name:  gen_matrix
+///
+/// Description: As above, expanding two entries at a time with the two way
+///  SIMD128 Keccak permutation. Each lane squeezes exactly the bytes the
+///  scalar version would, so the matrix is identical.
+#[cfg(all(
+    not(feature = "90s"),
+    target_arch = "wasm32",
+    target_feature = "simd128"
+))]
+fn gen_matrix(a: &mut [Polyvec], seed: &[u8], transposed: bool) {
+    use crate::simd128::fips202x2::*;
+
+    // 530 is expected number of required bytes
+    const GEN_MATRIX_NBLOCKS: usize =
+        (12 * KYBER_N / 8 * (1 << 12) / KYBER_Q + XOF_BLOCKBYTES) / XOF_BLOCKBYTES;
+    const ENTRIES: usize = KYBER_K * KYBER_K;
+    let mut buf = [[0u8; GEN_MATRIX_NBLOCKS * XOF_BLOCKBYTES + 2]; 2];
+    let mut state = KeccakX2State::new();
+
+    // An odd final entry is expanded in both lanes
+    for first in (0..ENTRIES).step_by(2) {
+        let entries = [first, (first + 1).min(ENTRIES - 1)];
+        let mut extseed = [[0u8; KYBER_SYMBYTES + 2]; 2];
+        for (ext, &n) in extseed.iter_mut().zip(entries.iter()) {
+            let (i, j) = ((n / KYBER_K) as u8, (n % KYBER_K) as u8);
+            ext[..KYBER_SYMBYTES].copy_from_slice(&seed[..KYBER_SYMBYTES]);
+            if transposed {
+                ext[KYBER_SYMBYTES] = i;
+                ext[KYBER_SYMBYTES + 1] = j;
+            } else {
+                ext[KYBER_SYMBYTES] = j;
+                ext[KYBER_SYMBYTES + 1] = i;
+            }
+        }
+        shake128x2_absorb_once(&mut state, &extseed[0], &extseed[1]);
+
+        let [buf0, buf1] = &mut buf;
+        shake128x2_squeezeblocks(buf0, buf1, GEN_MATRIX_NBLOCKS, &mut state);
+        let mut buflen = GEN_MATRIX_NBLOCKS * XOF_BLOCKBYTES;
+        let mut polys = [Poly::new(); 2];
+        let mut ctr = [0usize; 2];
+        for lane in 0..2 {
+            ctr[lane] = rej_uniform(&mut polys[lane].coeffs, KYBER_N, &buf[lane], buflen);
+        }
+
+        while ctr[0] < KYBER_N || ctr[1] < KYBER_N {
+            let off = buflen % 3;
+            for b in buf.iter_mut() {
+                b.copy_within(buflen - off..buflen, 0);
+            }
+            let [buf0, buf1] = &mut buf;
+            shake128x2_squeezeblocks(&mut buf0[off..], &mut buf1[off..], 1, &mut state);
+            buflen = off + XOF_BLOCKBYTES;
+            for lane in 0..2 {
+                if ctr[lane] < KYBER_N {
+                    ctr[lane] += rej_uniform(
+                        &mut polys[lane].coeffs[ctr[lane]..],
+                        KYBER_N - ctr[lane],
+                        &buf[lane],
+                        buflen,
+                    );
+                }
+            }
+        }
+
+        for (&n, poly) in entries.iter().zip(polys.iter()) {
+            a[n / KYBER_K].vec[n % KYBER_K] = *poly;
+        }
+    }
+}
+
 // Name:  indcpa_keypair
 //
 // Description: Generates public and private key for the CPA-secure
@@ -6,4 +6,6 @@ pub mod ntt;
 pub mod poly;
 pub mod polyvec;
 pub mod reduce;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub mod simd128;
 pub mod verify;
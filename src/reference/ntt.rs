@@ -1,5 +1,8 @@
 use crate::reduce::*;
 
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub use super::simd128::ntt::{invntt, ntt};
+
 // Code to generate zetas used in the number-theoretic transform:
 //
 //#define KYBER_ROOT_OF_UNITY 17
@@ -55,6 +58,7 @@ pub const ZETAS: [i16; 128] = [
 ///  - i16 b: second factor
 ///
 /// Returns 16-bit integer congruent to a*b*R^{-1} mod q
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn fqmul(a: i16, b: i16) -> i16 {
     montgomery_reduce(a as i32 * b as i32)
 }
@@ -65,6 +69,7 @@ pub fn fqmul(a: i16, b: i16) -> i16 {
 ///  input is in standard order, output is in bitreversed order
 ///
 /// Arguments:   - i16 r[256]: input/output vector of elements of Zq
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn ntt(r: &mut [i16]) {
     let mut j;
     let mut k = 1usize;
@@ -95,6 +100,7 @@ pub fn ntt(r: &mut [i16]) {
 ///  input is in bitreversed order, output is in standard order
 ///
 /// Arguments:   - i16 r[256]: input/output vector of elements of Zq
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn invntt(r: &mut [i16]) {
     let mut j;
     let mut k = 127usize;
@@ -132,6 +138,7 @@ pub fn invntt(r: &mut [i16]) {
 ///  - const i16 a[2]: the first factor
 ///  - const i16 b[2]: the second factor
 ///  - i16 zeta: integer defining the reduction polynomial
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn basemul(r: &mut [i16], a: &[i16], b: &[i16], zeta: i16) {
     r[0] = fqmul(a[1], b[1]);
     r[0] = fqmul(r[0], zeta);
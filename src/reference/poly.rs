@@ -32,6 +32,7 @@ pub fn poly_compress(r: &mut [u8], a: Poly) {
     let mut t = [0u8; 8];
     let mut k = 0usize;
     let mut u: i16;
+    let mut d0: u32;
 
     match KYBER_POLYCOMPRESSEDBYTES {
         128 => {
@@ -40,7 +41,12 @@ pub fn poly_compress(r: &mut [u8], a: Poly) {
                     // map to positive standard representatives
                     u = a.coeffs[8 * i + j];
                     u += (u >> 15) & KYBER_Q as i16;
-                    t[j] = (((((u as u16) << 4) + KYBER_Q as u16 / 2) / KYBER_Q as u16) & 15) as u8;
+                    // t[j] = ((((u as u16) << 4) + KYBER_Q / 2) / KYBER_Q) & 15
+                    d0 = (u as u32) << 4;
+                    d0 += 1665;
+                    d0 = d0.wrapping_mul(80635);
+                    d0 >>= 28;
+                    t[j] = (d0 & 0xf) as u8;
                 }
                 r[k] = t[0] | (t[1] << 4);
                 r[k + 1] = t[2] | (t[3] << 4);
@@ -55,7 +61,12 @@ pub fn poly_compress(r: &mut [u8], a: Poly) {
                     // map to positive standard representatives
                     u = a.coeffs[8 * i + j];
                     u += (u >> 15) & KYBER_Q as i16;
-                    t[j] = (((((u as u32) << 5) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 31) as u8;
+                    // t[j] = ((((u as u32) << 5) + KYBER_Q / 2) / KYBER_Q) & 31
+                    d0 = (u as u32) << 5;
+                    d0 += 1664;
+                    d0 = d0.wrapping_mul(40318);
+                    d0 >>= 27;
+                    t[j] = (d0 & 0x1f) as u8;
                 }
                 r[k] = t[0] | (t[1] << 5);
                 r[k + 1] = (t[1] >> 3) | (t[2] << 2) | (t[3] << 7);
@@ -208,6 +219,7 @@ pub fn poly_invntt_tomont(r: &mut Poly) {
 /// Arguments:   - poly *r:   output polynomial
 ///  - const poly *a: first input polynomial
 ///  - const poly *b: second input polynomial
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 pub fn poly_basemul(r: &mut Poly, a: &Poly, b: &Poly) {
     for i in 0..(KYBER_N / 4) {
         basemul(
@@ -225,6 +237,11 @@ pub fn poly_basemul(r: &mut Poly, a: &Poly, b: &Poly) {
     }
 }
 
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub fn poly_basemul(r: &mut Poly, a: &Poly, b: &Poly) {
+    crate::simd128::ntt::basemul(&mut r.coeffs, &a.coeffs, &b.coeffs);
+}
+
 /// Name:  poly_tomont
 ///
 /// Description: Inplace conversion of all coefficients of a polynomial
@@ -300,14 +317,20 @@ pub fn poly_frommsg(r: &mut Poly, msg: &[u8]) {
 /// Arguments:   - [u8] msg: output message
 ///  - const poly *a:  input polynomial
 pub fn poly_tomsg(msg: &mut [u8], a: Poly) {
-    let mut t;
+    let mut t: u32;
+    let mut u: i16;
 
     for i in 0..KYBER_N / 8 {
         msg[i] = 0;
         for j in 0..8 {
-            t = a.coeffs[8 * i + j];
-            t += (t >> 15) & KYBER_Q as i16;
-            t = (((t << 1) + KYBER_Q as i16 / 2) / KYBER_Q as i16) & 1;
+            u = a.coeffs[8 * i + j];
+            u += (u >> 15) & KYBER_Q as i16;
+            // t = (((u << 1) + KYBER_Q / 2) / KYBER_Q) & 1
+            t = (u as u32) << 1;
+            t += 1665;
+            t = t.wrapping_mul(80635);
+            t >>= 28;
+            t &= 1;
             msg[i] |= (t << j) as u8;
         }
     }
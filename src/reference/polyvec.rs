@@ -27,13 +27,18 @@ pub fn polyvec_compress(r: &mut [u8], a: Polyvec) {
     {
         let mut t = [0u16; 8];
         let mut idx = 0usize;
+        let mut d0: u64;
         for i in 0..KYBER_K {
             for j in 0..KYBER_N / 8 {
                 for k in 0..8 {
                     t[k] = a.vec[i].coeffs[8 * j + k] as u16;
                     t[k] = t[k].wrapping_add((((t[k] as i16) >> 15) & KYBER_Q as i16) as u16);
-                    t[k] = (((((t[k] as u32) << 11) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 0x7ff)
-                        as u16;
+                    // t[k] = ((((t[k] as u32) << 11) + KYBER_Q / 2) / KYBER_Q) & 0x7ff
+                    d0 = (t[k] as u64) << 11;
+                    d0 += 1664;
+                    d0 *= 645084;
+                    d0 >>= 31;
+                    t[k] = (d0 & 0x7ff) as u16;
                 }
                 r[idx + 0] = (t[0] >> 0) as u8;
                 r[idx + 1] = ((t[0] >> 8) | (t[1] << 3)) as u8;
@@ -55,13 +60,18 @@ pub fn polyvec_compress(r: &mut [u8], a: Polyvec) {
     {
         let mut t = [0u16; 4];
         let mut idx = 0usize;
+        let mut d0: u64;
         for i in 0..KYBER_K {
             for j in 0..KYBER_N / 4 {
                 for k in 0..4 {
                     t[k] = a.vec[i].coeffs[4 * j + k] as u16;
                     t[k] = t[k].wrapping_add((((t[k] as i16) >> 15) & KYBER_Q as i16) as u16);
-                    t[k] = (((((t[k] as u32) << 10) + KYBER_Q as u32 / 2) / KYBER_Q as u32) & 0x3ff)
-                        as u16;
+                    // t[k] = ((((t[k] as u32) << 10) + KYBER_Q / 2) / KYBER_Q) & 0x3ff
+                    d0 = (t[k] as u64) << 10;
+                    d0 += 1665;
+                    d0 *= 1290167;
+                    d0 >>= 32;
+                    t[k] = (d0 & 0x3ff) as u16;
                 }
                 r[idx + 0] = (t[0] >> 0) as u8;
                 r[idx + 1] = ((t[0] >> 8) | (t[1] << 2)) as u8;
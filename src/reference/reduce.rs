@@ -33,5 +33,5 @@ pub fn barrett_reduce(a: i16) -> i16 {
     let mut t = v * a as i32 + (1 << 25);
     t >>= 26;
     t *= KYBER_Q as i32;
-    a - t as i16
+    (a as i32 - t) as i16
 }
@@ -0,0 +1,80 @@
+use super::{load_bytes, store};
+use crate::{params::KYBER_N, poly::Poly};
+use core::arch::wasm32::*;
+
+/// Name:  cbd2
+///
+/// Description: Given an array of uniformly random bytes, compute
+///  polynomial with coefficients distributed according to
+///  a centered binomial distribution with parameter eta=2
+///
+/// Arguments:   - poly *r:    output polynomial
+///  - const [u8] buf: input byte array
+pub fn cbd2(r: &mut Poly, buf: &[u8]) {
+    let m = u32x4_splat(0x55555555);
+    let nibble = u8x16_splat(0x0f);
+    let three = u8x16_splat(0x03);
+    // 16 bytes give 32 coefficients, one from each nibble
+    for i in 0..(KYBER_N / 32) {
+        let t = load_bytes(&buf[16 * i..]);
+        let d = i32x4_add(v128_and(t, m), v128_and(u32x4_shr(t, 1), m));
+        let lo = v128_and(d, nibble);
+        let hi = v128_and(u32x4_shr(d, 4), nibble);
+        let lo = i8x16_sub(v128_and(lo, three), u8x16_shr(lo, 2));
+        let hi = i8x16_sub(v128_and(hi, three), u8x16_shr(hi, 2));
+        let c0 = i8x16_shuffle::<0, 16, 1, 17, 2, 18, 3, 19, 4, 20, 5, 21, 6, 22, 7, 23>(lo, hi);
+        let c1 =
+            i8x16_shuffle::<8, 24, 9, 25, 10, 26, 11, 27, 12, 28, 13, 29, 14, 30, 15, 31>(lo, hi);
+        let out = &mut r.coeffs[32 * i..];
+        store(out, i16x8_extend_low_i8x16(c0));
+        store(&mut out[8..], i16x8_extend_high_i8x16(c0));
+        store(&mut out[16..], i16x8_extend_low_i8x16(c1));
+        store(&mut out[24..], i16x8_extend_high_i8x16(c1));
+    }
+}
+
+/// Name:  cbd3
+///
+/// Description: Given an array of uniformly random bytes, compute
+///  polynomial with coefficients distributed according to
+///  a centered binomial distribution with parameter eta=3
+///  This function is only needed for Kyber-512
+/// Arguments:   - poly *r:    output polynomial
+///  - const [u8] buf: input byte array
+pub fn cbd3(r: &mut Poly, buf: &[u8]) {
+    let m = u32x4_splat(0x00249249);
+    let seven = u32x4_splat(0x7);
+    // 12 bytes give 16 coefficients, each 3 byte group goes in a 32-bit lane
+    for i in 0..(KYBER_N / 16) {
+        let mut bytes = [0u8; 16];
+        bytes[..12].copy_from_slice(&buf[12 * i..12 * i + 12]);
+        let t = i8x16_shuffle::<0, 1, 2, 16, 3, 4, 5, 16, 6, 7, 8, 16, 9, 10, 11, 16>(
+            load_bytes(&bytes),
+            u8x16_splat(0),
+        );
+        let d = v128_and(t, m);
+        let d = i32x4_add(d, v128_and(u32x4_shr(t, 1), m));
+        let d = i32x4_add(d, v128_and(u32x4_shr(t, 2), m));
+
+        // Coefficient j of every group, then transposed to group order
+        let c = |j: u32| {
+            let a = v128_and(u32x4_shr(d, 6 * j), seven);
+            let b = v128_and(u32x4_shr(d, 6 * j + 3), seven);
+            i32x4_sub(a, b)
+        };
+        let (c0, c1, c2, c3) = (c(0), c(1), c(2), c(3));
+        let t0 = i32x4_shuffle::<0, 4, 1, 5>(c0, c1);
+        let t1 = i32x4_shuffle::<2, 6, 3, 7>(c0, c1);
+        let t2 = i32x4_shuffle::<0, 4, 1, 5>(c2, c3);
+        let t3 = i32x4_shuffle::<2, 6, 3, 7>(c2, c3);
+        let out = &mut r.coeffs[16 * i..];
+        store(
+            out,
+            i16x8_narrow_i32x4(i64x2_shuffle::<0, 2>(t0, t2), i64x2_shuffle::<1, 3>(t0, t2)),
+        );
+        store(
+            &mut out[8..],
+            i16x8_narrow_i32x4(i64x2_shuffle::<0, 2>(t1, t3), i64x2_shuffle::<1, 3>(t1, t3)),
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Two Keccak states permuted in parallel, one in each 64-bit lane, used to
+//! expand two entries of the public matrix at once.
+use crate::fips202::{load64, store64, KECCAKF_ROUNDCONSTANTS, SHAKE128_RATE};
+use core::arch::wasm32::*;
+
+// Rotation offsets of each lane, indexed by x + 5y
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+#[derive(Copy, Clone)]
+pub struct KeccakX2State {
+    pub s: [v128; 25],
+}
+
+impl KeccakX2State {
+    pub fn new() -> Self {
+        KeccakX2State {
+            s: [u64x2_splat(0); 25],
+        }
+    }
+}
+
+#[inline(always)]
+fn rol(a: v128, offset: u32) -> v128 {
+    if offset == 0 {
+        return a;
+    }
+    v128_or(i64x2_shl(a, offset), u64x2_shr(a, 64 - offset))
+}
+
+/// Name:  keccakf1600_statepermute_x2
+///
+/// Description: The Keccak F1600 Permutation of two states at once
+///
+/// Arguments:   - v128 * a: in/output interleaved Keccak states
+pub fn keccakf1600_statepermute_x2(a: &mut [v128; 25]) {
+    let zero = u64x2_splat(0);
+    for rc in KECCAKF_ROUNDCONSTANTS.iter() {
+        // Theta
+        let mut c = [zero; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            *c = v128_xor(
+                v128_xor(v128_xor(a[x], a[x + 5]), v128_xor(a[x + 10], a[x + 15])),
+                a[x + 20],
+            );
+        }
+        for x in 0..5 {
+            let d = v128_xor(c[(x + 4) % 5], rol(c[(x + 1) % 5], 1));
+            for y in 0..5 {
+                a[x + 5 * y] = v128_xor(a[x + 5 * y], d);
+            }
+        }
+
+        // Rho and Pi
+        let mut b = [zero; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rol(a[x + 5 * y], RHO[x + 5 * y]);
+            }
+        }
+
+        // Chi
+        for y in 0..5 {
+            for x in 0..5 {
+                let not_and = v128_andnot(b[(x + 2) % 5 + 5 * y], b[(x + 1) % 5 + 5 * y]);
+                a[x + 5 * y] = v128_xor(b[x + 5 * y], not_and);
+            }
+        }
+
+        // Iota
+        a[0] = v128_xor(a[0], u64x2_splat(*rc));
+    }
+}
+
+/// Name:  shake128x2_absorb_once
+///
+/// Description: Absorbs two equal length inputs into two SHAKE128 states,
+///  non-incremental, starts by zeroeing the state.
+///
+/// Arguments:   - KeccakX2State state: output Keccak states
+///  - const [u8] in0, in1: inputs to be absorbed
+pub fn shake128x2_absorb_once(state: &mut KeccakX2State, in0: &[u8], in1: &[u8]) {
+    debug_assert_eq!(in0.len(), in1.len());
+    let s = &mut state.s;
+    let r = SHAKE128_RATE;
+    *s = [u64x2_splat(0); 25];
+
+    let (mut in0, mut in1) = (in0, in1);
+    while in0.len() >= r {
+        for i in 0..r / 8 {
+            s[i] = v128_xor(s[i], u64x2(load64(&in0[8 * i..]), load64(&in1[8 * i..])));
+        }
+        keccakf1600_statepermute_x2(s);
+        in0 = &in0[r..];
+        in1 = &in1[r..];
+    }
+
+    // Final block with SHAKE padding
+    let mut b0 = [0u8; SHAKE128_RATE];
+    let mut b1 = [0u8; SHAKE128_RATE];
+    b0[..in0.len()].copy_from_slice(in0);
+    b1[..in1.len()].copy_from_slice(in1);
+    b0[in0.len()] = 0x1F;
+    b1[in1.len()] = 0x1F;
+    b0[r - 1] |= 0x80;
+    b1[r - 1] |= 0x80;
+    for i in 0..r / 8 {
+        s[i] = v128_xor(s[i], u64x2(load64(&b0[8 * i..]), load64(&b1[8 * i..])));
+    }
+}
+
+/// Name:  shake128x2_squeezeblocks
+///
+/// Description: Squeezes full SHAKE128_RATE blocks from both states.
+///  Can be called multiple times to keep squeezing.
+///
+/// Arguments:   - [u8] out0, out1: output blocks
+///  - u64 nblocks: number of blocks to be squeezed into each output
+///  - KeccakX2State state: in/output Keccak states
+pub fn shake128x2_squeezeblocks(
+    out0: &mut [u8],
+    out1: &mut [u8],
+    nblocks: usize,
+    state: &mut KeccakX2State,
+) {
+    let r = SHAKE128_RATE;
+    for n in 0..nblocks {
+        keccakf1600_statepermute_x2(&mut state.s);
+        for i in 0..r / 8 {
+            let lanes = state.s[i];
+            store64(&mut out0[n * r + 8 * i..], u64x2_extract_lane::<0>(lanes));
+            store64(&mut out1[n * r + 8 * i..], u64x2_extract_lane::<1>(lanes));
+        }
+    }
+}
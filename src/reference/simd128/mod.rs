@@ -0,0 +1,36 @@
+//! WebAssembly SIMD128 versions of the NTT, base multiplication, centered
+//! binomial sampling and Keccak permutation.
+//!
+//! Compiled in place of the scalar code when building for wasm32 with
+//! `-C target-feature=+simd128`. Every function gives bit for bit the same
+//! output as the scalar code it replaces, so the known answer tests apply
+//! unchanged.
+use core::arch::wasm32::*;
+
+pub mod cbd;
+pub mod fips202x2;
+pub mod ntt;
+
+// Loads 8 coefficients, panics if fewer remain
+#[inline(always)]
+fn load(a: &[i16]) -> v128 {
+    assert!(a.len() >= 8);
+    // SAFETY: 16 bytes are in bounds, wasm loads have no alignment requirement
+    unsafe { v128_load(a.as_ptr() as *const v128) }
+}
+
+// Stores 8 coefficients, panics if fewer remain
+#[inline(always)]
+fn store(r: &mut [i16], v: v128) {
+    assert!(r.len() >= 8);
+    // SAFETY: 16 bytes are in bounds, wasm stores have no alignment requirement
+    unsafe { v128_store(r.as_mut_ptr() as *mut v128, v) }
+}
+
+// Loads 16 bytes, panics if fewer remain
+#[inline(always)]
+fn load_bytes(a: &[u8]) -> v128 {
+    assert!(a.len() >= 16);
+    // SAFETY: 16 bytes are in bounds, wasm loads have no alignment requirement
+    unsafe { v128_load(a.as_ptr() as *const v128) }
+}
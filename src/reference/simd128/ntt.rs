@@ -0,0 +1,192 @@
+use super::{load, store};
+use crate::{ntt::ZETAS, params::KYBER_Q};
+use core::arch::wasm32::*;
+
+const QINV: i16 = -3327; // q^(-1) mod 2^16
+const BARRETT_V: i16 = 20159; // 2^26 / q + 1
+const F: i16 = 1441; // mont^2/128
+
+// High halves of the 32-bit products of each lane
+#[inline(always)]
+fn mulhi(a: v128, b: v128) -> v128 {
+    let lo = i32x4_extmul_low_i16x8(a, b);
+    let hi = i32x4_extmul_high_i16x8(a, b);
+    i16x8_shuffle::<1, 3, 5, 7, 9, 11, 13, 15>(lo, hi)
+}
+
+// Montgomery multiplication of each lane, matches the scalar fqmul as the low
+// halves of a*b and u*q cancel exactly
+#[inline(always)]
+fn fqmul(a: v128, b: v128) -> v128 {
+    let u = i16x8_mul(i16x8_mul(a, b), i16x8_splat(QINV));
+    i16x8_sub(mulhi(a, b), mulhi(u, i16x8_splat(KYBER_Q as i16)))
+}
+
+// Barrett reduction of each lane, (a*v + 2^25) >> 26 computed from the high
+// half of a*v
+#[inline(always)]
+fn barrett_reduce(a: v128) -> v128 {
+    let t = i16x8_add(mulhi(a, i16x8_splat(BARRETT_V)), i16x8_splat(1 << 9));
+    let t = i16x8_shr(t, 10);
+    i16x8_sub(a, i16x8_mul(t, i16x8_splat(KYBER_Q as i16)))
+}
+
+// Zetas for the len 4 layer, lanes 0-3 use block k and 4-7 block k + 1
+#[inline(always)]
+fn zetas4(k: usize) -> v128 {
+    let (a, b) = (ZETAS[k], ZETAS[k + 1]);
+    i16x8(a, a, a, a, b, b, b, b)
+}
+
+// Zetas for the len 2 layer, each pair of lanes uses the next block
+#[inline(always)]
+fn zetas2(z: [i16; 4]) -> v128 {
+    i16x8(z[0], z[0], z[1], z[1], z[2], z[2], z[3], z[3])
+}
+
+/// Name:  ntt
+///
+/// Description: Inplace number-theoretic transform (NTT) in Rq
+///  input is in standard order, output is in bitreversed order
+///
+/// Arguments:   - i16 r[256]: input/output vector of elements of Zq
+pub fn ntt(r: &mut [i16]) {
+    let mut k = 1usize;
+    let mut len = 128;
+
+    // Butterflies between whole vectors
+    while len >= 8 {
+        for start in (0..256).step_by(2 * len) {
+            let zeta = i16x8_splat(ZETAS[k]);
+            k += 1;
+            for j in (start..start + len).step_by(8) {
+                let a = load(&r[j..]);
+                let t = fqmul(load(&r[j + len..]), zeta);
+                store(&mut r[j + len..], i16x8_sub(a, t));
+                store(&mut r[j..], i16x8_add(a, t));
+            }
+        }
+        len >>= 1;
+    }
+
+    // The len 4 and 2 layers shuffle 16 coefficients into two vectors
+    for i in (0..256).step_by(16) {
+        let (v0, v1) = (load(&r[i..]), load(&r[i + 8..]));
+
+        let a = i64x2_shuffle::<0, 2>(v0, v1);
+        let t = fqmul(i64x2_shuffle::<1, 3>(v0, v1), zetas4(32 + i / 8));
+        let (a, b) = (i16x8_add(a, t), i16x8_sub(a, t));
+        let (v0, v1) = (i64x2_shuffle::<0, 2>(a, b), i64x2_shuffle::<1, 3>(a, b));
+
+        let k = 64 + i / 4;
+        let zeta = zetas2([ZETAS[k], ZETAS[k + 1], ZETAS[k + 2], ZETAS[k + 3]]);
+        let a = i32x4_shuffle::<0, 2, 4, 6>(v0, v1);
+        let t = fqmul(i32x4_shuffle::<1, 3, 5, 7>(v0, v1), zeta);
+        let (a, b) = (i16x8_add(a, t), i16x8_sub(a, t));
+        store(&mut r[i..], i32x4_shuffle::<0, 4, 1, 5>(a, b));
+        store(&mut r[i + 8..], i32x4_shuffle::<2, 6, 3, 7>(a, b));
+    }
+}
+
+/// Name:  invntt
+///
+/// Description: Inplace inverse number-theoretic transform in Rq
+///  input is in bitreversed order, output is in standard order
+///
+/// Arguments:   - i16 r[256]: input/output vector of elements of Zq
+pub fn invntt(r: &mut [i16]) {
+    // The len 2 and 4 layers shuffle 16 coefficients into two vectors
+    for i in (0..256).step_by(16) {
+        let (v0, v1) = (load(&r[i..]), load(&r[i + 8..]));
+
+        let k = 127 - i / 4;
+        let zeta = zetas2([ZETAS[k], ZETAS[k - 1], ZETAS[k - 2], ZETAS[k - 3]]);
+        let a = i32x4_shuffle::<0, 2, 4, 6>(v0, v1);
+        let b = i32x4_shuffle::<1, 3, 5, 7>(v0, v1);
+        let (a, b) = (
+            barrett_reduce(i16x8_add(a, b)),
+            fqmul(i16x8_sub(b, a), zeta),
+        );
+        let (v0, v1) = (
+            i32x4_shuffle::<0, 4, 1, 5>(a, b),
+            i32x4_shuffle::<2, 6, 3, 7>(a, b),
+        );
+
+        let k = 63 - i / 8;
+        let zeta = i16x8(
+            ZETAS[k],
+            ZETAS[k],
+            ZETAS[k],
+            ZETAS[k],
+            ZETAS[k - 1],
+            ZETAS[k - 1],
+            ZETAS[k - 1],
+            ZETAS[k - 1],
+        );
+        let a = i64x2_shuffle::<0, 2>(v0, v1);
+        let b = i64x2_shuffle::<1, 3>(v0, v1);
+        let (a, b) = (
+            barrett_reduce(i16x8_add(a, b)),
+            fqmul(i16x8_sub(b, a), zeta),
+        );
+        store(&mut r[i..], i64x2_shuffle::<0, 2>(a, b));
+        store(&mut r[i + 8..], i64x2_shuffle::<1, 3>(a, b));
+    }
+
+    // Butterflies between whole vectors
+    let mut k = 31usize;
+    let mut len = 8;
+    while len <= 128 {
+        for start in (0..256).step_by(2 * len) {
+            let zeta = i16x8_splat(ZETAS[k]);
+            k -= 1;
+            for j in (start..start + len).step_by(8) {
+                let a = load(&r[j..]);
+                let b = load(&r[j + len..]);
+                store(&mut r[j..], barrett_reduce(i16x8_add(a, b)));
+                store(&mut r[j + len..], fqmul(i16x8_sub(b, a), zeta));
+            }
+        }
+        len <<= 1;
+    }
+
+    let f = i16x8_splat(F);
+    for j in (0..256).step_by(8) {
+        let a = load(&r[j..]);
+        store(&mut r[j..], fqmul(a, f));
+    }
+}
+
+/// Name:  basemul
+///
+/// Description: Multiplication of two polynomials in NTT domain, the
+///  products in Zq[X]/((X^2-zeta)) of every pair of coefficients
+///
+/// Arguments:   - i16 r[256]: the output polynomial
+///  - const i16 a[256]: the first factor
+///  - const i16 b[256]: the second factor
+pub fn basemul(r: &mut [i16], a: &[i16], b: &[i16]) {
+    for i in (0..256).step_by(16) {
+        let (a0, a1) = (load(&a[i..]), load(&a[i + 8..]));
+        let (b0, b1) = (load(&b[i..]), load(&b[i + 8..]));
+        let ae = i16x8_shuffle::<0, 2, 4, 6, 8, 10, 12, 14>(a0, a1);
+        let ao = i16x8_shuffle::<1, 3, 5, 7, 9, 11, 13, 15>(a0, a1);
+        let be = i16x8_shuffle::<0, 2, 4, 6, 8, 10, 12, 14>(b0, b1);
+        let bo = i16x8_shuffle::<1, 3, 5, 7, 9, 11, 13, 15>(b0, b1);
+
+        // Pairs alternate between zeta and -zeta
+        let z = &ZETAS[64 + i / 4..];
+        let zeta = i16x8(z[0], -z[0], z[1], -z[1], z[2], -z[2], z[3], -z[3]);
+
+        let re = i16x8_add(fqmul(fqmul(ao, bo), zeta), fqmul(ae, be));
+        let ro = i16x8_add(fqmul(ae, bo), fqmul(ao, be));
+        store(
+            &mut r[i..],
+            i16x8_shuffle::<0, 8, 1, 9, 2, 10, 3, 11>(re, ro),
+        );
+        store(
+            &mut r[i + 8..],
+            i16x8_shuffle::<4, 12, 5, 13, 6, 14, 7, 15>(re, ro),
+        );
+    }
+}
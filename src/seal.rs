@@ -0,0 +1,126 @@
+//! Sealing of in-progress key exchange state.
+//!
+//! The state is encrypted with a SHAKE256 keystream and authenticated with a
+//! keyed SHAKE256 tag over the header and ciphertext. The header carries a
+//! format version, the key exchange kind and parameter set, the caller's
+//! expiry time and a random nonce.
+use crate::{
+    fips202::shake256, kex::*, params::*, rng::randombytes, verify::verify, CryptoRng, KyberError,
+    RngCore,
+};
+
+/// Size in bytes of the key used to seal key exchange state
+pub const SEAL_KEY_BYTES: usize = 32;
+/// Sealed Key Exchange State Byte Length
+pub const SEALED_STATE_BYTES: usize = SEAL_HEADER_BYTES + STATE_BYTES + SEAL_TAG_BYTES;
+
+/// Key used to seal and unseal key exchange state
+pub type SealKey = [u8; SEAL_KEY_BYTES];
+/// Encrypted and authenticated key exchange state, safe to store in a cookie or cache
+pub type SealedState = [u8; SEALED_STATE_BYTES];
+
+const SEAL_VERSION: u8 = 1;
+const SEAL_NONCE_BYTES: usize = 32;
+const SEAL_TAG_BYTES: usize = 32;
+const SEAL_HEADER_BYTES: usize = 2 + KEX_HEADER_BYTES + 8 + SEAL_NONCE_BYTES;
+const STATE_BYTES: usize = KYBER_SSBYTES + KYBER_SECRETKEYBYTES;
+const ENC_LABEL: &[u8] = b"pqc_kyber seal enc";
+const MAC_LABEL: &[u8] = b"pqc_kyber seal mac";
+
+// Which key exchange the sealed state belongs to
+pub(crate) const SEAL_UAKE: u8 = 1;
+pub(crate) const SEAL_AKE: u8 = 2;
+
+// Seals the ephemeral temp_key and eska of a key exchange
+pub(crate) fn seal_state<R>(
+    out: &mut [u8],
+    kind: u8,
+    tk: &[u8],
+    sk: &[u8],
+    key: &SealKey,
+    expires: u64,
+    rng: &mut R,
+) -> Result<(), KyberError>
+where
+    R: CryptoRng + RngCore,
+{
+    const EXPIRES: usize = 2 + KEX_HEADER_BYTES;
+    out[0] = SEAL_VERSION;
+    out[1] = kind;
+    out[2..EXPIRES].copy_from_slice(&KEX_HEADER);
+    out[EXPIRES..][..8].copy_from_slice(&expires.to_be_bytes());
+    randombytes(
+        &mut out[EXPIRES + 8..SEAL_HEADER_BYTES],
+        SEAL_NONCE_BYTES,
+        rng,
+    )?;
+
+    let (header, body) = out.split_at_mut(SEAL_HEADER_BYTES);
+    let (ct, tag) = body.split_at_mut(STATE_BYTES);
+    keystream(ct, key, &header[EXPIRES + 8..]);
+    for (c, p) in ct.iter_mut().zip(tk.iter().chain(sk.iter())) {
+        *c ^= p;
+    }
+    mac(tag, key, header, ct);
+    Ok(())
+}
+
+// Authenticates and decrypts sealed state into temp_key and eska
+pub(crate) fn unseal_state(
+    tk: &mut [u8],
+    sk: &mut [u8],
+    kind: u8,
+    sealed: &[u8],
+    key: &SealKey,
+    now: u64,
+) -> Result<(), KyberError> {
+    const EXPIRES: usize = 2 + KEX_HEADER_BYTES;
+    if sealed.len() != SEALED_STATE_BYTES {
+        return Err(KyberError::InvalidInput);
+    }
+    let (header, body) = sealed.split_at(SEAL_HEADER_BYTES);
+    let (ct, tag) = body.split_at(STATE_BYTES);
+    let mut expected = [0u8; SEAL_TAG_BYTES];
+    mac(&mut expected, key, header, ct);
+    if verify(tag, &expected, SEAL_TAG_BYTES) != 0
+        || header[0] != SEAL_VERSION
+        || header[1] != kind
+        || header[2..EXPIRES] != KEX_HEADER
+    {
+        return Err(KyberError::InvalidState);
+    }
+    let mut expires = [0u8; 8];
+    expires.copy_from_slice(&header[EXPIRES..][..8]);
+    if now > u64::from_be_bytes(expires) {
+        return Err(KyberError::StateExpired);
+    }
+
+    let mut state = [0u8; STATE_BYTES];
+    keystream(&mut state, key, &header[EXPIRES + 8..]);
+    for (s, c) in state.iter_mut().zip(ct.iter()) {
+        *s ^= c;
+    }
+    tk.copy_from_slice(&state[..KYBER_SSBYTES]);
+    sk.copy_from_slice(&state[KYBER_SSBYTES..]);
+    Ok(())
+}
+
+fn keystream(out: &mut [u8], key: &SealKey, nonce: &[u8]) {
+    const LEN: usize = SEAL_KEY_BYTES + ENC_LABEL.len() + SEAL_NONCE_BYTES;
+    let mut buf = [0u8; LEN];
+    buf[..SEAL_KEY_BYTES].copy_from_slice(key);
+    buf[SEAL_KEY_BYTES..][..ENC_LABEL.len()].copy_from_slice(ENC_LABEL);
+    buf[LEN - SEAL_NONCE_BYTES..].copy_from_slice(nonce);
+    let outlen = out.len();
+    shake256(out, outlen, &buf, LEN);
+}
+
+fn mac(out: &mut [u8], key: &SealKey, header: &[u8], ct: &[u8]) {
+    const LEN: usize = SEAL_KEY_BYTES + MAC_LABEL.len() + SEAL_HEADER_BYTES + STATE_BYTES;
+    let mut buf = [0u8; LEN];
+    buf[..SEAL_KEY_BYTES].copy_from_slice(key);
+    buf[SEAL_KEY_BYTES..][..MAC_LABEL.len()].copy_from_slice(MAC_LABEL);
+    buf[LEN - SEAL_HEADER_BYTES - STATE_BYTES..][..SEAL_HEADER_BYTES].copy_from_slice(header);
+    buf[LEN - STATE_BYTES..].copy_from_slice(ct);
+    shake256(out, SEAL_TAG_BYTES, &buf, LEN);
+}
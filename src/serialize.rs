@@ -0,0 +1,227 @@
+//! Serde support for keys, ciphertexts and key exchange messages.
+//!
+//! Byte arrays are written as lowercase hex strings in human-readable formats
+//! and as raw bytes in binary formats. Lengths are checked on deserialization.
+//!
+//! The array aliases such as [`PublicKey`](crate::PublicKey) and
+//! [`UakeSendInit`](crate::UakeSendInit) can be used in your own structs with
+//! `#[serde(with = "pqc_kyber::serialize")]`:
+//!
+//! ```
+//! # use pqc_kyber::*;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Peer {
+//!     name: String,
+//!     #[serde(with = "pqc_kyber::serialize")]
+//!     public_key: PublicKey,
+//! }
+//! # fn main() -> Result<(), KyberError> {
+//! # let mut rng = rand::thread_rng();
+//! let keys = keypair(&mut rng)?;
+//! let peer = Peer { name: "bob".into(), public_key: keys.public };
+//! let json = serde_json::to_string(&peer).unwrap();
+//! let peer: Peer = serde_json::from_str(&json).unwrap();
+//! assert_eq!(peer.public_key, keys.public);
+//! # Ok(()) }
+//! ```
+use crate::{api::Keypair, params::*};
+use core::fmt;
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Serializes a byte array as hex or raw bytes depending on the format
+pub fn serialize<S, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    ByteSlice(bytes).serialize(serializer)
+}
+
+/// Deserializes a byte array, failing if the input is not exactly `N` bytes
+pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(ArrayVisitor::<N>)
+    } else {
+        deserializer.deserialize_bytes(ArrayVisitor::<N>)
+    }
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for b in self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+struct ArrayVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes or a hex string of {} characters", N, 2 * N)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        if s.len() != 2 * N {
+            return Err(E::invalid_length(s.len() / 2, &self));
+        }
+        let mut out = [0u8; N];
+        for (o, pair) in out.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+            match (nibble(pair[0]), nibble(pair[1])) {
+                (Some(hi), Some(lo)) => *o = hi << 4 | lo,
+                _ => return Err(E::invalid_value(de::Unexpected::Str(s), &self)),
+            }
+        }
+        Ok(out)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        if v.len() != N {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        let mut out = [0u8; N];
+        out.copy_from_slice(v);
+        Ok(out)
+    }
+
+    // Some binary formats encode bytes as a sequence
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = [0u8; N];
+        for (i, o) in out.iter_mut().enumerate() {
+            *o = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(out)
+    }
+}
+
+fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+// Wrappers so the array helpers can be used for struct fields
+struct ByteSlice<'a>(&'a [u8]);
+struct ByteArray<const N: usize>([u8; N]);
+
+impl Serialize for ByteSlice<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&Hex(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for ByteArray<N> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize(d).map(ByteArray)
+    }
+}
+
+const KEYPAIR_FIELDS: &[&str] = &["public", "secret"];
+
+enum KeypairField {
+    Public,
+    Secret,
+}
+
+impl<'de> Deserialize<'de> for KeypairField {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = KeypairField;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "`public` or `secret`")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<KeypairField, E> {
+                match v {
+                    "public" => Ok(KeypairField::Public),
+                    "secret" => Ok(KeypairField::Secret),
+                    _ => Err(E::unknown_field(v, KEYPAIR_FIELDS)),
+                }
+            }
+        }
+
+        d.deserialize_identifier(FieldVisitor)
+    }
+}
+
+impl Serialize for Keypair {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Keypair", 2)?;
+        state.serialize_field("public", &ByteSlice(&self.public))?;
+        state.serialize_field("secret", &ByteSlice(&self.secret))?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Keypair {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Keypair", KEYPAIR_FIELDS, KeypairVisitor)
+    }
+}
+
+struct KeypairVisitor;
+
+impl<'de> Visitor<'de> for KeypairVisitor {
+    type Value = Keypair;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a Kyber keypair")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Keypair, A::Error> {
+        let public: ByteArray<KYBER_PUBLICKEYBYTES> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let secret: ByteArray<KYBER_SECRETKEYBYTES> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Keypair {
+            public: public.0,
+            secret: secret.0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Keypair, A::Error> {
+        let mut public: Option<ByteArray<KYBER_PUBLICKEYBYTES>> = None;
+        let mut secret: Option<ByteArray<KYBER_SECRETKEYBYTES>> = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                KeypairField::Public if public.is_none() => public = Some(map.next_value()?),
+                KeypairField::Secret if secret.is_none() => secret = Some(map.next_value()?),
+                KeypairField::Public => return Err(de::Error::duplicate_field("public")),
+                KeypairField::Secret => return Err(de::Error::duplicate_field("secret")),
+            }
+        }
+        Ok(Keypair {
+            public: public.ok_or_else(|| de::Error::missing_field("public"))?.0,
+            secret: secret.ok_or_else(|| de::Error::missing_field("secret"))?.0,
+        })
+    }
+}
@@ -6,47 +6,74 @@ use crate::params::*;
 use alloc::boxed::Box;
 use wasm_bindgen::prelude::*;
 
+// With std, JsError's blanket impl over std::error::Error applies instead
+#[cfg(not(feature = "std"))]
+impl From<KyberError> for JsError {
+    fn from(err: KyberError) -> JsError {
+        JsError::new(&alloc::string::ToString::to_string(&err))
+    }
+}
+
+// Copies a JS byte array into a fixed size array
+fn array<const N: usize>(bytes: &[u8], name: &str) -> Result<[u8; N], JsError> {
+    if bytes.len() != N {
+        return Err(JsError::new(&alloc::format!(
+            "{} must be {} bytes, found {}",
+            name,
+            N,
+            bytes.len()
+        )));
+    }
+    let mut out = [0u8; N];
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
 #[wasm_bindgen]
 pub fn keypair() -> Result<Keys, JsError> {
     let mut rng = rand::rngs::OsRng {};
-    match api::keypair(&mut rng) {
-        Ok(keys) => Ok(Keys {
-            pubkey: Box::new(keys.public),
-            secret: Box::new(keys.secret),
-        }),
-        Err(KyberError::RandomBytesGeneration) => {
-            Err(JsError::new("Error trying to fill random bytes"))
-        }
-        _ => Err(JsError::new("The keypair could not be generated")),
-    }
+    let keys = api::keypair(&mut rng)?;
+    Ok(Keys {
+        pubkey: Box::new(keys.public),
+        secret: Box::new(keys.secret),
+    })
 }
 
+/// Deterministically derives a keypair from a 64 byte seed
 #[wasm_bindgen]
-pub fn encapsulate(pk: Box<[u8]>) -> Result<Kex, JsValue> {
-    if pk.len() != KYBER_PUBLICKEYBYTES {
-        return Err(JsValue::null());
-    }
+pub fn derive(seed: Box<[u8]>) -> Result<Keys, JsError> {
+    array::<64>(&seed, "Seed")?;
+    let keys = api::derive(&seed)?;
+    Ok(Keys {
+        pubkey: Box::new(keys.public),
+        secret: Box::new(keys.secret),
+    })
+}
 
-    let mut rng = rand::rngs::OsRng {};
-    match api::encapsulate(&pk, &mut rng) {
-        Ok(kex) => Ok(Kex {
-            ciphertext: Box::new(kex.0),
-            sharedSecret: Box::new(kex.1),
-        }),
-        Err(_) => Err(JsValue::null()),
-    }
+/// Extracts the public key from a secret key, exported as `publicKey` as
+/// `public` is reserved in JavaScript
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(sk: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+    let sk = array::<KYBER_SECRETKEYBYTES>(&sk, "Secret key")?;
+    Ok(Box::new(api::public(&sk)))
 }
 
 #[wasm_bindgen]
-pub fn decapsulate(ct: Box<[u8]>, sk: Box<[u8]>) -> Result<Box<[u8]>, JsValue> {
-    if ct.len() != KYBER_CIPHERTEXTBYTES || sk.len() != KYBER_SECRETKEYBYTES {
-        return Err(JsValue::null());
-    }
+pub fn encapsulate(pk: Box<[u8]>) -> Result<Kex, JsError> {
+    array::<KYBER_PUBLICKEYBYTES>(&pk, "Public key")?;
+    let mut rng = rand::rngs::OsRng {};
+    let (ciphertext, shared_secret) = api::encapsulate(&pk, &mut rng)?;
+    Ok(Kex {
+        ciphertext: Box::new(ciphertext),
+        sharedSecret: Box::new(shared_secret),
+    })
+}
 
-    match api::decapsulate(&ct, &sk) {
-        Ok(ss) => Ok(Box::new(ss)),
-        Err(_) => Err(JsValue::null()),
-    }
+#[wasm_bindgen]
+pub fn decapsulate(ct: Box<[u8]>, sk: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+    array::<KYBER_CIPHERTEXTBYTES>(&ct, "Ciphertext")?;
+    array::<KYBER_SECRETKEYBYTES>(&sk, "Secret key")?;
+    Ok(Box::new(api::decapsulate(&ct, &sk)?))
 }
 
 #[wasm_bindgen]
@@ -82,8 +109,8 @@ impl Keys {
 #[wasm_bindgen]
 impl Kex {
     #[wasm_bindgen(constructor)]
-    pub fn new(public_key: Box<[u8]>) -> Self {
-        encapsulate(public_key).expect("Invalid Public Key Size")
+    pub fn new(public_key: Box<[u8]>) -> Result<Kex, JsError> {
+        encapsulate(public_key)
     }
 
     #[wasm_bindgen(getter)]
@@ -140,4 +167,107 @@ impl Params {
     pub fn sharedSecretBytes() -> usize {
         KYBER_SSBYTES
     }
+
+    /// Algorithm name for `generateKey` and the other promise functions
+    #[wasm_bindgen(getter)]
+    pub fn algorithm() -> alloc::string::String {
+        crate::params::KYBER_ALGORITHM.into()
+    }
+}
+
+/// Unilaterally authenticated key exchange
+#[wasm_bindgen(js_name = Uake)]
+#[derive(Default)]
+pub struct WasmUake(Uake);
+
+#[wasm_bindgen(js_class = Uake)]
+impl WasmUake {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmUake {
+        WasmUake::default()
+    }
+
+    /// The shared secret, set once the exchange completes
+    #[wasm_bindgen(getter)]
+    pub fn sharedSecret(&self) -> Box<[u8]> {
+        Box::new(self.0.shared_secret)
+    }
+
+    /// Starts the exchange with the server's public key
+    pub fn clientInit(&mut self, serverPublicKey: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+        let pk = array(&serverPublicKey, "Server public key")?;
+        let mut rng = rand::rngs::OsRng {};
+        Ok(Box::new(self.0.client_init(&pk, &mut rng)?))
+    }
+
+    /// Handles the client's initiation with the server's secret key
+    pub fn serverReceive(
+        &mut self,
+        clientInit: Box<[u8]>,
+        serverSecretKey: Box<[u8]>,
+    ) -> Result<Box<[u8]>, JsError> {
+        let init = array(&clientInit, "Client init")?;
+        let sk = array(&serverSecretKey, "Server secret key")?;
+        let mut rng = rand::rngs::OsRng {};
+        Ok(Box::new(self.0.server_receive(init, &sk, &mut rng)?))
+    }
+
+    /// Completes the exchange with the server's response
+    pub fn clientConfirm(&mut self, serverResponse: Box<[u8]>) -> Result<(), JsError> {
+        let response = array(&serverResponse, "Server response")?;
+        Ok(self.0.client_confirm(response)?)
+    }
+}
+
+/// Mutually authenticated key exchange
+#[wasm_bindgen(js_name = Ake)]
+#[derive(Default)]
+pub struct WasmAke(Ake);
+
+#[wasm_bindgen(js_class = Ake)]
+impl WasmAke {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmAke {
+        WasmAke::default()
+    }
+
+    /// The shared secret, set once the exchange completes
+    #[wasm_bindgen(getter)]
+    pub fn sharedSecret(&self) -> Box<[u8]> {
+        Box::new(self.0.shared_secret)
+    }
+
+    /// Starts the exchange with the server's public key
+    pub fn clientInit(&mut self, serverPublicKey: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+        let pk = array(&serverPublicKey, "Server public key")?;
+        let mut rng = rand::rngs::OsRng {};
+        Ok(Box::new(self.0.client_init(&pk, &mut rng)?))
+    }
+
+    /// Handles the client's initiation with the client's public key and the
+    /// server's secret key
+    pub fn serverReceive(
+        &mut self,
+        clientInit: Box<[u8]>,
+        clientPublicKey: Box<[u8]>,
+        serverSecretKey: Box<[u8]>,
+    ) -> Result<Box<[u8]>, JsError> {
+        let init = array(&clientInit, "Client init")?;
+        let pk = array(&clientPublicKey, "Client public key")?;
+        let sk = array(&serverSecretKey, "Server secret key")?;
+        let mut rng = rand::rngs::OsRng {};
+        Ok(Box::new(self.0.server_receive(init, &pk, &sk, &mut rng)?))
+    }
+
+    /// Completes the exchange with the server's response and the client's
+    /// secret key
+    pub fn clientConfirm(
+        &mut self,
+        serverResponse: Box<[u8]>,
+        clientSecretKey: Box<[u8]>,
+    ) -> Result<(), JsError> {
+        let response = array(&serverResponse, "Server response")?;
+        let sk = array(&clientSecretKey, "Client secret key")?;
+        Ok(self.0.client_confirm(response, &sk)?)
+    }
 }
@@ -0,0 +1,457 @@
+//! WebCrypto-style promise API for the wasm package.
+//!
+//! Follows the key encapsulation methods of the WICG "Modern Algorithms in
+//! WebCrypto" proposal, so code written against this module should port to
+//! `crypto.subtle` with few changes once browsers ship it:
+//!
+//! ```js
+//! const keys = await kyber.generateKey({ name: "Kyber768" }, true,
+//!     ["encapsulateBits", "decapsulateBits"]);
+//! const { sharedKey, ciphertext } =
+//!     await kyber.encapsulateBits({ name: "Kyber768" }, keys.publicKey);
+//! const secret =
+//!     await kyber.decapsulateBits({ name: "Kyber768" }, keys.privateKey, ciphertext);
+//! ```
+//!
+//! Only the level and mode this library was built with is available. Errors
+//! are `Error`s named after the `DOMException` WebCrypto would throw.
+//!
+//! This crate implements round 3 Kyber, not FIPS 203 ML-KEM, so algorithms
+//! are named `Kyber512`, `Kyber768` and `Kyber1024` and ML-KEM names are
+//! rejected. There are no OIDs for round 3 Kyber, keys are only available in
+//! the raw and jwk formats. The jwk private key is the expanded secret key,
+//! ML-KEM seeds would expand to a different keypair and are rejected.
+extern crate alloc;
+
+use crate::{api, params::*, KyberError};
+use alloc::{format, string::String, vec::Vec};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use js_sys::{Array, ArrayBuffer, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+
+const PUBLIC_USAGES: [&str; 1] = ["encapsulateBits"];
+const PRIVATE_USAGES: [&str; 1] = ["decapsulateBits"];
+
+// Builds an `Error` with the name of the matching `DOMException`
+fn error(name: &str, msg: &str) -> JsValue {
+    let err = js_sys::Error::new(msg);
+    err.set_name(name);
+    err.into()
+}
+
+impl From<KyberError> for JsValue {
+    fn from(err: KyberError) -> JsValue {
+        error("OperationError", &alloc::string::ToString::to_string(&err))
+    }
+}
+
+fn data_error(msg: &str) -> JsValue {
+    error("DataError", msg)
+}
+
+/// Public or private half of a keypair, shaped like a WebCrypto `CryptoKey`
+#[wasm_bindgen(js_name = KyberKey)]
+pub struct KyberKey {
+    private: bool,
+    extractable: bool,
+    usages: Vec<String>,
+    bytes: Vec<u8>,
+}
+
+#[wasm_bindgen(js_class = KyberKey)]
+impl KyberKey {
+    /// Either `"public"` or `"private"`
+    #[wasm_bindgen(getter = type)]
+    pub fn key_type(&self) -> String {
+        let kind = if self.private { "private" } else { "public" };
+        kind.into()
+    }
+
+    /// Whether the key may be exported, public keys always are
+    #[wasm_bindgen(getter)]
+    pub fn extractable(&self) -> bool {
+        self.extractable
+    }
+
+    /// A `{ name }` algorithm dictionary
+    #[wasm_bindgen(getter)]
+    pub fn algorithm(&self) -> Object {
+        algorithm()
+    }
+
+    /// Operations the key may be used for
+    #[wasm_bindgen(getter)]
+    pub fn usages(&self) -> Array {
+        self.usages.iter().map(|u| JsValue::from_str(u)).collect()
+    }
+}
+
+impl KyberKey {
+    fn public(bytes: Vec<u8>, usages: Vec<String>) -> Result<KyberKey, JsValue> {
+        check_usages(&usages, &PUBLIC_USAGES)?;
+        if bytes.len() != KYBER_PUBLICKEYBYTES {
+            return Err(data_error("Invalid public key length"));
+        }
+        Ok(KyberKey {
+            private: false,
+            extractable: true,
+            usages,
+            bytes,
+        })
+    }
+
+    fn private(
+        bytes: Vec<u8>,
+        extractable: bool,
+        usages: Vec<String>,
+    ) -> Result<KyberKey, JsValue> {
+        check_usages(&usages, &PRIVATE_USAGES)?;
+        if usages.is_empty() {
+            return Err(error("SyntaxError", "Private keys need at least one usage"));
+        }
+        if bytes.len() != KYBER_SECRETKEYBYTES {
+            return Err(data_error("Invalid private key length"));
+        }
+        Ok(KyberKey {
+            private: true,
+            extractable,
+            usages,
+            bytes,
+        })
+    }
+
+    fn allows(&self, usage: &str) -> Result<(), JsValue> {
+        if !self.usages.iter().any(|u| u == usage) {
+            return Err(error(
+                "InvalidAccessError",
+                &format!("Key usages do not include {}", usage),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The result of [`generate_key`], shaped like a WebCrypto `CryptoKeyPair`
+#[wasm_bindgen(js_name = KyberKeyPair)]
+pub struct KyberKeyPair {
+    public: KyberKey,
+    private: KyberKey,
+}
+
+#[wasm_bindgen(js_class = KyberKeyPair)]
+impl KyberKeyPair {
+    #[wasm_bindgen(getter = publicKey)]
+    pub fn public_key(&self) -> KyberKey {
+        self.public.clone()
+    }
+
+    #[wasm_bindgen(getter = privateKey)]
+    pub fn private_key(&self) -> KyberKey {
+        self.private.clone()
+    }
+}
+
+impl Clone for KyberKey {
+    fn clone(&self) -> Self {
+        KyberKey {
+            private: self.private,
+            extractable: self.extractable,
+            usages: self.usages.clone(),
+            bytes: self.bytes.clone(),
+        }
+    }
+}
+
+/// Generates a keypair, resolving to a [`KyberKeyPair`]. `algorithm` is a
+/// name or `{ name }` dictionary and `keyUsages` is split between the two keys.
+#[wasm_bindgen(js_name = generateKey)]
+pub fn generate_key(algorithm: JsValue, extractable: bool, key_usages: Array) -> Promise {
+    settle(generate(&algorithm, extractable, &key_usages))
+}
+
+/// Encapsulates a shared secret to a public key, resolving to
+/// `{ sharedKey, ciphertext }` as `ArrayBuffer`s
+#[wasm_bindgen(js_name = encapsulateBits)]
+pub fn encapsulate_bits(algorithm: JsValue, key: &KyberKey) -> Promise {
+    settle(encapsulate(&algorithm, key))
+}
+
+/// Decapsulates the shared secret from a ciphertext, resolving to an
+/// `ArrayBuffer`
+#[wasm_bindgen(js_name = decapsulateBits)]
+pub fn decapsulate_bits(algorithm: JsValue, key: &KyberKey, ciphertext: JsValue) -> Promise {
+    settle(decapsulate(&algorithm, key, &ciphertext))
+}
+
+/// Exports a key as `"raw"` (public keys) or `"jwk"` (either). Raw keys
+/// resolve to an `ArrayBuffer`, jwk to an object.
+#[wasm_bindgen(js_name = exportKey)]
+pub fn export_key(format: String, key: &KyberKey) -> Promise {
+    settle(export(&format, key))
+}
+
+/// Imports a key exported by [`export_key`], resolving to a [`KyberKey`].
+/// `keyData` is a `BufferSource` for raw keys or an object for jwk.
+#[wasm_bindgen(js_name = importKey)]
+pub fn import_key(
+    format: String,
+    key_data: JsValue,
+    algorithm: JsValue,
+    extractable: bool,
+    key_usages: Array,
+) -> Promise {
+    settle(import(
+        &format,
+        &key_data,
+        &algorithm,
+        extractable,
+        &key_usages,
+    ))
+}
+
+// Work is done synchronously, results are wrapped in a settled promise
+fn settle<T: Into<JsValue>>(result: Result<T, JsValue>) -> Promise {
+    match result {
+        Ok(value) => Promise::resolve(&value.into()),
+        Err(err) => Promise::reject(&err),
+    }
+}
+
+fn generate(
+    algorithm: &JsValue,
+    extractable: bool,
+    key_usages: &Array,
+) -> Result<KyberKeyPair, JsValue> {
+    check_algorithm(algorithm)?;
+    let usages = strings(key_usages)?;
+    check_usages(&usages, &[PUBLIC_USAGES, PRIVATE_USAGES].concat())?;
+    let keys = api::keypair(&mut rand::rngs::OsRng)?;
+    let split = |allowed: &[&str]| -> Vec<String> {
+        usages
+            .iter()
+            .filter(|u| allowed.contains(&u.as_str()))
+            .cloned()
+            .collect()
+    };
+    Ok(KyberKeyPair {
+        public: KyberKey::public(keys.public.to_vec(), split(&PUBLIC_USAGES))?,
+        private: KyberKey::private(keys.secret.to_vec(), extractable, split(&PRIVATE_USAGES))?,
+    })
+}
+
+fn encapsulate(algorithm: &JsValue, key: &KyberKey) -> Result<Object, JsValue> {
+    check_algorithm(algorithm)?;
+    if key.private {
+        return Err(error(
+            "InvalidAccessError",
+            "Encapsulation needs a public key",
+        ));
+    }
+    key.allows("encapsulateBits")?;
+    let (ct, ss) = api::encapsulate(&key.bytes, &mut rand::rngs::OsRng)?;
+    let out = Object::new();
+    Reflect::set(&out, &"sharedKey".into(), &buffer(&ss))?;
+    Reflect::set(&out, &"ciphertext".into(), &buffer(&ct))?;
+    Ok(out)
+}
+
+fn decapsulate(
+    algorithm: &JsValue,
+    key: &KyberKey,
+    ciphertext: &JsValue,
+) -> Result<ArrayBuffer, JsValue> {
+    check_algorithm(algorithm)?;
+    if !key.private {
+        return Err(error(
+            "InvalidAccessError",
+            "Decapsulation needs a private key",
+        ));
+    }
+    key.allows("decapsulateBits")?;
+    let ct = bytes(ciphertext)?;
+    if ct.len() != KYBER_CIPHERTEXTBYTES {
+        return Err(error("OperationError", "Invalid ciphertext length"));
+    }
+    Ok(buffer(&api::decapsulate(&ct, &key.bytes)?))
+}
+
+fn export(format: &str, key: &KyberKey) -> Result<JsValue, JsValue> {
+    if !key.extractable {
+        return Err(error("InvalidAccessError", "Key is not extractable"));
+    }
+    match (format, key.private) {
+        ("raw", false) => Ok(buffer(&key.bytes).into()),
+        ("jwk", _) => Ok(jwk(key)?.into()),
+        ("raw", true) => Err(error(
+            "InvalidAccessError",
+            &format!("Format {} does not match the key type", format),
+        )),
+        _ => Err(not_supported(format)),
+    }
+}
+
+fn import(
+    format: &str,
+    key_data: &JsValue,
+    algorithm: &JsValue,
+    extractable: bool,
+    key_usages: &Array,
+) -> Result<KyberKey, JsValue> {
+    check_algorithm(algorithm)?;
+    let usages = strings(key_usages)?;
+    match format {
+        "raw" => KyberKey::public(bytes(key_data)?, usages),
+        "jwk" => parse_jwk(key_data, extractable, usages),
+        _ => Err(not_supported(format)),
+    }
+}
+
+fn not_supported(format: &str) -> JsValue {
+    let reason = match format {
+        "spki" | "pkcs8" => ", there are no OIDs for round 3 Kyber",
+        _ => "",
+    };
+    error(
+        "NotSupportedError",
+        &format!("Key format {} is not supported{}", format, reason),
+    )
+}
+
+fn algorithm() -> Object {
+    let out = Object::new();
+    // Setting a property on a fresh object can't fail
+    let _ = Reflect::set(&out, &"name".into(), &KYBER_ALGORITHM.into());
+    out
+}
+
+// Accepts either an algorithm name or a `{ name }` dictionary, names are
+// matched case-insensitively as in WebCrypto's normalization
+fn check_algorithm(algorithm: &JsValue) -> Result<(), JsValue> {
+    let name = if algorithm.is_string() {
+        algorithm.clone()
+    } else {
+        Reflect::get(algorithm, &"name".into())?
+    };
+    match name.as_string() {
+        Some(name) if name.eq_ignore_ascii_case(KYBER_ALGORITHM) => Ok(()),
+        Some(name) => Err(error(
+            "NotSupportedError",
+            &format!(
+                "{} is not supported, this build implements {}",
+                name, KYBER_ALGORITHM
+            ),
+        )),
+        None => Err(error("TypeError", "Algorithm needs a name")),
+    }
+}
+
+fn check_usages(usages: &[String], allowed: &[&str]) -> Result<(), JsValue> {
+    match usages.iter().find(|u| !allowed.contains(&u.as_str())) {
+        Some(usage) => Err(error(
+            "SyntaxError",
+            &format!("Usage {} is not valid for this key", usage),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn strings(array: &Array) -> Result<Vec<String>, JsValue> {
+    array
+        .iter()
+        .map(|u| {
+            u.as_string()
+                .ok_or_else(|| error("TypeError", "Key usages must be strings"))
+        })
+        .collect()
+}
+
+fn buffer(bytes: &[u8]) -> ArrayBuffer {
+    Uint8Array::from(bytes).buffer()
+}
+
+// Copies a `BufferSource`, an `ArrayBuffer` or a view over one
+fn bytes(data: &JsValue) -> Result<Vec<u8>, JsValue> {
+    if let Some(buf) = data.dyn_ref::<ArrayBuffer>() {
+        return Ok(Uint8Array::new(buf).to_vec());
+    }
+    if ArrayBuffer::is_view(data) {
+        let field = |name: &str| -> Result<u32, JsValue> {
+            Ok(Reflect::get(data, &name.into())?.as_f64().unwrap_or(0.0) as u32)
+        };
+        let buf: ArrayBuffer = Reflect::get(data, &"buffer".into())?.unchecked_into();
+        let view = Uint8Array::new_with_byte_offset_and_length(
+            &buf,
+            field("byteOffset")?,
+            field("byteLength")?,
+        );
+        return Ok(view.to_vec());
+    }
+    Err(error(
+        "TypeError",
+        "Key data must be an ArrayBuffer or a view",
+    ))
+}
+
+// JWK with the AKP key type, as written by the `jwk` feature
+fn jwk(key: &KyberKey) -> Result<Object, JsValue> {
+    let out = Object::new();
+    let set = |name: &str, value: JsValue| Reflect::set(&out, &name.into(), &value);
+    set("kty", "AKP".into())?;
+    set("alg", KYBER_ALGORITHM.into())?;
+    if key.private {
+        set(
+            "pub",
+            URL_SAFE_NO_PAD.encode(api::public(&key.bytes)).into(),
+        )?;
+        set("priv", URL_SAFE_NO_PAD.encode(&key.bytes).into())?;
+    } else {
+        set("pub", URL_SAFE_NO_PAD.encode(&key.bytes).into())?;
+    }
+    set("key_ops", key.usages().into())?;
+    set("ext", key.extractable.into())?;
+    Ok(out)
+}
+
+fn parse_jwk(data: &JsValue, extractable: bool, usages: Vec<String>) -> Result<KyberKey, JsValue> {
+    let field = |name: &str| -> Result<Option<String>, JsValue> {
+        Ok(Reflect::get(data, &name.into())?.as_string())
+    };
+    let decode = |name: &str| -> Result<Option<Vec<u8>>, JsValue> {
+        field(name)?
+            .map(|v| {
+                URL_SAFE_NO_PAD
+                    .decode(v)
+                    .map_err(|_| data_error(&format!("Invalid base64url in {}", name)))
+            })
+            .transpose()
+    };
+    if field("kty")?.as_deref() != Some("AKP") {
+        return Err(data_error("JWK kty must be AKP"));
+    }
+    if field("alg")?.as_deref() != Some(KYBER_ALGORITHM) {
+        return Err(data_error(&format!("JWK alg must be {}", KYBER_ALGORITHM)));
+    }
+    if extractable && Reflect::get(data, &"ext".into())? == JsValue::FALSE {
+        return Err(data_error("JWK is not extractable"));
+    }
+    let key_ops = Reflect::get(data, &"key_ops".into())?;
+    if let Some(ops) = key_ops.dyn_ref::<Array>() {
+        let ops = strings(ops)?;
+        if usages.iter().any(|u| !ops.contains(u)) {
+            return Err(data_error("Key usages are not allowed by key_ops"));
+        }
+    }
+    let pk = decode("pub")?.ok_or_else(|| data_error("JWK is missing pub"))?;
+    match decode("priv")? {
+        None => KyberKey::public(pk, usages),
+        Some(sk) => {
+            if sk.len() == 2 * KYBER_SYMBYTES {
+                return Err(data_error("Seed-only private keys are not supported"));
+            }
+            if sk.len() != KYBER_SECRETKEYBYTES || api::public(&sk)[..] != pk[..] {
+                return Err(data_error("JWK pub does not match priv"));
+            }
+            KyberKey::private(sk, extractable, usages)
+        }
+    }
+}
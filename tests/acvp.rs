@@ -0,0 +1,215 @@
+// Runs NIST ACVP vector sets for ML-KEM keyGen and encapDecap, including the
+// encapsulation and decapsulation key checks, reporting every test case.
+// The vectors are downloaded separately, see tests/ACVP/readme.md, so this
+// is ignored by default:
+//
+// cargo test --test acvp -- --ignored --nocapture
+//
+// KYBER_ACVP_DIR sets the vector directory, tests/ACVP by default.
+// KYBER_ACVP_FUNCTIONS limits the run to a comma separated list of
+// functions, eg. encapsulationKeyCheck,decapsulationKeyCheck.
+//
+// This crate implements round 3 Kyber, which FIPS 203 changed in key
+// generation, encapsulation and decapsulation, so only the key checks are
+// expected to pass against ML-KEM vectors.
+
+use pqc_kyber::*;
+use serde_json::Value;
+use std::{collections::BTreeMap, env, fs, path::Path, path::PathBuf};
+mod utils;
+use utils::ReplayRng;
+
+fn vector_dir() -> PathBuf {
+    match env::var_os("KYBER_ACVP_DIR") {
+        Some(dir) => dir.into(),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ACVP"),
+    }
+}
+
+fn load(path: &Path) -> Value {
+    let json = fs::read_to_string(path).expect("Error loading ACVP file");
+    serde_json::from_str(&json).expect("Error parsing ACVP file")
+}
+
+// Copies the expected results into the matching prompt test cases
+fn merge(mut prompt: Value, expected: &Value) -> Value {
+    let expected_groups = expected["testGroups"].as_array().expect("testGroups");
+    for group in prompt["testGroups"].as_array_mut().expect("testGroups") {
+        let results = expected_groups
+            .iter()
+            .find(|g| g["tgId"] == group["tgId"])
+            .expect("No expected results for test group");
+        for test in group["tests"].as_array_mut().expect("tests") {
+            let result = results["tests"]
+                .as_array()
+                .and_then(|tests| tests.iter().find(|t| t["tcId"] == test["tcId"]))
+                .expect("No expected result for test case");
+            for (k, v) in result.as_object().expect("test case") {
+                test[k] = v.clone();
+            }
+        }
+    }
+    prompt
+}
+
+// Vector sets in the directory and its subdirectories, either the
+// internalProjection.json holding prompts and results together or a
+// prompt.json with its expectedResults.json
+fn vector_sets(dir: &Path) -> Vec<(PathBuf, Value)> {
+    let mut sets = Vec::new();
+    let projection = dir.join("internalProjection.json");
+    let prompt = dir.join("prompt.json");
+    if projection.exists() {
+        sets.push((projection.clone(), load(&projection)));
+    } else if prompt.exists() {
+        let expected = load(&dir.join("expectedResults.json"));
+        sets.push((prompt.clone(), merge(load(&prompt), &expected)));
+    }
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("Error reading ACVP directory")
+        .map(|e| e.expect("Error reading ACVP directory").path())
+        .filter(|p| p.is_dir())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        sets.extend(vector_sets(&subdir));
+    }
+    sets
+}
+
+fn hex(test: &Value, group: &Value, field: &str) -> Result<Vec<u8>, String> {
+    // Some revisions put the decapsulation key on the group
+    let s = test[field]
+        .as_str()
+        .or_else(|| group[field].as_str())
+        .ok_or(format!("missing {}", field))?;
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn expect(name: &str, got: &[u8], expected: &[u8]) -> Result<(), String> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(format!("{} mismatch", name))
+    }
+}
+
+fn expect_check(valid: bool, test: &Value) -> Result<(), String> {
+    let expected = test["testPassed"].as_bool().ok_or("missing testPassed")?;
+    if valid == expected {
+        Ok(())
+    } else if expected {
+        Err("valid key rejected".into())
+    } else {
+        Err("invalid key accepted".into())
+    }
+}
+
+// Ok(None) for functions this runner doesn't know
+fn run_case(function: &str, group: &Value, test: &Value) -> Result<Option<()>, String> {
+    let hex = |field| hex(test, group, field);
+    match function {
+        "keyGen" => {
+            let keys = derive(&[hex("d")?, hex("z")?].concat()).map_err(|e| e.to_string())?;
+            expect("ek", &keys.public, &hex("ek")?)?;
+            expect("dk", &keys.secret, &hex("dk")?)?;
+        }
+        "encapsulation" => {
+            let m = hex("m")?;
+            let (c, k) = encapsulate(&hex("ek")?, &mut ReplayRng(&m)).map_err(|e| e.to_string())?;
+            expect("c", &c, &hex("c")?)?;
+            expect("k", &k, &hex("k")?)?;
+        }
+        "decapsulation" => {
+            let k = decapsulate(&hex("c")?, &hex("dk")?).map_err(|e| e.to_string())?;
+            expect("k", &k, &hex("k")?)?;
+        }
+        "encapsulationKeyCheck" => expect_check(check_public_key(&hex("ek")?).is_ok(), test)?,
+        "decapsulationKeyCheck" => expect_check(check_secret_key(&hex("dk")?).is_ok(), test)?,
+        _ => return Ok(None),
+    }
+    Ok(Some(()))
+}
+
+#[derive(Default)]
+struct Tally {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+#[test]
+#[ignore]
+fn acvp() {
+    if KYBER_90S {
+        println!("ACVP vectors only cover ML-KEM, there are none for 90s mode");
+        return;
+    }
+    let parameter_set = format!("ML-KEM-{}", 256 * KYBER_K);
+    let functions = env::var("KYBER_ACVP_FUNCTIONS").ok();
+    let selected = |f: &str| match &functions {
+        Some(list) => list.split(',').any(|s| s == f),
+        None => true,
+    };
+
+    let dir = vector_dir();
+    assert!(
+        dir.is_dir(),
+        "No ACVP vectors at {}, see tests/ACVP/readme.md",
+        dir.display()
+    );
+    let sets = vector_sets(&dir);
+    assert!(!sets.is_empty(), "No ACVP vector sets in {}", dir.display());
+
+    let mut tally: BTreeMap<String, Tally> = BTreeMap::new();
+    for (path, set) in sets {
+        if set["algorithm"] != "ML-KEM" {
+            continue;
+        }
+        println!("{}", path.display());
+        let mode = set["mode"].as_str().unwrap_or_default();
+        for group in set["testGroups"].as_array().expect("testGroups") {
+            if group["parameterSet"] != parameter_set.as_str() {
+                continue;
+            }
+            // keyGen groups have no function field
+            let function = group["function"].as_str().unwrap_or(mode);
+            if !selected(function) {
+                continue;
+            }
+            let count = tally.entry(function.into()).or_default();
+            for test in group["tests"].as_array().expect("tests") {
+                let id = format!("{} tgId {} tcId {}", function, group["tgId"], test["tcId"]);
+                match run_case(function, group, test) {
+                    Ok(Some(())) => {
+                        count.passed += 1;
+                        println!("{}: pass", id);
+                    }
+                    Ok(None) => {
+                        count.skipped += 1;
+                        println!("{}: skipped, unknown function", id);
+                    }
+                    Err(e) => {
+                        count.failed += 1;
+                        println!("{}: FAIL, {}", id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    println!("\n{} summary", parameter_set);
+    for (function, count) in &tally {
+        println!(
+            "{}: {} passed, {} failed, {} skipped",
+            function, count.passed, count.failed, count.skipped
+        );
+    }
+    let failed: usize = tally.values().map(|c| c.failed).sum();
+    let run: usize = tally.values().map(|c| c.passed + c.failed).sum();
+    assert!(run > 0, "No {} test cases found", parameter_set);
+    assert_eq!(failed, 0, "{} of {} ACVP test cases failed", failed, run);
+}
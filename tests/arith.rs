@@ -0,0 +1,317 @@
+// Property tests for the polynomial arithmetic of whichever backend is
+// compiled, run once without and once with the avx2 feature. The avx2 NTT
+// leaves coefficients in a different order, so every property goes through
+// a round trip or is checked coefficient-wise where order doesn't matter.
+#![cfg(feature = "hazmat")]
+
+use pqc_kyber::*;
+use proptest::prelude::*;
+use std::convert::TryInto;
+
+const N: usize = 256;
+const Q: i32 = 3329;
+// 2^16 mod q
+const MONT: i32 = 2285;
+
+// Ciphertext compression parameters
+const DU: usize = if KYBER_K == 4 { 11 } else { 10 };
+const DV: usize = if KYBER_K == 4 { 5 } else { 4 };
+const POLYVECCOMPRESSEDBYTES: usize = KYBER_K * N * DU / 8;
+const POLYCOMPRESSEDBYTES: usize = N * DV / 8;
+
+#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
+mod backend {
+    use super::*;
+    use pqc_kyber::{poly::*, polyvec::*};
+
+    // Output range of poly_reduce
+    pub const REDUCED: (i16, i16) = (-(Q as i16 - 1) / 2, (Q as i16 - 1) / 2);
+    // Largest absolute value left by a Montgomery multiplication
+    pub const MONTGOMERY: i32 = Q - 1;
+    // Largest absolute value left by poly_invntt_tomont
+    pub const INVNTT: i32 = Q - 1;
+
+    pub fn ntt(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_ntt(&mut r);
+        r.coeffs
+    }
+
+    pub fn invntt_tomont(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_invntt_tomont(&mut r);
+        r.coeffs
+    }
+
+    pub fn basemul(a: [i16; N], b: [i16; N]) -> [i16; N] {
+        let mut r = Poly::new();
+        poly_basemul(&mut r, &Poly { coeffs: a }, &Poly { coeffs: b });
+        r.coeffs
+    }
+
+    pub fn tomont(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_tomont(&mut r);
+        r.coeffs
+    }
+
+    pub fn reduce(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_reduce(&mut r);
+        r.coeffs
+    }
+
+    pub fn compress_v(c: [i16; N]) -> [i16; N] {
+        let mut buf = [0u8; POLYCOMPRESSEDBYTES];
+        let mut r = Poly::new();
+        poly_compress(&mut buf, Poly { coeffs: c });
+        poly_decompress(&mut r, &buf);
+        r.coeffs
+    }
+
+    pub fn compress_u(c: [[i16; N]; KYBER_K]) -> [[i16; N]; KYBER_K] {
+        let mut buf = [0u8; POLYVECCOMPRESSEDBYTES];
+        let mut a = Polyvec::new();
+        let mut r = Polyvec::new();
+        for (p, coeffs) in a.vec.iter_mut().zip(c) {
+            p.coeffs = coeffs;
+        }
+        polyvec_compress(&mut buf, a);
+        polyvec_decompress(&mut r, &buf);
+        let mut out = [[0i16; N]; KYBER_K];
+        for (o, p) in out.iter_mut().zip(r.vec.iter()) {
+            *o = p.coeffs;
+        }
+        out
+    }
+
+    pub fn msg(c: [i16; N]) -> [i16; N] {
+        let mut m = [0u8; KYBER_SYMBYTES];
+        let mut r = Poly::new();
+        poly_tomsg(&mut m, Poly { coeffs: c });
+        poly_frommsg(&mut r, &m);
+        r.coeffs
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+mod backend {
+    use super::*;
+    use pqc_kyber::{poly::*, polyvec::*};
+
+    // Output range of poly_reduce
+    pub const REDUCED: (i16, i16) = (0, Q as i16);
+    // Largest absolute value left by a Montgomery multiplication
+    pub const MONTGOMERY: i32 = Q;
+    // The last butterflies of the inverse NTT are left unreduced, callers
+    // reduce afterwards
+    pub const INVNTT: i32 = 4 * Q;
+
+    fn coeffs(p: &Poly) -> [i16; N] {
+        unsafe { p.coeffs }
+    }
+
+    pub fn ntt(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_ntt(&mut r);
+        coeffs(&r)
+    }
+
+    pub fn invntt_tomont(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_invntt_tomont(&mut r);
+        coeffs(&r)
+    }
+
+    pub fn basemul(a: [i16; N], b: [i16; N]) -> [i16; N] {
+        let mut r = Poly::new();
+        poly_basemul(&mut r, &Poly { coeffs: a }, &Poly { coeffs: b });
+        coeffs(&r)
+    }
+
+    pub fn tomont(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_tomont(&mut r);
+        coeffs(&r)
+    }
+
+    pub fn reduce(c: [i16; N]) -> [i16; N] {
+        let mut r = Poly { coeffs: c };
+        poly_reduce(&mut r);
+        coeffs(&r)
+    }
+
+    pub fn compress_v(c: [i16; N]) -> [i16; N] {
+        let mut buf = [0u8; POLYCOMPRESSEDBYTES];
+        let mut r = Poly::new();
+        unsafe {
+            poly_compress(&mut buf, Poly { coeffs: c });
+            poly_decompress(&mut r, &buf);
+        }
+        coeffs(&r)
+    }
+
+    pub fn compress_u(c: [[i16; N]; KYBER_K]) -> [[i16; N]; KYBER_K] {
+        let mut buf = [0u8; POLYVECCOMPRESSEDBYTES];
+        let mut a = Polyvec::new();
+        let mut r = Polyvec::new();
+        for (p, coeffs) in a.vec.iter_mut().zip(c) {
+            p.coeffs = coeffs;
+        }
+        unsafe {
+            polyvec_compress(&mut buf, &a);
+            polyvec_decompress(&mut r, &buf);
+        }
+        let mut out = [[0i16; N]; KYBER_K];
+        for (o, p) in out.iter_mut().zip(r.vec.iter()) {
+            *o = coeffs(p);
+        }
+        out
+    }
+
+    pub fn msg(c: [i16; N]) -> [i16; N] {
+        let mut m = [0u8; KYBER_SYMBYTES];
+        let mut r = Poly::new();
+        poly_tomsg(&mut m, Poly { coeffs: c });
+        unsafe { poly_frommsg(&mut r, &m) };
+        coeffs(&r)
+    }
+}
+
+fn modq(x: i32) -> i32 {
+    x.rem_euclid(Q)
+}
+
+// Distance between x and y in Z_q
+fn distance(x: i16, y: i16) -> i32 {
+    let d = modq(x as i32 - y as i32);
+    d.min(Q - d)
+}
+
+// a * b mod X^256 + 1 and q
+fn schoolbook(a: &[i16; N], b: &[i16; N]) -> [i16; N] {
+    let mut r = [0i32; N];
+    for i in 0..N {
+        for j in 0..N {
+            let t = a[i] as i32 * b[j] as i32 % Q;
+            if i + j < N {
+                r[i + j] = (r[i + j] + t) % Q;
+            } else {
+                r[i + j - N] = (r[i + j - N] - t) % Q;
+            }
+        }
+    }
+    r.map(|x| modq(x) as i16)
+}
+
+// Largest error of decompress(compress(x, d)), round(q / 2^(d+1))
+fn compression_bound(d: usize) -> i32 {
+    (Q + (1 << d)) >> (d + 1)
+}
+
+// Coefficients as left by the NTT inputs, |x| < q
+fn poly() -> impl Strategy<Value = [i16; N]> {
+    prop::collection::vec(-(Q as i16 - 1)..Q as i16, N).prop_map(|v| v.try_into().unwrap())
+}
+
+// Reduced coefficients in {0, ..., q-1}
+fn reduced() -> impl Strategy<Value = [i16; N]> {
+    prop::collection::vec(0..Q as i16, N).prop_map(|v| v.try_into().unwrap())
+}
+
+fn any_i16() -> impl Strategy<Value = [i16; N]> {
+    prop::collection::vec(any::<i16>(), N).prop_map(|v| v.try_into().unwrap())
+}
+
+proptest! {
+    // invntt_tomont(ntt(a)) is a * 2^16 mod q
+    #[test]
+    fn ntt_invntt_identity(a in poly()) {
+        let r = backend::invntt_tomont(backend::ntt(a));
+        for (x, y) in a.iter().zip(r.iter()) {
+            prop_assert!((*y as i32).abs() <= backend::INVNTT, "{} to {}", x, y);
+            prop_assert_eq!(modq(*x as i32 * MONT), modq(*y as i32));
+        }
+    }
+
+    // basemul in the NTT domain, with invntt_tomont undoing its Montgomery
+    // factor, is multiplication in Z_q[X]/(X^256 + 1)
+    #[test]
+    fn basemul_matches_schoolbook(a in poly(), b in poly()) {
+        let r = backend::invntt_tomont(backend::basemul(backend::ntt(a), backend::ntt(b)));
+        let r = r.map(|x| modq(x as i32) as i16);
+        prop_assert_eq!(r, schoolbook(&a, &b));
+    }
+
+    #[test]
+    fn reduce_in_range(a in any_i16()) {
+        let (lo, hi) = backend::REDUCED;
+        for (x, y) in a.iter().zip(backend::reduce(a).iter()) {
+            prop_assert!(lo <= *y && *y <= hi, "{} reduced to {}", x, y);
+            prop_assert_eq!(modq(*x as i32), modq(*y as i32));
+        }
+    }
+
+    // tomont is a Montgomery multiplication by 2^32 mod q
+    #[test]
+    fn tomont_in_range(a in any_i16()) {
+        for (x, y) in a.iter().zip(backend::tomont(a).iter()) {
+            prop_assert!((*y as i32).abs() <= backend::MONTGOMERY, "{} to {}", x, y);
+            prop_assert_eq!(modq(*x as i32 * MONT), modq(*y as i32));
+        }
+    }
+
+    #[test]
+    fn compress_v_error_bounded(a in reduced()) {
+        let bound = compression_bound(DV);
+        for (x, y) in a.iter().zip(backend::compress_v(a).iter()) {
+            prop_assert!(distance(*x, *y) <= bound, "{} decompressed to {}", x, y);
+        }
+    }
+
+    #[test]
+    fn compress_u_error_bounded(a in prop::collection::vec(reduced(), KYBER_K)) {
+        let a: [[i16; N]; KYBER_K] = a.try_into().unwrap();
+        let bound = compression_bound(DU);
+        for (p, r) in a.iter().zip(backend::compress_u(a).iter()) {
+            for (x, y) in p.iter().zip(r.iter()) {
+                prop_assert!(distance(*x, *y) <= bound, "{} decompressed to {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn message_error_bounded(a in reduced()) {
+        let bound = compression_bound(1);
+        for (x, y) in a.iter().zip(backend::msg(a).iter()) {
+            prop_assert!(distance(*x, *y) <= bound, "{} decoded to {}", x, y);
+        }
+    }
+}
+
+// The scalar reductions only exist in the reference backend, the avx2 ones
+// are covered by the poly_reduce and poly_tomont properties above
+#[cfg(any(not(target_arch = "x86_64"), not(feature = "avx2")))]
+mod scalar {
+    use super::*;
+    use pqc_kyber::reduce::{barrett_reduce, montgomery_reduce};
+
+    #[test]
+    fn barrett_reduce_every_input() {
+        for a in i16::MIN..=i16::MAX {
+            let r = barrett_reduce(a);
+            assert!(r.abs() <= (Q as i16 - 1) / 2, "{} reduced to {}", a, r);
+            assert_eq!(modq(a as i32), modq(r as i32));
+        }
+    }
+
+    proptest! {
+        // Defined for inputs in {-q2^15, ..., q2^15 - 1}
+        #[test]
+        fn montgomery_reduce_in_range(a in -Q * (1 << 15)..Q * (1 << 15)) {
+            let r = montgomery_reduce(a);
+            prop_assert!((r as i32).abs() < Q);
+            prop_assert_eq!(modq(r as i32 * (1 << 16)), modq(a));
+        }
+    }
+}
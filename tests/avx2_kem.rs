@@ -0,0 +1,50 @@
+// Decapsulates valid and tampered ciphertexts with the avx2 backend and
+// compares the shared secrets with the build of the reference code in
+// tests/reference.
+//
+// Regression test for two avx2 bugs: cmov never selected z, so tampered
+// ciphertexts weren't implicitly rejected, and the 10 and 11 bit ciphertext
+// compression wrote past the end of its output.
+#![cfg(all(target_arch = "x86_64", feature = "avx2"))]
+
+mod reference;
+use params::*;
+use reference::*;
+
+fn reference_decapsulate(ct: &[u8], sk: &[u8]) -> [u8; KYBER_SSBYTES] {
+    let mut ss = [0u8; KYBER_SSBYTES];
+    kem::crypto_kem_dec(&mut ss, ct, sk).unwrap();
+    ss
+}
+
+#[test]
+fn tampered_ciphertexts_match_reference() {
+    let mut rng = rand::thread_rng();
+    let mut seed = [0u8; 64];
+    for (i, b) in seed.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let keys = pqc_kyber::derive(&seed).unwrap();
+    let (mut pk, mut sk) = ([0u8; KYBER_PUBLICKEYBYTES], [0u8; KYBER_SECRETKEYBYTES]);
+    kem::crypto_kem_keypair(&mut pk, &mut sk, &mut rng, Some((&seed[..32], &seed[32..]))).unwrap();
+    assert_eq!(keys.public[..], pk[..]);
+    assert_eq!(keys.secret[..], sk[..]);
+
+    for _ in 0..100 {
+        let (ct, ss) = pqc_kyber::encapsulate(&keys.public, &mut rng).unwrap();
+        // The reference code decodes the avx2 compressed ciphertext
+        assert_eq!(reference_decapsulate(&ct, &sk), ss);
+
+        let mut first = ct;
+        first[0] ^= 1;
+        let mut last = ct;
+        last[KYBER_CIPHERTEXTBYTES - 1] ^= 0x80;
+        let zeros = [0u8; KYBER_CIPHERTEXTBYTES];
+        let ones = [0xffu8; KYBER_CIPHERTEXTBYTES];
+        for tampered in [first, last, zeros, ones] {
+            let rejected = pqc_kyber::decapsulate(&tampered, &keys.secret).unwrap();
+            assert_ne!(rejected, ss);
+            assert_eq!(rejected, reference_decapsulate(&tampered, &sk));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+#![cfg(feature = "cli")]
+
+use pqc_kyber::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+mod utils;
+use utils::ReplayRng;
+
+fn kyber(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kyber"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let out = kyber(args);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    out.stdout
+}
+
+// Scratch directory unique to each test
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pqc_kyber_cli_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn path(dir: &Path, file: &str) -> String {
+    dir.join(file).to_str().unwrap().to_string()
+}
+
+fn level() -> String {
+    (256 * KYBER_K).to_string()
+}
+
+// Keygen, encapsulate and decapsulate agree in every format
+#[test]
+fn cli_kem_roundtrip() {
+    let dir = scratch("kem");
+    let (pk, sk, ct) = (path(&dir, "pk"), path(&dir, "sk"), path(&dir, "ct"));
+    for format in ["raw", "hex", "base64", "pem"] {
+        run(&["-f", format, "keygen", "--public", &pk, "--secret", &sk]);
+        let ss1 = run(&[
+            "-f",
+            format,
+            "encapsulate",
+            "--public",
+            &pk,
+            "--ciphertext",
+            &ct,
+        ]);
+        let ss2 = run(&[
+            "-f",
+            format,
+            "decapsulate",
+            "--secret",
+            &sk,
+            "--ciphertext",
+            &ct,
+        ]);
+        assert_eq!(ss1, ss2);
+        let public = run(&["-f", format, "public", "--secret", &sk]);
+        assert_eq!(public, fs::read(&pk).unwrap());
+    }
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_pem_labels() {
+    let dir = scratch("pem");
+    let (pk, sk) = (path(&dir, "pk"), path(&dir, "sk"));
+    run(&["keygen", "--public", &pk, "--secret", &sk]);
+    let pem = fs::read_to_string(&pk).unwrap();
+    assert!(pem.starts_with(&format!("-----BEGIN KYBER{}", level())));
+    // A public key is not accepted where a secret key is expected
+    let out = kyber(&["public", "--secret", &pk]);
+    assert!(!out.status.success());
+    let _ = fs::remove_dir_all(dir);
+}
+
+// Seeds give the same keys as the library
+#[test]
+fn cli_derive() {
+    let dir = scratch("derive");
+    let (seed, pk, sk) = (path(&dir, "seed"), path(&dir, "pk"), path(&dir, "sk"));
+    fs::write(&seed, [9u8; 64]).unwrap();
+    run(&[
+        "-f", "raw", "derive", "--seed", &seed, "--public", &pk, "--secret", &sk,
+    ]);
+    let keys = derive(&[9u8; 64]).unwrap();
+    assert_eq!(fs::read(&pk).unwrap(), keys.public);
+    assert_eq!(fs::read(&sk).unwrap(), keys.secret);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_encrypt_decrypt() {
+    let dir = scratch("encrypt");
+    let (pk, sk) = (path(&dir, "pk"), path(&dir, "sk"));
+    let (plain, sealed, opened) = (
+        path(&dir, "plain"),
+        path(&dir, "sealed"),
+        path(&dir, "opened"),
+    );
+    fs::write(&plain, b"attack at dawn").unwrap();
+    run(&["keygen", "--public", &pk, "--secret", &sk]);
+    run(&["encrypt", "--public", &pk, "--in", &plain, "--out", &sealed]);
+    run(&[
+        "decrypt", "--secret", &sk, "--in", &sealed, "--out", &opened,
+    ]);
+    assert_eq!(fs::read(&opened).unwrap(), b"attack at dawn");
+
+    // Tampering is detected
+    let mut bytes = fs::read(&sealed).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    fs::write(&sealed, bytes).unwrap();
+    assert!(!kyber(&["decrypt", "--secret", &sk, "--in", &sealed])
+        .status
+        .success());
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_wrong_level() {
+    let dir = scratch("level");
+    let (pk, sk) = (path(&dir, "pk"), path(&dir, "sk"));
+    run(&[
+        "--level",
+        &level(),
+        "keygen",
+        "--public",
+        &pk,
+        "--secret",
+        &sk,
+    ]);
+    let wrong = if KYBER_K == 2 { "1024" } else { "512" };
+    let out = kyber(&["--level", wrong, "keygen", "--public", &pk, "--secret", &sk]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("rebuild"));
+    let _ = fs::remove_dir_all(dir);
+}
+
+// Secret keys aren't left readable by other users
+#[cfg(unix)]
+#[test]
+fn cli_secret_key_mode() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = scratch("mode");
+    let (pk, sk) = (path(&dir, "pk"), path(&dir, "sk"));
+    run(&["keygen", "--public", &pk, "--secret", &sk]);
+    let mode = fs::metadata(&sk).unwrap().permissions().mode();
+    assert_eq!(mode & 0o077, 0);
+    let _ = fs::remove_dir_all(dir);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+// Test vectors in the reference implementation's format
+#[test]
+fn cli_verify_kat() {
+    let dir = scratch("kat");
+    let kat = path(&dir, "tvecs");
+    let (buf1, buf2, buf3) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+    let keys = derive(&[buf1, buf2].concat()).unwrap();
+    let (ct, ss) = encapsulate(&keys.public, &mut ReplayRng(&buf3)).unwrap();
+    let record = format!(
+        "{}\n{}\nPublic Key: {}\nSecret Key: {}\n{}\nCiphertext: {}\nShared Secret B: {}\n\n",
+        hex(&buf1),
+        hex(&buf2),
+        hex(&keys.public),
+        hex(&keys.secret),
+        hex(&buf3),
+        hex(&ct),
+        hex(&ss)
+    );
+    fs::write(&kat, record.repeat(3)).unwrap();
+    let out = run(&["verify-kat", &kat]);
+    assert_eq!(
+        String::from_utf8_lossy(&out).trim(),
+        "3 test vectors passed"
+    );
+
+    // A corrupted shared secret fails
+    let bad = record.replace(&hex(&ss), &hex(&[0u8; 32]));
+    fs::write(&kat, bad).unwrap();
+    assert!(!kyber(&["verify-kat", &kat]).status.success());
+    let _ = fs::remove_dir_all(dir);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "cose")]
+
+use ciborium::value::Value;
+use pqc_kyber::*;
+
+fn decode(cose: &[u8]) -> Vec<(Value, Value)> {
+    match ciborium::de::from_reader(cose).unwrap() {
+        Value::Map(map) => map,
+        _ => panic!("not a map"),
+    }
+}
+
+fn encode(map: Vec<(Value, Value)>) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::ser::into_writer(&Value::Map(map), &mut out).unwrap();
+    out
+}
+
+fn set(map: &mut Vec<(Value, Value)>, label: i64, value: Value) {
+    map.retain(|(k, _)| *k != Value::from(label));
+    map.push((label.into(), value));
+}
+
+#[test]
+fn cose_public_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let cose = public_to_cose(&keys.public);
+    let map = decode(&cose);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[0], (1.into(), COSE_KTY_AKP.into()));
+    assert_eq!(map[1], (3.into(), COSE_ALG.into()));
+    assert_eq!(public_from_cose(&cose).unwrap(), keys.public);
+}
+
+#[test]
+fn cose_secret_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let cose = secret_to_cose(&keys.secret);
+    assert_eq!(secret_from_cose(&cose).unwrap(), keys);
+    assert_eq!(public_from_cose(&cose).unwrap(), keys.public);
+}
+
+// ML-KEM seeds expand to a different keypair, so they aren't accepted in
+// place of the expanded secret key
+#[test]
+fn cose_seed_rejected() {
+    let seed = [3u8; 64];
+    let keys = derive(&seed).unwrap();
+    let mut map = decode(&public_to_cose(&keys.public));
+    set(&mut map, -2, Value::Bytes(seed.to_vec()));
+    assert_eq!(
+        secret_from_cose(&encode(map)),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+// A public key that doesn't belong to the secret key is rejected
+#[test]
+fn cose_mismatched_public() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let other = keypair(&mut rng).unwrap();
+    let mut map = decode(&secret_to_cose(&keys.secret));
+    set(&mut map, -1, Value::Bytes(other.public.to_vec()));
+    assert_eq!(
+        secret_from_cose(&encode(map)),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn cose_wrong_alg() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut map = decode(&public_to_cose(&keys.public));
+    set(&mut map, 3, (-7).into());
+    assert_eq!(
+        public_from_cose(&encode(map)),
+        Err(KyberError::ParameterMismatch)
+    );
+}
+
+#[test]
+fn cose_malformed() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let cose = public_to_cose(&keys.public);
+    assert_eq!(public_from_cose(&[0xff]), Err(KyberError::InvalidInput));
+    assert_eq!(
+        public_from_cose(&cose[..cose.len() - 1]),
+        Err(KyberError::InvalidInput)
+    );
+    let mut wrong_kty = decode(&cose);
+    set(&mut wrong_kty, 1, 1.into());
+    assert_eq!(
+        public_from_cose(&encode(wrong_kty)),
+        Err(KyberError::InvalidInput)
+    );
+    let mut short = decode(&cose);
+    set(&mut short, -1, Value::Bytes(vec![0u8; 4]));
+    assert_eq!(
+        public_from_cose(&encode(short)),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+// Thumbprints only depend on the public key and ignore member order
+#[test]
+fn cose_thumbprint_stable() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let other = keypair(&mut rng).unwrap();
+    let mut reordered = decode(&public_to_cose(&keys.public));
+    reordered.reverse();
+    let reordered = public_from_cose(&encode(reordered)).unwrap();
+    assert_eq!(cose_thumbprint(&keys.public), cose_thumbprint(&reordered));
+    assert_ne!(
+        cose_thumbprint(&keys.public),
+        cose_thumbprint(&other.public)
+    );
+}
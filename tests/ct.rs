@@ -0,0 +1,137 @@
+// dudect-style timing test for decapsulation, comparing valid ciphertexts
+// against random ones that take the implicit rejection path. Timing only
+// means something in release builds, so it is ignored by default:
+//
+// cargo test --release --test ct -- --ignored --nocapture
+//
+// Run once with the avx2 feature and RUSTFLAGS to check that backend.
+// KYBER_CT_MEASUREMENTS sets the number of measurements, 200,000 by default.
+
+use pqc_kyber::*;
+use rand::Rng;
+use std::time::Instant;
+
+// Welch's t statistic above this is taken as a timing leak, dudect uses the
+// same bound. Below 4.5 would be needed to claim no leak was detected.
+const THRESHOLD: f64 = 10.0;
+
+// Number of cropping percentiles tested besides the uncropped samples
+const PERCENTILES: usize = 16;
+
+// Online mean and variance for one class of measurements
+#[derive(Default, Clone, Copy)]
+struct Class {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Class {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+// Welch's t-test between the two classes
+#[derive(Default, Clone, Copy)]
+struct TTest([Class; 2]);
+
+impl TTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.0[class].push(x);
+    }
+
+    fn t(&self) -> f64 {
+        let [a, b] = self.0;
+        if a.n < 2.0 || b.n < 2.0 {
+            return 0.0;
+        }
+        (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+    }
+}
+
+// Cropping thresholds, measurements above them are dropped to cut the long
+// tail left by interrupts and scheduling
+fn percentiles(times: &[u64]) -> Vec<u64> {
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    (0..PERCENTILES)
+        .map(|i| {
+            let p = 1.0 - 0.5f64.powf(10.0 * (i + 1) as f64 / PERCENTILES as f64);
+            sorted[(p * sorted.len() as f64) as usize]
+        })
+        .collect()
+}
+
+fn measurements() -> usize {
+    std::env::var("KYBER_CT_MEASUREMENTS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(200_000)
+}
+
+#[test]
+#[ignore]
+fn decapsulation_timing() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let n = measurements();
+
+    // Inputs are prepared up front so only decapsulation is timed
+    let mut classes = Vec::with_capacity(n);
+    let mut ciphertexts = Vec::with_capacity(n);
+    for _ in 0..n {
+        let class = rng.gen_range(0..2);
+        let ct = if class == 0 {
+            encapsulate(&keys.public, &mut rng).unwrap().0
+        } else {
+            let mut ct = [0u8; KYBER_CIPHERTEXTBYTES];
+            rng.fill(&mut ct[..]);
+            ct
+        };
+        classes.push(class);
+        ciphertexts.push(ct);
+    }
+
+    let mut times = Vec::with_capacity(n);
+    let mut sink = 0u8;
+    for ct in &ciphertexts {
+        let start = Instant::now();
+        let ss = decapsulate(ct, &keys.secret).unwrap();
+        times.push(start.elapsed().as_nanos() as u64);
+        sink ^= ss[0];
+    }
+
+    // The first measurements warm up caches and branch predictors
+    let skip = n / 10;
+    let crops = percentiles(&times[skip..]);
+    let mut tests = vec![TTest::default(); PERCENTILES + 1];
+    for (&class, &time) in classes.iter().zip(&times).skip(skip) {
+        tests[0].push(class, time as f64);
+        for (test, &crop) in tests[1..].iter_mut().zip(&crops) {
+            if time < crop {
+                test.push(class, time as f64);
+            }
+        }
+    }
+
+    let max = tests.iter().map(|t| t.t().abs()).fold(0.0, f64::max);
+    println!(
+        "{} measurements, max |t| = {:.2}, sink {}",
+        n - skip,
+        max,
+        sink
+    );
+    assert!(
+        max < THRESHOLD,
+        "decapsulation timing depends on ciphertext validity, |t| = {:.2}",
+        max
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "fault-simulation")]
+
+use pqc_kyber::{fault::*, *};
+
+// Decapsulates with a bit flipped at the given fault point
+fn faulted(point: usize, ct: &[u8], sk: &[u8]) -> Decapsulated {
+    inject_fault(Some(point));
+    let res = decapsulate(ct, sk);
+    inject_fault(None);
+    res
+}
+
+// A fault at any point while decapsulating a valid ciphertext changes the
+// shared secret, so every one must be caught
+#[test]
+fn every_fault_detected_for_valid_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    for point in 0..FAULT_POINTS {
+        assert_eq!(
+            faulted(point, &ct, &keys.secret),
+            Err(KyberError::FaultDetected),
+            "fault at point {} not detected",
+            point
+        );
+    }
+    assert_eq!(decapsulate(&ct, &keys.secret).unwrap(), ss);
+}
+
+// For an invalid ciphertext a fault must never skip implicit rejection,
+// decapsulation either fails closed or still gives the rejection secret
+#[test]
+fn faults_never_skip_implicit_rejection() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (mut ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    ct[..4].copy_from_slice(&[255u8; 4]);
+    let rejected = decapsulate(&ct, &keys.secret).unwrap();
+    assert_ne!(rejected, ss);
+    for point in 0..FAULT_POINTS {
+        match faulted(point, &ct, &keys.secret) {
+            Ok(res) => assert_eq!(res, rejected, "fault at point {}", point),
+            Err(e) => assert_eq!(e, KyberError::FaultDetected),
+        }
+    }
+}
+
+// The stored H(pk) is checked against the public key in the secret key
+#[test]
+fn corrupted_secret_key_detected() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut rng).unwrap();
+    let pk_start = KYBER_SECRETKEYBYTES - KYBER_PUBLICKEYBYTES - 2 * KYBER_SYMBYTES;
+    let hpk_start = KYBER_SECRETKEYBYTES - 2 * KYBER_SYMBYTES;
+    for i in [pk_start, hpk_start] {
+        let mut sk = keys.secret;
+        sk[i] ^= 1;
+        assert_eq!(decapsulate(&ct, &sk), Err(KyberError::FaultDetected));
+    }
+}
+
+// Faults are reported through the key exchanges as well
+#[test]
+fn fault_in_uake() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    inject_fault(Some(0));
+    let res = bob.server_receive(client_init, &bob_keys.secret, &mut rng);
+    inject_fault(None);
+    assert_eq!(res, Err(KyberError::FaultDetected));
+}
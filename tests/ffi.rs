@@ -0,0 +1,77 @@
+#![cfg(feature = "ffi")]
+
+use pqc_kyber::{ffi::*, *};
+use std::ptr;
+
+#[test]
+fn ffi_sizes() {
+    assert_eq!(PQC_KYBER_PUBLICKEYBYTES, KYBER_PUBLICKEYBYTES);
+    assert_eq!(PQC_KYBER_SECRETKEYBYTES, KYBER_SECRETKEYBYTES);
+    assert_eq!(PQC_KYBER_CIPHERTEXTBYTES, KYBER_CIPHERTEXTBYTES);
+    assert_eq!(PQC_KYBER_SSBYTES, KYBER_SSBYTES);
+    assert_eq!(PQC_KYBER_SEEDBYTES, 64);
+}
+
+#[test]
+fn ffi_roundtrip() {
+    let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+    let mut sk = [0u8; KYBER_SECRETKEYBYTES];
+    let mut ct = [0u8; KYBER_CIPHERTEXTBYTES];
+    let mut ss1 = [0u8; KYBER_SSBYTES];
+    let mut ss2 = [0u8; KYBER_SSBYTES];
+    unsafe {
+        assert_eq!(pqc_kyber_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()), 0);
+        assert_eq!(
+            pqc_kyber_enc(ct.as_mut_ptr(), ss1.as_mut_ptr(), pk.as_ptr()),
+            0
+        );
+        assert_eq!(pqc_kyber_dec(ss2.as_mut_ptr(), ct.as_ptr(), sk.as_ptr()), 0);
+    }
+    assert_eq!(ss1, ss2);
+    assert_eq!(decapsulate(&ct, &sk).unwrap(), ss1);
+}
+
+// Deterministic functions match the Rust API
+#[test]
+fn ffi_derand() {
+    let seed = [5u8; 64];
+    let coins = [6u8; 32];
+    let keys = derive(&seed).unwrap();
+    let mut pk = [0u8; KYBER_PUBLICKEYBYTES];
+    let mut sk = [0u8; KYBER_SECRETKEYBYTES];
+    let mut ct = [[0u8; KYBER_CIPHERTEXTBYTES]; 2];
+    let mut ss = [[0u8; KYBER_SSBYTES]; 2];
+    unsafe {
+        assert_eq!(
+            pqc_kyber_derive(pk.as_mut_ptr(), sk.as_mut_ptr(), seed.as_ptr()),
+            0
+        );
+        for (ct, ss) in ct.iter_mut().zip(ss.iter_mut()) {
+            let ret = pqc_kyber_enc_derand(
+                ct.as_mut_ptr(),
+                ss.as_mut_ptr(),
+                pk.as_ptr(),
+                coins.as_ptr(),
+            );
+            assert_eq!(ret, 0);
+        }
+    }
+    assert_eq!(pk, keys.public);
+    assert_eq!(sk, keys.secret);
+    assert_eq!(ct[0], ct[1]);
+    assert_eq!(ss[0], ss[1]);
+    assert_eq!(decapsulate(&ct[0], &sk).unwrap(), ss[0]);
+}
+
+#[test]
+fn ffi_null_pointers() {
+    let mut buf = [0u8; KYBER_SECRETKEYBYTES];
+    let out = buf.as_mut_ptr();
+    unsafe {
+        assert_eq!(pqc_kyber_keypair(ptr::null_mut(), out), -1);
+        assert_eq!(pqc_kyber_derive(out, out, ptr::null()), -1);
+        assert_eq!(pqc_kyber_enc(out, ptr::null_mut(), out), -1);
+        assert_eq!(pqc_kyber_enc_derand(out, out, out, ptr::null()), -1);
+        assert_eq!(pqc_kyber_dec(out, ptr::null(), out), -1);
+    }
+}
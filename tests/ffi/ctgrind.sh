@@ -0,0 +1,36 @@
+#!/bin/bash
+set -e
+
+# Builds the cdylib for each backend and runs the constant-time harness
+# under valgrind memcheck. Needs valgrind and its headers installed.
+#
+# Usage: tests/ffi/ctgrind.sh [cargo features]
+# eg. tests/ffi/ctgrind.sh "kyber1024 90s"
+
+ROOT=$(cd "$(dirname "$0")/../.." && pwd)
+FEATURES=${1:+",$1"}
+OUT="$ROOT/target/ctgrind"
+AVX2_FLAGS="-C target-feature=+aes,+avx2,+sse2,+sse4.1,+bmi2,+popcnt"
+
+BACKENDS=("reference")
+if [ "$(uname -m)" = "x86_64" ]; then
+  BACKENDS+=("avx2")
+fi
+
+for backend in "${BACKENDS[@]}"; do
+  echo "ctgrind: $backend ${1:-}"
+  dir="$OUT/$backend"
+  if [ "$backend" = "avx2" ]; then
+    flags="$AVX2_FLAGS"
+    feat="ffi,avx2$FEATURES"
+  else
+    flags=""
+    feat="ffi$FEATURES"
+  fi
+  RUSTFLAGS="${RUSTFLAGS:-} $flags" cargo build --release \
+    --manifest-path "$ROOT/Cargo.toml" --target-dir "$dir" --features "$feat"
+  ${CC:-cc} -std=c99 -Wall -Wextra -Werror -g -I "$ROOT/include" \
+    "$ROOT/tests/ffi/ctgrind.c" -L "$dir/release" -lpqc_kyber \
+    -Wl,-rpath,"$dir/release" -o "$dir/ctgrind"
+  valgrind --error-exitcode=1 --track-origins=yes "$dir/ctgrind"
+done
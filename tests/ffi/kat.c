@@ -0,0 +1,147 @@
+/*
+ * Checks the C ABI against the reference implementation's test vectors.
+ *
+ * Usage: kat [tvecs file]
+ *
+ * Without a file only the round trip and argument checks are run. See
+ * run.sh for building against the cdylib.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "pqc_kyber.h"
+
+#define MAX_LINE 8192
+
+static int failures = 0;
+
+#define CHECK(cond, msg)                                   \
+  do {                                                     \
+    if (!(cond)) {                                         \
+      fprintf(stderr, "FAIL %s:%d: %s\n", __FILE__, __LINE__, msg); \
+      failures++;                                          \
+    }                                                      \
+  } while (0)
+
+static uint8_t *alloc(size_t len) {
+  uint8_t *p = calloc(1, len);
+  if (p == NULL) {
+    perror("calloc");
+    exit(2);
+  }
+  return p;
+}
+
+static void roundtrip(void) {
+  uint8_t *pk = alloc(PQC_KYBER_PUBLICKEYBYTES);
+  uint8_t *sk = alloc(PQC_KYBER_SECRETKEYBYTES);
+  uint8_t *ct = alloc(PQC_KYBER_CIPHERTEXTBYTES);
+  uint8_t *ss1 = alloc(PQC_KYBER_SSBYTES);
+  uint8_t *ss2 = alloc(PQC_KYBER_SSBYTES);
+
+  CHECK(pqc_kyber_keypair(pk, sk) == 0, "keypair");
+  CHECK(pqc_kyber_enc(ct, ss1, pk) == 0, "enc");
+  CHECK(pqc_kyber_dec(ss2, ct, sk) == 0, "dec");
+  CHECK(memcmp(ss1, ss2, PQC_KYBER_SSBYTES) == 0, "shared secrets differ");
+
+  /* Implicit rejection still returns success with a different secret */
+  ct[0] ^= 1;
+  CHECK(pqc_kyber_dec(ss2, ct, sk) == 0, "dec of modified ciphertext");
+  CHECK(memcmp(ss1, ss2, PQC_KYBER_SSBYTES) != 0, "modified ciphertext accepted");
+
+  CHECK(pqc_kyber_keypair(NULL, sk) == -1, "null public key");
+  CHECK(pqc_kyber_enc(ct, NULL, pk) == -1, "null shared secret");
+  CHECK(pqc_kyber_dec(ss2, ct, NULL) == -1, "null secret key");
+  CHECK(pqc_kyber_derive(pk, sk, NULL) == -1, "null seed");
+
+  free(pk); free(sk); free(ct); free(ss1); free(ss2);
+}
+
+/* Decodes the hex value of a "Label: HEX" or bare "HEX" line */
+static size_t decode_line(const char *line, uint8_t *out, size_t max) {
+  const char *hex = strrchr(line, ' ');
+  hex = hex ? hex + 1 : line;
+  size_t len = strcspn(hex, "\r\n");
+  if (len % 2 != 0 || len / 2 > max) {
+    return (size_t)-1;
+  }
+  for (size_t i = 0; i < len / 2; i++) {
+    unsigned int byte;
+    if (sscanf(hex + 2 * i, "%2x", &byte) != 1) {
+      return (size_t)-1;
+    }
+    out[i] = (uint8_t)byte;
+  }
+  return len / 2;
+}
+
+static int read_value(FILE *f, uint8_t *out, size_t expected) {
+  char line[MAX_LINE];
+  if (fgets(line, sizeof line, f) == NULL) {
+    return 0;
+  }
+  if (decode_line(line, out, expected) != expected) {
+    fprintf(stderr, "malformed line: %.40s\n", line);
+    exit(2);
+  }
+  return 1;
+}
+
+static int known_answers(const char *path) {
+  FILE *f = fopen(path, "r");
+  if (f == NULL) {
+    perror(path);
+    exit(2);
+  }
+  uint8_t seed[64], coins[32];
+  uint8_t *pk = alloc(PQC_KYBER_PUBLICKEYBYTES), *kat_pk = alloc(PQC_KYBER_PUBLICKEYBYTES);
+  uint8_t *sk = alloc(PQC_KYBER_SECRETKEYBYTES), *kat_sk = alloc(PQC_KYBER_SECRETKEYBYTES);
+  uint8_t *ct = alloc(PQC_KYBER_CIPHERTEXTBYTES), *kat_ct = alloc(PQC_KYBER_CIPHERTEXTBYTES);
+  uint8_t *ss = alloc(PQC_KYBER_SSBYTES), *kat_ss = alloc(PQC_KYBER_SSBYTES);
+  char blank[MAX_LINE];
+  int count = 0;
+
+  /* Records are two keygen buffers, pk, sk, encapsulation buffer, ct, ss and a blank line */
+  while (read_value(f, seed, 32)) {
+    read_value(f, seed + 32, 32);
+    read_value(f, kat_pk, PQC_KYBER_PUBLICKEYBYTES);
+    read_value(f, kat_sk, PQC_KYBER_SECRETKEYBYTES);
+    read_value(f, coins, 32);
+    read_value(f, kat_ct, PQC_KYBER_CIPHERTEXTBYTES);
+    read_value(f, kat_ss, PQC_KYBER_SSBYTES);
+    if (fgets(blank, sizeof blank, f) == NULL && !feof(f)) {
+      break;
+    }
+
+    CHECK(pqc_kyber_derive(pk, sk, seed) == 0, "derive");
+    CHECK(memcmp(pk, kat_pk, PQC_KYBER_PUBLICKEYBYTES) == 0, "public key mismatch");
+    CHECK(memcmp(sk, kat_sk, PQC_KYBER_SECRETKEYBYTES) == 0, "secret key mismatch");
+    CHECK(pqc_kyber_enc_derand(ct, ss, kat_pk, coins) == 0, "enc_derand");
+    CHECK(memcmp(ct, kat_ct, PQC_KYBER_CIPHERTEXTBYTES) == 0, "ciphertext mismatch");
+    CHECK(memcmp(ss, kat_ss, PQC_KYBER_SSBYTES) == 0, "encapsulated secret mismatch");
+    CHECK(pqc_kyber_dec(ss, kat_ct, kat_sk) == 0, "dec");
+    CHECK(memcmp(ss, kat_ss, PQC_KYBER_SSBYTES) == 0, "decapsulated secret mismatch");
+    count++;
+  }
+
+  fclose(f);
+  free(pk); free(kat_pk); free(sk); free(kat_sk);
+  free(ct); free(kat_ct); free(ss); free(kat_ss);
+  return count;
+}
+
+int main(int argc, char **argv) {
+  roundtrip();
+  if (argc > 1) {
+    int count = known_answers(argv[1]);
+    CHECK(count > 0, "no test vectors found");
+    printf("%d test vectors checked\n", count);
+  }
+  if (failures) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  printf("ok\n");
+  return 0;
+}
@@ -0,0 +1,83 @@
+/*
+ * Round trips through an OQS_KEM filled by pqc_kyber_oqs_kem_init.
+ *
+ * The OQS_KEM and OQS_STATUS definitions are copied from liboqs' kem.h and
+ * common.h so the test doesn't need liboqs installed. See run.sh.
+ */
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "pqc_kyber.h"
+
+typedef enum {
+  OQS_ERROR = -1,
+  OQS_SUCCESS = 0,
+  OQS_EXTERNAL_LIB_ERROR_OPENSSL = 50,
+} OQS_STATUS;
+
+typedef struct OQS_KEM {
+  const char *method_name;
+  const char *alg_version;
+  uint8_t claimed_nist_level;
+  bool ind_cca;
+  size_t length_public_key;
+  size_t length_secret_key;
+  size_t length_ciphertext;
+  size_t length_shared_secret;
+  OQS_STATUS (*keypair)(uint8_t *public_key, uint8_t *secret_key);
+  OQS_STATUS (*encaps)(uint8_t *ciphertext, uint8_t *shared_secret, const uint8_t *public_key);
+  OQS_STATUS (*decaps)(uint8_t *shared_secret, const uint8_t *ciphertext, const uint8_t *secret_key);
+} OQS_KEM;
+
+static int failures = 0;
+
+#define CHECK(cond, msg)                                   \
+  do {                                                     \
+    if (!(cond)) {                                         \
+      fprintf(stderr, "FAIL %s:%d: %s\n", __FILE__, __LINE__, msg); \
+      failures++;                                          \
+    }                                                      \
+  } while (0)
+
+/* Generic liboqs style caller, only knows about OQS_KEM */
+static void roundtrip(const OQS_KEM *kem) {
+  uint8_t *pk = malloc(kem->length_public_key);
+  uint8_t *sk = malloc(kem->length_secret_key);
+  uint8_t *ct = malloc(kem->length_ciphertext);
+  uint8_t *ss1 = malloc(kem->length_shared_secret);
+  uint8_t *ss2 = malloc(kem->length_shared_secret);
+  if (!pk || !sk || !ct || !ss1 || !ss2) {
+    perror("malloc");
+    exit(2);
+  }
+  CHECK(kem->keypair(pk, sk) == OQS_SUCCESS, "keypair");
+  CHECK(kem->encaps(ct, ss1, pk) == OQS_SUCCESS, "encaps");
+  CHECK(kem->decaps(ss2, ct, sk) == OQS_SUCCESS, "decaps");
+  CHECK(memcmp(ss1, ss2, kem->length_shared_secret) == 0, "shared secrets differ");
+  CHECK(kem->encaps(ct, NULL, pk) == OQS_ERROR, "null shared secret");
+  free(pk); free(sk); free(ct); free(ss1); free(ss2);
+}
+
+int main(void) {
+  OQS_KEM kem;
+  CHECK(sizeof(OQS_KEM) == sizeof(PQC_KYBER_OQS_KEM), "struct size");
+  CHECK(pqc_kyber_oqs_kem_init(NULL) == OQS_ERROR, "null kem");
+  CHECK(pqc_kyber_oqs_kem_init((PQC_KYBER_OQS_KEM *)&kem) == OQS_SUCCESS, "init");
+
+  CHECK(strncmp(kem.method_name, "Kyber", 5) == 0, "method name");
+  CHECK(kem.ind_cca, "ind_cca");
+  CHECK(kem.length_public_key == PQC_KYBER_PUBLICKEYBYTES, "public key length");
+  CHECK(kem.length_secret_key == PQC_KYBER_SECRETKEYBYTES, "secret key length");
+  CHECK(kem.length_ciphertext == PQC_KYBER_CIPHERTEXTBYTES, "ciphertext length");
+  CHECK(kem.length_shared_secret == PQC_KYBER_SSBYTES, "shared secret length");
+  roundtrip(&kem);
+
+  if (failures) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  printf("%s (%s) ok\n", kem.method_name, kem.alg_version);
+  return 0;
+}
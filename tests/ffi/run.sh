@@ -0,0 +1,31 @@
+#!/bin/bash
+set -e
+
+# Builds the cdylib with the C ABI and runs the C test harnesses against it.
+#
+# Usage: tests/ffi/run.sh [cargo features] [tvecs file]
+# eg. tests/ffi/run.sh kyber1024 tests/KAT/tvecs1024
+
+ROOT=$(cd "$(dirname "$0")/../.." && pwd)
+FEATURES=${1:+",$1"}
+TVECS=${2:-""}
+OUT="$ROOT/target/ffi"
+
+cargo build --release --manifest-path "$ROOT/Cargo.toml" --features "oqs$FEATURES"
+mkdir -p "$OUT"
+
+# The header must be exactly what cbindgen generates, never edited by hand
+if command -v cbindgen > /dev/null; then
+  (cd "$ROOT" && cbindgen --quiet --config cbindgen.toml --output "$OUT/pqc_kyber.h")
+  diff -u "$ROOT/include/pqc_kyber.h" "$OUT/pqc_kyber.h"
+else
+  echo "cbindgen not installed, not checking include/pqc_kyber.h"
+fi
+
+for test in kat oqs; do
+  ${CC:-cc} -std=c99 -Wall -Wextra -Werror -I "$ROOT/include" \
+    "$ROOT/tests/ffi/$test.c" -L "$ROOT/target/release" -lpqc_kyber \
+    -Wl,-rpath,"$ROOT/target/release" -o "$OUT/$test"
+done
+"$OUT/kat" $TVECS
+"$OUT/oqs"
@@ -0,0 +1,118 @@
+#![cfg(feature = "tokio")]
+
+use pqc_kyber::*;
+use rand::rngs::OsRng;
+use std::time::Duration;
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn handshake_valid() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, mut server) = duplex(4096);
+    let server_task =
+        tokio::spawn(async move { server_handshake(&mut server, &keys.secret, &mut OsRng).await });
+    let client_secret = client_handshake(&mut client, &keys.public, &mut OsRng)
+        .await
+        .unwrap();
+    let server_secret = server_task.await.unwrap().unwrap();
+    assert_eq!(client_secret, server_secret);
+}
+
+// Server holds a different keypair, the secrets won't match
+#[tokio::test]
+async fn handshake_wrong_server_key() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let other_keys = keypair(&mut OsRng).unwrap();
+    let (mut client, mut server) = duplex(4096);
+    let server_task =
+        tokio::spawn(
+            async move { server_handshake(&mut server, &other_keys.secret, &mut OsRng).await },
+        );
+    let client_secret = client_handshake(&mut client, &keys.public, &mut OsRng)
+        .await
+        .unwrap();
+    let server_secret = server_task.await.unwrap().unwrap();
+    assert_ne!(client_secret, server_secret);
+}
+
+// Server rejects the initiation with a header for another protocol version
+#[tokio::test]
+async fn handshake_version_mismatch() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, mut server) = duplex(4096);
+    let peer = tokio::spawn(async move {
+        let mut init = vec![0u8; 4 + UAKE_INIT_BYTES];
+        server.read_exact(&mut init).await.unwrap();
+        let mut reply = [0, 0, 0, KEX_HEADER_BYTES as u8, 0, 0, 0];
+        reply[4..].copy_from_slice(&KEX_HEADER);
+        reply[4] = KEX_VERSION + 1;
+        server.write_all(&reply).await.unwrap();
+        server
+    });
+    let res = client_handshake(&mut client, &keys.public, &mut OsRng).await;
+    assert!(matches!(
+        res,
+        Err(HandshakeError::Kyber(KyberError::ParameterMismatch))
+    ));
+    peer.await.unwrap();
+}
+
+// Client initiates at a different security level, the server reports the
+// mismatch rather than the frame length and replies with its header
+#[tokio::test]
+async fn handshake_security_level_mismatch() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, mut server) = duplex(8192);
+    let (k, bytes) = if KYBER_K == 4 {
+        (2, 800 + 768)
+    } else {
+        (4, 1568 + 1568)
+    };
+    let mut init = ((KEX_HEADER_BYTES + bytes) as u32).to_be_bytes().to_vec();
+    init.extend_from_slice(&[KEX_VERSION, k, KEX_HEADER[2]]);
+    init.resize(4 + KEX_HEADER_BYTES + bytes, 0);
+    client.write_all(&init).await.unwrap();
+    let res = server_handshake(&mut server, &keys.secret, &mut OsRng).await;
+    assert!(matches!(
+        res,
+        Err(HandshakeError::Kyber(KyberError::ParameterMismatch))
+    ));
+    let mut reply = [0u8; 4 + KEX_HEADER_BYTES];
+    client.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply[4..], KEX_HEADER);
+}
+
+// Frame length prefix larger than any handshake message
+#[tokio::test]
+async fn handshake_oversized_frame() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, mut server) = duplex(4096);
+    client.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+    let res = server_handshake(&mut server, &keys.secret, &mut OsRng).await;
+    assert!(matches!(res, Err(HandshakeError::InvalidFrame)));
+}
+
+// Peer never responds
+#[tokio::test]
+async fn handshake_timeout() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, _server) = duplex(4096);
+    let res = client_handshake_with_timeout(
+        &mut client,
+        &keys.public,
+        &mut OsRng,
+        Duration::from_millis(50),
+    )
+    .await;
+    assert!(matches!(res, Err(HandshakeError::Timeout)));
+}
+
+// Peer closes the stream mid handshake
+#[tokio::test]
+async fn handshake_closed_stream() {
+    let keys = keypair(&mut OsRng).unwrap();
+    let (mut client, server) = duplex(4096);
+    drop(server);
+    let res = client_handshake(&mut client, &keys.public, &mut OsRng).await;
+    assert!(matches!(res, Err(HandshakeError::Io(_))));
+}
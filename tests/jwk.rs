@@ -0,0 +1,113 @@
+#![cfg(feature = "jwk")]
+
+use pqc_kyber::*;
+use serde_json::Value;
+
+fn edit(jwk: &str, f: impl FnOnce(&mut serde_json::Map<String, Value>)) -> String {
+    let mut value: Value = serde_json::from_str(jwk).unwrap();
+    f(value.as_object_mut().unwrap());
+    value.to_string()
+}
+
+#[test]
+fn jwk_public_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let jwk = public_to_jwk(&keys.public);
+    let value: Value = serde_json::from_str(&jwk).unwrap();
+    assert_eq!(value["kty"], "AKP");
+    assert_eq!(value["alg"], JWK_ALG);
+    assert!(value.get("priv").is_none());
+    assert_eq!(public_from_jwk(&jwk).unwrap(), keys.public);
+}
+
+#[test]
+fn jwk_secret_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let jwk = secret_to_jwk(&keys.secret);
+    assert_eq!(secret_from_jwk(&jwk).unwrap(), keys);
+    assert_eq!(public_from_jwk(&jwk).unwrap(), keys.public);
+}
+
+// ML-KEM seeds expand to a different keypair, so they aren't accepted in
+// place of the expanded secret key
+#[test]
+fn jwk_seed_rejected() {
+    let keys = derive(&[0u8; 64]).unwrap();
+    let jwk = edit(&public_to_jwk(&keys.public), |map| {
+        // 64 zero bytes, base64url without padding
+        map.insert("priv".into(), "A".repeat(86).into());
+    });
+    assert_eq!(secret_from_jwk(&jwk), Err(KyberError::InvalidInput));
+}
+
+// Keys labelled as ML-KEM would give ML-KEM peers different shared secrets
+#[test]
+fn jwk_mlkem_rejected() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    assert!(!JWK_ALG.starts_with("ML-KEM"));
+    let jwk = edit(&public_to_jwk(&keys.public), |map| {
+        map.insert("alg".into(), format!("ML-KEM-{}", 256 * KYBER_K).into());
+    });
+    assert_eq!(public_from_jwk(&jwk), Err(KyberError::ParameterMismatch));
+}
+
+// A public key that doesn't belong to the secret key is rejected
+#[test]
+fn jwk_mismatched_public() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let other = keypair(&mut rng).unwrap();
+    let other_pub =
+        serde_json::from_str::<Value>(&public_to_jwk(&other.public)).unwrap()["pub"].clone();
+    let jwk = edit(&secret_to_jwk(&keys.secret), |map| {
+        map.insert("pub".into(), other_pub);
+    });
+    assert_eq!(secret_from_jwk(&jwk), Err(KyberError::InvalidInput));
+}
+
+#[test]
+fn jwk_wrong_alg() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let jwk = edit(&public_to_jwk(&keys.public), |map| {
+        map.insert("alg".into(), "ML-KEM-9000".into());
+    });
+    assert_eq!(public_from_jwk(&jwk), Err(KyberError::ParameterMismatch));
+}
+
+#[test]
+fn jwk_malformed() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let jwk = public_to_jwk(&keys.public);
+    assert_eq!(public_from_jwk("not json"), Err(KyberError::InvalidInput));
+    let wrong_kty = edit(&jwk, |map| {
+        map.insert("kty".into(), "OKP".into());
+    });
+    assert_eq!(public_from_jwk(&wrong_kty), Err(KyberError::InvalidInput));
+    let short = edit(&jwk, |map| {
+        map.insert("pub".into(), "AAAA".into());
+    });
+    assert_eq!(public_from_jwk(&short), Err(KyberError::InvalidInput));
+    let padded = edit(&jwk, |map| {
+        map.insert("pub".into(), "AA==".into());
+    });
+    assert_eq!(public_from_jwk(&padded), Err(KyberError::InvalidInput));
+}
+
+// The kid is the thumbprint and only depends on the public key
+#[test]
+fn jwk_thumbprint_stable() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let other = keypair(&mut rng).unwrap();
+    let public: Value = serde_json::from_str(&public_to_jwk(&keys.public)).unwrap();
+    let secret: Value = serde_json::from_str(&secret_to_jwk(&keys.secret)).unwrap();
+    assert_eq!(public["kid"], jwk_thumbprint_base64(&keys.public));
+    assert_eq!(public["kid"], secret["kid"]);
+    assert_eq!(jwk_thumbprint(&keys.public), jwk_thumbprint(&keys.public));
+    assert_ne!(jwk_thumbprint(&keys.public), jwk_thumbprint(&other.public));
+}
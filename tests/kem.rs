@@ -20,6 +20,20 @@ fn keypair_encap_decap_invalid_ciphertext() {
     assert!(decapsulate(&ct, &keys.secret).unwrap() != ss);
 }
 
+// Implicit rejection derives the secret from z, the last bytes of sk, so
+// changing z must change it for an invalid ciphertext and only then
+#[test]
+fn implicit_rejection_uses_z() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (mut ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    let mut sk = keys.secret;
+    sk[KYBER_SECRETKEYBYTES - 1] ^= 1;
+    assert_eq!(decapsulate(&ct, &sk).unwrap(), ss);
+    ct[0] ^= 1;
+    assert!(decapsulate(&ct, &sk).unwrap() != decapsulate(&ct, &keys.secret).unwrap());
+}
+
 #[test]
 fn keypair_encap_pk_wrong_size() {
     let mut rng = rand::thread_rng();
@@ -65,3 +79,13 @@ fn public_from_private() {
     let pk2 = public(&keys.secret);
     assert_eq!(pk2, keys.public);
 }
+
+// Debug output must never leak the secret key
+#[test]
+fn keypair_debug_redacts_secret() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let debug = format!("{:?}", keys);
+    assert!(debug.contains("[REDACTED]"));
+    assert!(!debug.contains(&format!("{:?}", &keys.secret[..16])));
+}
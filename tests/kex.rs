@@ -25,7 +25,7 @@ fn uake_invalid_client_init_ciphertext() {
     let mut bob = Uake::new();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
+    client_init[KEX_HEADER_BYTES + KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
     assert!(!bob
         .server_receive(client_init, &bob_keys.secret, &mut rng)
         .is_err());
@@ -40,7 +40,7 @@ fn uake_invalid_client_init_publickey() {
     let mut bob = Uake::new();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[..4].copy_from_slice(&[255u8; 4]);
+    client_init[KEX_HEADER_BYTES..][..4].copy_from_slice(&[255u8; 4]);
     let server_send = bob
         .server_receive(client_init, &bob_keys.secret, &mut rng)
         .unwrap();
@@ -64,6 +64,36 @@ fn uake_invalid_server_send_ciphertext() {
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
+// Initiation header from a peer on a newer wire format version
+#[test]
+fn uake_invalid_client_init_version() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[0] = KEX_VERSION + 1;
+    assert_eq!(
+        bob.server_receive(client_init, &bob_keys.secret, &mut rng),
+        Err(KyberError::ParameterMismatch)
+    );
+}
+
+// Initiation header from a peer using a different mode
+#[test]
+fn uake_invalid_client_init_mode() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[2] ^= KEX_FLAG_90S;
+    assert_eq!(
+        bob.server_receive(client_init, &bob_keys.secret, &mut rng),
+        Err(KyberError::ParameterMismatch)
+    );
+}
+
 // Same tests for AKE
 
 #[test]
@@ -91,7 +121,7 @@ fn ake_invalid_client_init_ciphertext() {
     let alice_keys = keypair(&mut rng).unwrap();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
+    client_init[KEX_HEADER_BYTES + KYBER_PUBLICKEYBYTES..][..4].copy_from_slice(&[255u8; 4]);
     assert!(!bob
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
         .is_err());
@@ -106,7 +136,7 @@ fn ake_invalid_client_init_publickey() {
     let alice_keys = keypair(&mut rng).unwrap();
     let bob_keys = keypair(&mut rng).unwrap();
     let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
-    client_init[..4].copy_from_slice(&[255u8; 4]);
+    client_init[KEX_HEADER_BYTES..][..4].copy_from_slice(&[255u8; 4]);
     let server_send = bob
         .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
         .unwrap();
@@ -116,6 +146,22 @@ fn ake_invalid_client_init_publickey() {
     assert_ne!(alice.shared_secret, bob.shared_secret);
 }
 
+// Initiation header from a peer using a different security level
+#[test]
+fn ake_invalid_client_init_security_level() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    client_init[1] = if KYBER_K == 4 { 2 } else { 4 };
+    assert_eq!(
+        bob.server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng),
+        Err(KyberError::ParameterMismatch)
+    );
+}
+
 #[test]
 fn ake_invalid_server_send_first_ciphertext() {
     let mut rng = rand::thread_rng();
@@ -210,3 +256,113 @@ fn ake_failed_randombytes_server_receive() {
         Err(KyberError::RandomBytesGeneration)
     )
 }
+
+// Sealed client state resumed between client_init and client_confirm
+#[test]
+fn uake_sealed_state() {
+    let mut rng = rand::thread_rng();
+    let key = [7u8; SEAL_KEY_BYTES];
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&key, 100, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let mut alice = Uake::unseal(&sealed, &key, 100).unwrap();
+    alice.client_confirm(server_send).unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn ake_sealed_state() {
+    let mut rng = rand::thread_rng();
+    let key = [7u8; SEAL_KEY_BYTES];
+    let mut alice = Ake::new();
+    let mut bob = Ake::new();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&key, 100, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &alice_keys.public, &bob_keys.secret, &mut rng)
+        .unwrap();
+    let mut alice = Ake::unseal(&sealed, &key, 0).unwrap();
+    alice
+        .client_confirm(server_send, &alice_keys.secret)
+        .unwrap();
+    assert_eq!(alice.shared_secret, bob.shared_secret);
+}
+
+#[test]
+fn sealed_state_expired() {
+    let mut rng = rand::thread_rng();
+    let key = [7u8; SEAL_KEY_BYTES];
+    let mut alice = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&key, 100, &mut rng).unwrap();
+    assert_eq!(
+        Uake::unseal(&sealed, &key, 101),
+        Err(KyberError::StateExpired)
+    );
+}
+
+#[test]
+fn sealed_state_wrong_key() {
+    let mut rng = rand::thread_rng();
+    let mut alice = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&[7u8; SEAL_KEY_BYTES], 100, &mut rng).unwrap();
+    assert_eq!(
+        Uake::unseal(&sealed, &[8u8; SEAL_KEY_BYTES], 0),
+        Err(KyberError::InvalidState)
+    );
+}
+
+// Flipping a bit anywhere invalidates the state, including the expiry
+#[test]
+fn sealed_state_tampered() {
+    let mut rng = rand::thread_rng();
+    let key = [7u8; SEAL_KEY_BYTES];
+    let mut alice = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&key, 100, &mut rng).unwrap();
+    for i in [0, 1, 2, 12, 20, 200, SEALED_STATE_BYTES - 1] {
+        let mut tampered = sealed;
+        tampered[i] ^= 1;
+        assert_eq!(
+            Uake::unseal(&tampered, &key, 0),
+            Err(KyberError::InvalidState)
+        );
+    }
+    assert_eq!(
+        Uake::unseal(&sealed[1..], &key, 0),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+// State sealed by a UAKE client can't be resumed as an AKE client
+#[test]
+fn sealed_state_wrong_kind() {
+    let mut rng = rand::thread_rng();
+    let key = [7u8; SEAL_KEY_BYTES];
+    let mut alice = Uake::new();
+    let bob_keys = keypair(&mut rng).unwrap();
+    alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let sealed = alice.seal(&key, 100, &mut rng).unwrap();
+    assert_eq!(Ake::unseal(&sealed, &key, 0), Err(KyberError::InvalidState));
+}
+
+#[test]
+fn sealed_state_failed_randombytes() {
+    let mut rng = FailingRng::default();
+    let alice = Uake::new();
+    assert_eq!(
+        alice.seal(&[7u8; SEAL_KEY_BYTES], 100, &mut rng),
+        Err(KyberError::RandomBytesGeneration)
+    );
+}
@@ -0,0 +1,144 @@
+// Regression test for KyberSlash, division of secret dependent coefficients
+// by KYBER_Q is variable time on many CPUs. Builds the reference code as an
+// object file and checks with objdump that the functions handling secret
+// coefficients contain no division instructions.
+//
+// At opt-level 2 and above the compiler replaces division by a constant with
+// a multiplication, so the library is built without optimisations, where
+// division is emitted as written, and optimised for size, where it is kept
+// and inlined into the KEM functions.
+#![cfg(all(target_os = "linux", not(feature = "avx2")))]
+
+use pqc_kyber::*;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const OPT_LEVELS: [&str; 2] = ["0", "z"];
+
+// Functions handling secret coefficients, and those they get inlined into.
+// gen_matrix and the hashes divide public lengths.
+const FUNCTIONS: [&str; 7] = [
+    "::poly_tomsg",
+    "::poly_compress",
+    "::polyvec_compress",
+    "::indcpa_enc",
+    "::indcpa_dec",
+    "::crypto_kem_enc",
+    "::crypto_kem_dec",
+];
+
+// x86 div/idiv and aarch64 udiv/sdiv, floating point division is not used
+fn is_division(mnemonic: &str) -> bool {
+    matches!(
+        mnemonic,
+        "div"
+            | "divb"
+            | "divw"
+            | "divl"
+            | "divq"
+            | "idiv"
+            | "idivb"
+            | "idivw"
+            | "idivl"
+            | "idivq"
+            | "udiv"
+            | "sdiv"
+    )
+}
+
+// Level and mode features matching this test build
+fn features() -> String {
+    let mut features = match KYBER_K {
+        2 => "kyber512",
+        4 => "kyber1024",
+        _ => "kyber768",
+    }
+    .to_string();
+    if KYBER_90S {
+        features.push_str(",90s");
+    }
+    features
+}
+
+// Builds the library as a single object file at the given opt-level
+fn build(opt_level: &str) -> PathBuf {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target = root.join("target").join("kyberslash").join(opt_level);
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .current_dir(root)
+        .args(["rustc", "--lib", "--features", &features(), "--target-dir"])
+        .arg(&target)
+        .args(["--", "-C", "codegen-units=1", "--emit=obj", "-C"])
+        .arg(format!("opt-level={}", opt_level))
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "building at opt-level {} failed",
+        opt_level
+    );
+
+    std::fs::read_dir(target.join("debug").join("deps"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("o".as_ref()))
+        .max_by_key(|path| path.metadata().unwrap().modified().unwrap())
+        .expect("no object file built")
+}
+
+// Splits objdump output into (symbol, instruction mnemonics)
+fn functions(disassembly: &str) -> Vec<(String, Vec<String>)> {
+    let mut out: Vec<(String, Vec<String>)> = Vec::new();
+    for line in disassembly.lines() {
+        if let Some(name) = line.strip_suffix(">:") {
+            let name = name.split_once('<').map_or("", |(_, name)| name);
+            out.push((name.to_string(), Vec::new()));
+        } else if let Some((_, instrs)) = out.last_mut() {
+            // address: \t mnemonic operands
+            if let Some((_, asm)) = line.split_once('\t') {
+                if let Some(mnemonic) = asm.split_whitespace().next() {
+                    instrs.push(mnemonic.to_string());
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn no_division_on_secret_data() {
+    if Command::new("objdump").arg("--version").output().is_err() {
+        println!("objdump not available, skipping");
+        return;
+    }
+
+    for opt_level in OPT_LEVELS {
+        let obj = build(opt_level);
+        let output = Command::new("objdump")
+            .args(["-d", "-C", "--no-show-raw-insn"])
+            .arg(&obj)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let disassembly = String::from_utf8_lossy(&output.stdout);
+
+        let mut found = 0;
+        for (name, instrs) in functions(&disassembly) {
+            if !name.starts_with("pqc_kyber::") || !FUNCTIONS.iter().any(|f| name.ends_with(f)) {
+                continue;
+            }
+            found += 1;
+            let divs = instrs.iter().filter(|m| is_division(m)).count();
+            assert_eq!(
+                divs, 0,
+                "{} contains {} division instructions at opt-level {}",
+                name, divs, opt_level
+            );
+        }
+        assert!(found > 0, "no functions found in {:?}", obj);
+        println!("opt-level {}: checked {} functions", opt_level, found);
+    }
+}
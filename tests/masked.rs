@@ -0,0 +1,92 @@
+#![cfg(feature = "masked")]
+
+use pqc_kyber::*;
+use rand::Rng;
+mod utils;
+use utils::*;
+
+#[test]
+fn masked_decap_matches_unmasked() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    for _ in 0..10 {
+        let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+        assert_eq!(masked.decapsulate(&ct, &mut rng).unwrap(), ss);
+    }
+}
+
+// Implicit rejection must give the same pseudorandom secret as the
+// unmasked code, for small changes in u and v and for random ciphertexts
+#[test]
+fn masked_decap_invalid_ciphertexts() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    for i in [0, KYBER_CIPHERTEXTBYTES / 2, KYBER_CIPHERTEXTBYTES - 1] {
+        let (mut ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+        ct[i] ^= 1;
+        let expected = decapsulate(&ct, &keys.secret).unwrap();
+        assert_ne!(expected, ss);
+        assert_eq!(masked.decapsulate(&ct, &mut rng).unwrap(), expected);
+    }
+    for _ in 0..5 {
+        let mut ct = [0u8; KYBER_CIPHERTEXTBYTES];
+        rng.fill(&mut ct[..]);
+        let expected = decapsulate(&ct, &keys.secret).unwrap();
+        assert_eq!(masked.decapsulate(&ct, &mut rng).unwrap(), expected);
+    }
+}
+
+#[test]
+fn masked_refresh_keeps_key() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    for _ in 0..3 {
+        masked.refresh(&mut rng).unwrap();
+    }
+    let (ct, ss) = encapsulate(&keys.public, &mut rng).unwrap();
+    assert_eq!(masked.decapsulate(&ct, &mut rng).unwrap(), ss);
+}
+
+#[test]
+fn masked_wrong_sizes() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    assert_eq!(
+        MaskedSecretKey::new(&keys.secret[1..], &mut rng).unwrap_err(),
+        KyberError::InvalidInput
+    );
+    let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    let ct = [1u8; KYBER_CIPHERTEXTBYTES + 3];
+    assert_eq!(
+        masked.decapsulate(&ct, &mut rng),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+#[test]
+fn masked_failed_randombytes() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let (ct, _) = encapsulate(&keys.public, &mut rng).unwrap();
+    let mut failing = FailingRng::default();
+    assert_eq!(
+        MaskedSecretKey::new(&keys.secret, &mut failing).unwrap_err(),
+        KyberError::RandomBytesGeneration
+    );
+    let mut masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    assert_eq!(
+        masked.decapsulate(&ct, &mut failing),
+        Err(KyberError::RandomBytesGeneration)
+    );
+}
+
+#[test]
+fn masked_debug_redacts_shares() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let masked = MaskedSecretKey::new(&keys.secret, &mut rng).unwrap();
+    assert!(format!("{:?}", masked).contains("[REDACTED]"));
+}
@@ -0,0 +1,217 @@
+// Checks the avx2 NTT, inverse NTT, basemul and Montgomery conversion
+// against the reference code coefficient by coefficient. Only one backend
+// is compiled into pqc_kyber, so the reference sources are included from
+// tests/reference, and the avx2 NTT order is mapped back
+// to the reference order with the permutation poly_nttunpack applies.
+//
+// Both backends only ever add multiples of q when reducing, so inside their
+// input bounds each function is linear mod q and basemul is bilinear.
+// Agreeing on every basis polynomial, and for basemul every pair of them,
+// then covers all inputs. The random and boundary cases check nothing
+// overflows on the way.
+#![cfg(all(target_arch = "x86_64", feature = "avx2", feature = "hazmat"))]
+
+mod reference;
+use reference::*;
+
+use pqc_kyber::poly as avx;
+use proptest::prelude::{any, prop, prop_assert_eq, proptest, Strategy};
+use std::convert::TryInto;
+
+const N: usize = 256;
+const Q: i16 = 3329;
+
+// Extremes of the NTT inputs, |x| < q
+const NTT_BOUNDARY: [i16; 4] = [1, -1, Q - 1, -(Q - 1)];
+// Extremes of an i16, allowed for tomont and one basemul operand
+const I16_BOUNDARY: [i16; 4] = [i16::MAX, i16::MIN, i16::MAX - 1, i16::MIN + 1];
+
+// order()[j] is the reference index of the coefficient the avx2 NTT keeps
+// at position j
+fn order() -> [usize; N] {
+    let mut index = [0i16; N];
+    for (i, c) in index.iter_mut().enumerate() {
+        *c = i as i16;
+    }
+    let mut p = avx::Poly { coeffs: index };
+    avx::poly_nttunpack(&mut p);
+    let mut order = [0usize; N];
+    let mut seen = [false; N];
+    for (o, &c) in order.iter_mut().zip(unsafe { p.coeffs }.iter()) {
+        assert!(!seen[c as usize], "poly_nttunpack is not a permutation");
+        seen[c as usize] = true;
+        *o = c as usize;
+    }
+    order
+}
+
+fn to_avx(c: [i16; N]) -> avx::Poly {
+    let mut r = [0i16; N];
+    for (x, &i) in r.iter_mut().zip(order().iter()) {
+        *x = c[i];
+    }
+    avx::Poly { coeffs: r }
+}
+
+fn from_avx(p: &avx::Poly) -> [i16; N] {
+    let mut r = [0i16; N];
+    for (&x, &i) in unsafe { p.coeffs }.iter().zip(order().iter()) {
+        r[i] = x;
+    }
+    r
+}
+
+fn canonical(c: [i16; N]) -> [i16; N] {
+    c.map(|x| (x as i32).rem_euclid(Q as i32) as i16)
+}
+
+fn basis(i: usize, v: i16) -> [i16; N] {
+    let mut c = [0i16; N];
+    c[i] = v;
+    c
+}
+
+fn alternating(v: i16) -> [i16; N] {
+    let mut c = [v; N];
+    c.iter_mut()
+        .skip(1)
+        .step_by(2)
+        .for_each(|x| *x = x.wrapping_neg());
+    c
+}
+
+// Reference and avx2 results for the same input, in reference order
+fn ntt(c: [i16; N]) -> ([i16; N], [i16; N]) {
+    let mut r = poly::Poly { coeffs: c };
+    let mut a = avx::Poly { coeffs: c };
+    poly::poly_ntt(&mut r);
+    avx::poly_ntt(&mut a);
+    (r.coeffs, from_avx(&a))
+}
+
+fn invntt_tomont(c: [i16; N]) -> ([i16; N], [i16; N]) {
+    let mut r = poly::Poly { coeffs: c };
+    let mut a = to_avx(c);
+    poly::poly_invntt_tomont(&mut r);
+    avx::poly_invntt_tomont(&mut a);
+    (r.coeffs, unsafe { a.coeffs })
+}
+
+fn basemul(c: [i16; N], d: [i16; N]) -> ([i16; N], [i16; N]) {
+    let mut r = poly::Poly::new();
+    let mut a = avx::Poly::new();
+    poly::poly_basemul(&mut r, &poly::Poly { coeffs: c }, &poly::Poly { coeffs: d });
+    avx::poly_basemul(&mut a, &to_avx(c), &to_avx(d));
+    (r.coeffs, from_avx(&a))
+}
+
+fn tomont(c: [i16; N]) -> ([i16; N], [i16; N]) {
+    let mut r = poly::Poly { coeffs: c };
+    let mut a = avx::Poly { coeffs: c };
+    poly::poly_tomont(&mut r);
+    avx::poly_tomont(&mut a);
+    (r.coeffs, unsafe { a.coeffs })
+}
+
+fn assert_equivalent((r, a): ([i16; N], [i16; N]), what: &str) {
+    assert_eq!(canonical(r), canonical(a), "{}", what);
+}
+
+// Serializing an NTT domain polynomial undoes the avx2 order, so it has to
+// agree with the permutation used here
+#[test]
+fn order_matches_tobytes() {
+    let mut c = [0i16; N];
+    for (i, x) in c.iter_mut().enumerate() {
+        *x = (i as i32 * 1337 % Q as i32) as i16;
+    }
+    let (mut r, mut a) = ([0u8; 384], [0u8; 384]);
+    poly::poly_tobytes(&mut r, poly::Poly { coeffs: c });
+    avx::poly_tobytes(&mut a, to_avx(c));
+    assert_eq!(r[..], a[..]);
+}
+
+#[test]
+fn ntt_every_basis_polynomial() {
+    for i in 0..N {
+        for &v in NTT_BOUNDARY.iter() {
+            assert_equivalent(ntt(basis(i, v)), &format!("ntt of {} at {}", v, i));
+            assert_equivalent(
+                invntt_tomont(basis(i, v)),
+                &format!("invntt_tomont of {} at {}", v, i),
+            );
+        }
+    }
+}
+
+#[test]
+fn basemul_every_basis_pair() {
+    for i in 0..N {
+        for j in 0..N {
+            assert_equivalent(
+                basemul(basis(i, 1), basis(j, 1)),
+                &format!("basemul of {} and {}", i, j),
+            );
+        }
+    }
+}
+
+#[test]
+fn tomont_every_input() {
+    for chunk in (i16::MIN..=i16::MAX).collect::<Vec<_>>().chunks(N) {
+        let c: [i16; N] = chunk.try_into().unwrap();
+        assert_equivalent(tomont(c), &format!("tomont from {}", c[0]));
+    }
+}
+
+#[test]
+fn boundary_inputs() {
+    for &v in NTT_BOUNDARY.iter() {
+        for c in [[v; N], alternating(v)] {
+            assert_equivalent(ntt(c), &format!("ntt of {}", v));
+            assert_equivalent(invntt_tomont(c), &format!("invntt_tomont of {}", v));
+            for &w in NTT_BOUNDARY.iter().chain(I16_BOUNDARY.iter()) {
+                for d in [[w; N], alternating(w)] {
+                    let what = format!("basemul of {} and {}", w, v);
+                    assert_equivalent(basemul(d, c), &what);
+                }
+            }
+        }
+    }
+}
+
+// Coefficients as left by the NTT inputs, |x| < q
+fn poly() -> impl Strategy<Value = [i16; N]> {
+    prop::collection::vec(-(Q - 1)..Q, N).prop_map(|v| v.try_into().unwrap())
+}
+
+fn any_i16() -> impl Strategy<Value = [i16; N]> {
+    prop::collection::vec(any::<i16>(), N).prop_map(|v| v.try_into().unwrap())
+}
+
+proptest! {
+    #[test]
+    fn ntt_equivalent(a in poly()) {
+        let (r, a) = ntt(a);
+        prop_assert_eq!(canonical(r), canonical(a));
+    }
+
+    #[test]
+    fn invntt_tomont_equivalent(a in poly()) {
+        let (r, a) = invntt_tomont(a);
+        prop_assert_eq!(canonical(r), canonical(a));
+    }
+
+    // One operand can be any i16 as long as the other is below q
+    #[test]
+    fn basemul_equivalent(a in any_i16(), b in poly()) {
+        let (r, a) = basemul(a, b);
+        prop_assert_eq!(canonical(r), canonical(a));
+    }
+
+    #[test]
+    fn tomont_equivalent(a in any_i16()) {
+        let (r, a) = tomont(a);
+        prop_assert_eq!(canonical(r), canonical(a));
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "oqs")]
+
+use pqc_kyber::{oqs::*, *};
+use std::{ffi::CStr, mem::MaybeUninit, ptr};
+
+fn init() -> OqsKem {
+    let mut kem = MaybeUninit::<OqsKem>::uninit();
+    unsafe {
+        assert_eq!(pqc_kyber_oqs_kem_init(kem.as_mut_ptr()), 0);
+        kem.assume_init()
+    }
+}
+
+#[test]
+fn oqs_kem_fields() {
+    let kem = init();
+    let name = unsafe { CStr::from_ptr(kem.method_name) }.to_str().unwrap();
+    assert!(name.starts_with(&format!("Kyber{}", 256 * KYBER_K)));
+    assert_eq!(name.ends_with("-90s"), KYBER_90S);
+    assert!(kem.ind_cca);
+    assert_eq!(kem.length_public_key, KYBER_PUBLICKEYBYTES);
+    assert_eq!(kem.length_secret_key, KYBER_SECRETKEYBYTES);
+    assert_eq!(kem.length_ciphertext, KYBER_CIPHERTEXTBYTES);
+    assert_eq!(kem.length_shared_secret, KYBER_SSBYTES);
+    assert_eq!(unsafe { pqc_kyber_oqs_kem_init(ptr::null_mut()) }, -1);
+}
+
+// Round trip through the vtable
+#[test]
+fn oqs_kem_roundtrip() {
+    let kem = init();
+    let mut pk = vec![0u8; kem.length_public_key];
+    let mut sk = vec![0u8; kem.length_secret_key];
+    let mut ct = vec![0u8; kem.length_ciphertext];
+    let mut ss1 = vec![0u8; kem.length_shared_secret];
+    let mut ss2 = vec![0u8; kem.length_shared_secret];
+    unsafe {
+        assert_eq!((kem.keypair)(pk.as_mut_ptr(), sk.as_mut_ptr()), 0);
+        assert_eq!(
+            (kem.encaps)(ct.as_mut_ptr(), ss1.as_mut_ptr(), pk.as_ptr()),
+            0
+        );
+        assert_eq!((kem.decaps)(ss2.as_mut_ptr(), ct.as_ptr(), sk.as_ptr()), 0);
+    }
+    assert_eq!(ss1, ss2);
+    assert_eq!(decapsulate(&ct, &sk).unwrap()[..], ss1[..]);
+}
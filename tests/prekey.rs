@@ -0,0 +1,159 @@
+use pqc_kyber::*;
+mod utils;
+use utils::*;
+
+#[test]
+fn prekey_valid() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    assert_eq!(store.replenish(&mut rng).unwrap(), MAX_PREKEYS);
+    let bundle = store
+        .bundle(&bob_keys.public, store.ids().nth(3).unwrap())
+        .unwrap();
+    let (init, alice_ss) = prekey_initiate(&bundle, &alice_keys.public, &mut rng).unwrap();
+    let bob_ss = store.accept(&init, &bob_keys.secret).unwrap();
+    assert_eq!(alice_ss, bob_ss);
+    assert_eq!(store.len(), MAX_PREKEYS - 1);
+    assert!(!store.contains(bundle.prekey_id));
+}
+
+// Each prekey can only be used once
+#[test]
+fn prekey_consumed() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let bundle = store.bundle(&bob_keys.public, 0).unwrap();
+    let (init, _) = prekey_initiate(&bundle, &alice_keys.public, &mut rng).unwrap();
+    store.accept(&init, &bob_keys.secret).unwrap();
+    assert_eq!(
+        store.accept(&init, &bob_keys.secret),
+        Err(KyberError::PrekeyUnavailable)
+    );
+    assert_eq!(
+        store.bundle(&bob_keys.public, 0),
+        Err(KyberError::PrekeyUnavailable)
+    );
+}
+
+// Identifiers are not reused after replenishing
+#[test]
+fn prekey_replenish() {
+    let mut rng = rand::thread_rng();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    store.consume(0).unwrap();
+    store.consume(5).unwrap();
+    assert_eq!(store.replenish(&mut rng).unwrap(), 2);
+    assert!(!store.contains(0));
+    assert!(store.contains(MAX_PREKEYS as u32 + 1));
+    assert_eq!(store.len(), MAX_PREKEYS);
+}
+
+#[test]
+fn prekey_bundle_bytes() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let bundle = store.bundle(&bob_keys.public, 7).unwrap();
+    let bytes = bundle.to_bytes();
+    assert_eq!(PrekeyBundle::from_bytes(&bytes), Ok(bundle));
+    assert_eq!(
+        PrekeyBundle::from_bytes(&bytes[1..]),
+        Err(KyberError::InvalidInput)
+    );
+}
+
+// Tampered initiator identity changes the transcript
+#[test]
+fn prekey_invalid_initiator_identity() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let bundle = store.bundle(&bob_keys.public, 1).unwrap();
+    let (mut init, alice_ss) = prekey_initiate(&bundle, &alice_keys.public, &mut rng).unwrap();
+    init[4..][..4].copy_from_slice(&[255u8; 4]);
+    assert_ne!(store.accept(&init, &bob_keys.secret).unwrap(), alice_ss);
+}
+
+// Accepting with the wrong identity key
+#[test]
+fn prekey_invalid_identity_secret() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let bundle = store.bundle(&bob_keys.public, 1).unwrap();
+    let (init, alice_ss) = prekey_initiate(&bundle, &alice_keys.public, &mut rng).unwrap();
+    assert_ne!(store.accept(&init, &alice_keys.secret).unwrap(), alice_ss);
+}
+
+#[test]
+fn prekey_invalid_init_size() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let init = [0u8; PREKEY_INIT_BYTES - 1];
+    assert_eq!(
+        store.accept(&init, &bob_keys.secret),
+        Err(KyberError::InvalidInput)
+    );
+    assert_eq!(store.len(), MAX_PREKEYS);
+}
+
+#[test]
+fn prekey_failed_randombytes() {
+    let mut store = PrekeyStore::new();
+    let mut rng = FailingRng::default();
+    assert_eq!(
+        store.replenish(&mut rng),
+        Err(KyberError::RandomBytesGeneration)
+    );
+    assert!(store.is_empty());
+}
+
+// A restored store accepts initiations made against the original
+#[test]
+fn prekey_store_bytes() {
+    let mut rng = rand::thread_rng();
+    let alice_keys = keypair(&mut rng).unwrap();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    store.consume(1).unwrap();
+    let bundle = store.bundle(&bob_keys.public, 2).unwrap();
+    let (init, alice_ss) = prekey_initiate(&bundle, &alice_keys.public, &mut rng).unwrap();
+
+    let mut restored = PrekeyStore::from_bytes(&store.to_bytes()).unwrap();
+    assert_eq!(restored, store);
+    assert!(!restored.contains(1));
+    assert_eq!(restored.accept(&init, &bob_keys.secret).unwrap(), alice_ss);
+    restored.replenish(&mut rng).unwrap();
+    assert!(restored.contains(MAX_PREKEYS as u32));
+
+    let mut bytes = store.to_bytes();
+    assert!(PrekeyStore::from_bytes(&bytes[1..]).is_err());
+    bytes[0] ^= 0xff;
+    assert!(PrekeyStore::from_bytes(&bytes).is_err());
+}
+
+// Debug output must never leak the prekey seeds
+#[test]
+fn prekey_store_debug_redacts_seeds() {
+    let mut rng = rand::thread_rng();
+    let mut store = PrekeyStore::new();
+    store.replenish(&mut rng).unwrap();
+    let seed = &store.to_bytes()[10..26];
+    let debug = format!("{:?}", store);
+    assert!(debug.contains("[REDACTED]"));
+    assert!(!debug.contains(&format!("{:?}", seed)));
+}
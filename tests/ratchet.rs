@@ -0,0 +1,181 @@
+use pqc_kyber::*;
+mod utils;
+use utils::*;
+
+fn setup() -> (Ratchet, Ratchet) {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let mut alice = Uake::new();
+    let mut bob = Uake::new();
+    let client_init = alice.client_init(&bob_keys.public, &mut rng).unwrap();
+    let server_send = bob
+        .server_receive(client_init, &bob_keys.secret, &mut rng)
+        .unwrap();
+    alice.client_confirm(server_send).unwrap();
+    let alice = Ratchet::new_initiator(&alice.shared_secret, &bob_keys.public, &mut rng).unwrap();
+    let bob = Ratchet::new_responder(&bob.shared_secret, &bob_keys);
+    (alice, bob)
+}
+
+#[test]
+fn ratchet_in_order() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    for _ in 0..3 {
+        for _ in 0..3 {
+            let (header, key) = alice.send(&mut rng).unwrap();
+            assert_eq!(bob.receive(&header).unwrap(), key);
+        }
+        for _ in 0..2 {
+            let (header, key) = bob.send(&mut rng).unwrap();
+            assert_eq!(alice.receive(&header).unwrap(), key);
+        }
+    }
+}
+
+// Every message key is distinct
+#[test]
+fn ratchet_unique_keys() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let (h1, k1) = alice.send(&mut rng).unwrap();
+    let (_, k2) = alice.send(&mut rng).unwrap();
+    bob.receive(&h1).unwrap();
+    let (_, k3) = bob.send(&mut rng).unwrap();
+    assert_ne!(k1, k2);
+    assert_ne!(k1, k3);
+    assert_ne!(k2, k3);
+}
+
+#[test]
+fn ratchet_out_of_order() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let sent: Vec<_> = (0..4).map(|_| alice.send(&mut rng).unwrap()).collect();
+    for i in [3, 0, 2, 1] {
+        assert_eq!(bob.receive(&sent[i].0).unwrap(), sent[i].1);
+    }
+}
+
+// Messages from a previous epoch arriving after the peer has ratcheted
+#[test]
+fn ratchet_out_of_order_across_epochs() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let (h1, k1) = alice.send(&mut rng).unwrap();
+    let (h2, k2) = alice.send(&mut rng).unwrap();
+    assert_eq!(bob.receive(&h1).unwrap(), k1);
+    let (h3, k3) = bob.send(&mut rng).unwrap();
+    assert_eq!(alice.receive(&h3).unwrap(), k3);
+    let (h4, k4) = alice.send(&mut rng).unwrap();
+    assert_eq!(bob.receive(&h4).unwrap(), k4);
+    assert_eq!(bob.receive(&h2).unwrap(), k2);
+}
+
+#[test]
+fn ratchet_replay() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let (header, _) = alice.send(&mut rng).unwrap();
+    bob.receive(&header).unwrap();
+    assert_eq!(
+        bob.receive(&header),
+        Err(RatchetError::MessageKeyUnavailable)
+    );
+}
+
+#[test]
+fn ratchet_too_many_skipped() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    for _ in 0..RATCHET_MAX_SKIP + 1 {
+        alice.send(&mut rng).unwrap();
+    }
+    let (header, _) = alice.send(&mut rng).unwrap();
+    let before = bob.clone();
+    assert_eq!(bob.receive(&header), Err(RatchetError::TooManySkipped));
+    assert_eq!(bob, before);
+}
+
+#[test]
+fn ratchet_responder_not_ready() {
+    let mut rng = rand::thread_rng();
+    let (_, mut bob) = setup();
+    assert_eq!(bob.send(&mut rng), Err(RatchetError::NotReady));
+}
+
+// Corrupted ciphertext in the header, the keys won't match
+#[test]
+fn ratchet_invalid_header_ciphertext() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let (mut header, key) = alice.send(&mut rng).unwrap();
+    header[RATCHET_HEADER_BYTES - 4..].copy_from_slice(&[255u8; 4]);
+    assert_ne!(bob.receive(&header).unwrap(), key);
+}
+
+#[test]
+fn ratchet_invalid_header_size() {
+    let (_, mut bob) = setup();
+    let header = [0u8; RATCHET_HEADER_BYTES - 1];
+    assert_eq!(
+        bob.receive(&header),
+        Err(RatchetError::Kyber(KyberError::InvalidInput))
+    );
+}
+
+#[test]
+fn ratchet_serialize_state() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let sent: Vec<_> = (0..3).map(|_| alice.send(&mut rng).unwrap()).collect();
+    bob.receive(&sent[2].0).unwrap();
+    let mut bob = Ratchet::from_bytes(&bob.to_bytes()).unwrap();
+    assert_eq!(bob.receive(&sent[0].0).unwrap(), sent[0].1);
+    let (header, key) = bob.send(&mut rng).unwrap();
+    let mut alice = Ratchet::from_bytes(&alice.to_bytes()).unwrap();
+    assert_eq!(alice.receive(&header).unwrap(), key);
+}
+
+#[test]
+fn ratchet_invalid_state() {
+    let (alice, _) = setup();
+    let mut state = alice.to_bytes();
+    assert_eq!(
+        Ratchet::from_bytes(&state[1..]),
+        Err(RatchetError::InvalidState)
+    );
+    state[0] += 1;
+    assert_eq!(Ratchet::from_bytes(&state), Err(RatchetError::InvalidState));
+}
+
+#[test]
+fn ratchet_failed_randombytes() {
+    let mut rng = rand::thread_rng();
+    let (mut alice, mut bob) = setup();
+    let (header, _) = alice.send(&mut rng).unwrap();
+    bob.receive(&header).unwrap();
+    let before = bob.clone();
+    let mut rng = FailingRng::default();
+    assert_eq!(
+        bob.send(&mut rng),
+        Err(RatchetError::Kyber(KyberError::RandomBytesGeneration))
+    );
+    assert_eq!(bob, before);
+}
+
+// Debug output must only show the epochs and counters
+#[test]
+fn ratchet_debug_redacts_keys() {
+    let mut rng = rand::thread_rng();
+    let bob_keys = keypair(&mut rng).unwrap();
+    let shared_secret = [7u8; KYBER_SSBYTES];
+    let mut alice = Ratchet::new_initiator(&shared_secret, &bob_keys.public, &mut rng).unwrap();
+    let bob = Ratchet::new_responder(&shared_secret, &bob_keys);
+    alice.send(&mut rng).unwrap();
+    let debug = format!("{:?} {:?}", alice, bob);
+    assert!(debug.contains("[REDACTED]"));
+    assert!(debug.contains("send_n: 1"));
+    assert!(!debug.contains(&format!("{:?}", &shared_secret[..16])));
+    assert!(!debug.contains(&format!("{:?}", &bob_keys.secret[..16])));
+}
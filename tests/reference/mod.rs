@@ -0,0 +1,46 @@
+// A second build of the reference backend, for comparing against the avx2
+// backend compiled into pqc_kyber. Only one backend can be compiled into the
+// crate at a time, so the reference sources are included here. They refer to
+// each other through `crate::`, so users glob import this module at their
+// crate root:
+//
+// mod reference;
+// use reference::*;
+//
+// Also included by the differential fuzz targets in fuzz/src/lib.rs.
+#![allow(dead_code, clippy::many_single_char_names)]
+
+#[path = "../../src/reference/aes256ctr.rs"]
+pub mod aes256ctr;
+#[path = "../../src/reference/cbd.rs"]
+pub mod cbd;
+#[path = "../../src/error.rs"]
+pub mod error;
+#[cfg(feature = "fault-hardened")]
+#[path = "../../src/fault.rs"]
+pub mod fault;
+#[path = "../../src/reference/fips202.rs"]
+pub mod fips202;
+#[path = "../../src/reference/indcpa.rs"]
+pub mod indcpa;
+#[path = "../../src/kem.rs"]
+pub mod kem;
+#[path = "../../src/reference/ntt.rs"]
+pub mod ntt;
+#[path = "../../src/params.rs"]
+pub mod params;
+#[path = "../../src/reference/poly.rs"]
+pub mod poly;
+#[path = "../../src/reference/polyvec.rs"]
+pub mod polyvec;
+#[path = "../../src/reference/reduce.rs"]
+pub mod reduce;
+#[path = "../../src/rng.rs"]
+pub mod rng;
+#[path = "../../src/symmetric.rs"]
+pub mod symmetric;
+#[path = "../../src/reference/verify.rs"]
+pub mod verify;
+
+pub use error::KyberError;
+pub use rand_core::{CryptoRng, RngCore};
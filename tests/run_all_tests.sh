@@ -7,6 +7,7 @@ set -e
 # KAT - Runs the known answer tests
 # AVX2 - Runs avx2 code on x86 platforms with compiled GAS files
 # NASM - Runs avx2 code with both GAS and NASM files seperately
+# WASM - Runs the simd128 code on wasm32-wasip1 under wasmtime
 
 # When setting AVX2 or NASM flags enable avx2 target features 
 # and LLVM address sanitser checks (requires nightly):
@@ -40,6 +41,21 @@ if [[ ! -z "$NASM" ]]
     OPT+=("nasm")
 fi
 
+# The wasm tests need the wasm32-wasip1 target and wasmtime installed,
+# the crate folder is preopened so the KAT files can be read
+if [ -z "$WASM" ]
+  then
+    echo Not using WASM SIMD128 optimisations
+    TARGET_FLAG=""
+  else
+    echo Using WASM SIMD128 optimisations under wasmtime
+    RUSTFLAGS+=" -C target-feature=+simd128"
+    TARGET=wasm32-wasip1
+    TARGET_FLAG="--target $TARGET"
+    ROOT=$(cd "$(dirname "$0")/.." && pwd)
+    export CARGO_TARGET_WASM32_WASIP1_RUNNER="wasmtime run --dir=$ROOT"
+fi
+
 # Print Headers
 announce(){
   title="#    $1    #"
@@ -62,12 +78,38 @@ for level in "${LEVELS[@]}"; do
       name="$level $nine $opt"
       feat=${level:+"$level"}${opt:+",$opt"}${nine:+",$nine"}
       announce "$name"
-      RUSTFLAGS=$RUSTFLAGS cargo test --features $feat
+      RUSTFLAGS=$RUSTFLAGS cargo test $TARGET_FLAG --features $feat
       break;
     done
   done
 done
 
+# Arithmetic properties run against both backends, the avx2 NTT is also
+# checked against the reference code
+for level in "${LEVELS[@]}"; do
+  for opt in "${OPT[@]}"; do
+    announce "$level hazmat $opt"
+    RUSTFLAGS=$RUSTFLAGS cargo test $TARGET_FLAG --features $level,hazmat${opt:+",$opt"} --test arith --test ntt_equivalence
+  done
+done
+
+# Fault simulation runs against both backends
+for level in "${LEVELS[@]}"; do
+  for opt in "${OPT[@]}"; do
+    announce "$level fault-simulation $opt"
+    RUSTFLAGS=$RUSTFLAGS cargo test $TARGET_FLAG --features $level,fault-simulation${opt:+",$opt"} --test fault
+  done
+done
+
+# Masked decapsulation only exists for the reference SHAKE mode
+if [ -z "$AVX2" ]
+  then
+  for level in "${LEVELS[@]}"; do
+    announce "$level masked"
+    RUSTFLAGS=$RUSTFLAGS cargo test $TARGET_FLAG --features $level,masked --test masked
+  done
+fi
+
 end=`date +%s`
 runtime=$((end-start))
 announce "Test runtime: $runtime seconds"
@@ -0,0 +1,94 @@
+#![cfg(feature = "serde")]
+
+use pqc_kyber::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Message {
+    #[serde(with = "pqc_kyber::serialize")]
+    public_key: PublicKey,
+    #[serde(with = "pqc_kyber::serialize")]
+    client_init: UakeSendInit,
+}
+
+fn to_cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::ser::into_writer(value, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn serde_keypair_json() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let json = serde_json::to_string(&keys).unwrap();
+    assert!(json.starts_with("{\"public\":\""));
+    assert_eq!(serde_json::from_str::<Keypair>(&json).unwrap(), keys);
+}
+
+#[test]
+fn serde_keypair_cbor() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let cbor = to_cbor(&keys);
+    // Raw bytes are smaller than two hex strings
+    assert!(cbor.len() < KYBER_PUBLICKEYBYTES + KYBER_SECRETKEYBYTES + 32);
+    let decoded: Keypair = ciborium::de::from_reader(&cbor[..]).unwrap();
+    assert_eq!(decoded, keys);
+}
+
+#[test]
+fn serde_with_arrays() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut alice = Uake::new();
+    let message = Message {
+        public_key: keys.public,
+        client_init: alice.client_init(&keys.public, &mut rng).unwrap(),
+    };
+    let json = serde_json::to_string(&message).unwrap();
+    assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+    let cbor = to_cbor(&message);
+    let decoded: Message = ciborium::de::from_reader(&cbor[..]).unwrap();
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn serde_uppercase_hex() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let json = serde_json::to_string(&keys).unwrap().to_uppercase();
+    let json = json.replace("PUBLIC", "public").replace("SECRET", "secret");
+    assert_eq!(serde_json::from_str::<Keypair>(&json).unwrap(), keys);
+}
+
+// Same field names as Message with a truncated public key
+#[derive(Serialize)]
+struct Truncated {
+    #[serde(with = "pqc_kyber::serialize")]
+    public_key: [u8; KYBER_PUBLICKEYBYTES - 1],
+    #[serde(with = "pqc_kyber::serialize")]
+    client_init: UakeSendInit,
+}
+
+#[test]
+fn serde_wrong_length() {
+    let truncated = Truncated {
+        public_key: [1u8; KYBER_PUBLICKEYBYTES - 1],
+        client_init: [1u8; UAKE_INIT_BYTES],
+    };
+    let json = serde_json::to_string(&truncated).unwrap();
+    assert!(serde_json::from_str::<Message>(&json).is_err());
+    let cbor = to_cbor(&truncated);
+    assert!(ciborium::de::from_reader::<Message, _>(&cbor[..]).is_err());
+}
+
+#[test]
+fn serde_invalid_hex() {
+    let mut rng = rand::thread_rng();
+    let keys = keypair(&mut rng).unwrap();
+    let mut json = serde_json::to_string(&keys).unwrap();
+    let start = "{\"public\":\"".len();
+    json.replace_range(start..start + 2, "zz");
+    assert!(serde_json::from_str::<Keypair>(&json).is_err());
+}
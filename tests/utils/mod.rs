@@ -1,3 +1,6 @@
+// Each test crate only uses some of these
+#![allow(dead_code)]
+
 use rand_core::{CryptoRng, Error, RngCore};
 
 pub struct FailingRng(u64);
@@ -28,3 +31,34 @@ impl RngCore for FailingRng {
 }
 
 impl CryptoRng for FailingRng {}
+
+/// Replays recorded randomness, eg. the encapsulation message of a test
+/// vector, failing once it runs out
+pub struct ReplayRng<'a>(pub &'a [u8]);
+
+impl RngCore for ReplayRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest)
+            .expect("replayed randomness exhausted")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        if dest.len() > self.0.len() {
+            return Err(Error::new("replayed randomness exhausted"));
+        }
+        let (head, rest) = self.0.split_at(dest.len());
+        dest.copy_from_slice(head);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for ReplayRng<'_> {}
@@ -0,0 +1,180 @@
+// Runs the Wycheproof-style edge cases in wycheproof/kyber_kem_test.json
+// against encapsulate and decapsulate. Each test changes the keypair
+// derived from the file's seed, or the ciphertext encapsulated to it, and
+// gives the expected outcome for this crate and under ML-KEM. The ML-KEM
+// expectations are checked through the FIPS 203 input checks,
+// check_public_key and check_secret_key.
+
+use pqc_kyber::*;
+use serde_json::Value;
+use std::ops::Range;
+mod utils;
+use utils::ReplayRng;
+
+const VECTORS: &str = include_str!("wycheproof/kyber_kem_test.json");
+
+const POLYVECBYTES: usize = 384 * KYBER_K;
+const POLYVECCOMPRESSEDBYTES: usize = KYBER_K * if KYBER_K == 4 { 352 } else { 320 };
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("Hex string decoding"))
+        .collect()
+}
+
+// Byte ranges of the named parts of each input
+fn region(name: &str) -> Range<usize> {
+    const PK: usize = POLYVECBYTES + KYBER_PUBLICKEYBYTES;
+    match name {
+        // Public key
+        "t" => 0..POLYVECBYTES,
+        "rho" => POLYVECBYTES..KYBER_PUBLICKEYBYTES,
+        // Secret key
+        "s" => 0..POLYVECBYTES,
+        "pk" => POLYVECBYTES..PK,
+        "hpk" => PK..PK + KYBER_SYMBYTES,
+        "z" => PK + KYBER_SYMBYTES..KYBER_SECRETKEYBYTES,
+        // Ciphertext
+        "u" => 0..POLYVECCOMPRESSEDBYTES,
+        "v" => POLYVECCOMPRESSEDBYTES..KYBER_CIPHERTEXTBYTES,
+        _ => panic!("Unknown region {}", name),
+    }
+}
+
+// Negative positions count back from the end
+fn position(i: i64, len: usize) -> usize {
+    if i < 0 {
+        len - i.unsigned_abs() as usize
+    } else {
+        i as usize
+    }
+}
+
+fn mutate(input: &[u8], op: &Value) -> Vec<u8> {
+    let mut out = input.to_vec();
+    let range = match op["region"].as_str() {
+        Some(name) => region(name),
+        None => 0..out.len(),
+    };
+    match op["op"].as_str().expect("op") {
+        "none" => {}
+        "fill" => {
+            let byte = decode_hex(op["byte"].as_str().expect("byte"))[0];
+            out[range].iter_mut().for_each(|b| *b = byte);
+        }
+        "flipBit" => {
+            let i = range.start + position(op["byte"].as_i64().expect("byte"), range.len());
+            out[i] ^= 1 << op["bit"].as_u64().expect("bit");
+        }
+        "setCoefficient" => {
+            let i = position(op["index"].as_i64().expect("index"), POLYVECBYTES * 2 / 3);
+            let v = op["value"].as_u64().expect("value") as u16;
+            let b = &mut out[3 * (i / 2)..];
+            if i % 2 == 0 {
+                b[0] = v as u8;
+                b[1] = (b[1] & 0xf0) | (v >> 8) as u8;
+            } else {
+                b[1] = (b[1] & 0x0f) | (v << 4) as u8;
+                b[2] = (v >> 4) as u8;
+            }
+        }
+        "resize" => {
+            let len = out.len() as i64 + op["by"].as_i64().expect("by");
+            out.resize(len as usize, 0);
+        }
+        "truncate" => out.truncate(op["length"].as_u64().expect("length") as usize),
+        other => panic!("Unknown op {}", other),
+    }
+    out
+}
+
+fn has_flag(test: &Value, flag: &str) -> bool {
+    test["flags"]
+        .as_array()
+        .expect("flags")
+        .iter()
+        .any(|f| f == flag)
+}
+
+fn run_encapsulation(test: &Value, keys: &Keypair, m: &[u8]) {
+    let id = test["tcId"].clone();
+    let pk = mutate(&keys.public, &test["publicKey"]);
+    let res = encapsulate(&pk, &mut ReplayRng(m));
+    match test["kyber"].as_str().expect("kyber") {
+        "valid" | "acceptable" => {
+            let (ct, ss) = res.unwrap_or_else(|e| panic!("tcId {}: {}", id, e));
+            if pk[..] == keys.public[..] {
+                assert_eq!(decapsulate(&ct, &keys.secret).unwrap(), ss, "tcId {}", id);
+            }
+        }
+        "invalid" => assert_eq!(res, Err(KyberError::InvalidInput), "tcId {}", id),
+        other => panic!("tcId {}: unknown result {}", id, other),
+    }
+    let check = check_public_key(&pk);
+    match test["mlkem"].as_str().expect("mlkem") {
+        "valid" => assert_eq!(check, Ok(()), "tcId {}", id),
+        "invalid" => assert_eq!(check, Err(KyberError::InvalidInput), "tcId {}", id),
+        other => panic!("tcId {}: unknown result {}", id, other),
+    }
+}
+
+fn run_decapsulation(test: &Value, keys: &Keypair, ct: &[u8], ss: &[u8]) {
+    let id = test["tcId"].clone();
+    let ct = mutate(ct, &test["ciphertext"]);
+    let sk = mutate(&keys.secret, &test["secretKey"]);
+    let res = decapsulate(&ct, &sk);
+    let expected = test["kyber"].as_str().expect("kyber");
+    if cfg!(feature = "fault-hardened") && has_flag(test, "HashCheck") {
+        assert_eq!(res, Err(KyberError::FaultDetected), "tcId {}", id);
+    } else {
+        match expected {
+            "valid" => assert_eq!(res.unwrap()[..], ss[..], "tcId {}", id),
+            "implicitReject" => {
+                let rejected = res.unwrap_or_else(|e| panic!("tcId {}: {}", id, e));
+                assert_ne!(rejected[..], ss[..], "tcId {}", id);
+                // The rejection secret is derived from z
+                let mut other_z = sk.clone();
+                other_z[KYBER_SECRETKEYBYTES - 1] ^= 1;
+                assert_ne!(decapsulate(&ct, &other_z).unwrap(), rejected, "tcId {}", id);
+            }
+            "invalid" => assert_eq!(res, Err(KyberError::InvalidInput), "tcId {}", id),
+            other => panic!("tcId {}: unknown result {}", id, other),
+        }
+    }
+    // ML-KEM rejects wrong lengths and failed key checks, otherwise it has
+    // the same outcome as Kyber
+    let check = if ct.len() == KYBER_CIPHERTEXTBYTES {
+        check_secret_key(&sk)
+    } else {
+        Err(KyberError::InvalidInput)
+    };
+    match test["mlkem"].as_str().expect("mlkem") {
+        "invalid" => assert_eq!(check, Err(KyberError::InvalidInput), "tcId {}", id),
+        result => {
+            assert_eq!(check, Ok(()), "tcId {}", id);
+            assert_eq!(result, expected, "tcId {}", id);
+        }
+    }
+}
+
+#[test]
+fn wycheproof_vectors() {
+    let vectors: Value = serde_json::from_str(VECTORS).expect("Error parsing test vectors");
+    let keys = derive(&decode_hex(vectors["seed"].as_str().expect("seed"))).unwrap();
+    let m = decode_hex(vectors["message"].as_str().expect("message"));
+    let (ct, ss) = encapsulate(&keys.public, &mut ReplayRng(&m)).unwrap();
+
+    let mut count = 0;
+    for group in vectors["testGroups"].as_array().expect("testGroups") {
+        for test in group["tests"].as_array().expect("tests") {
+            match group["type"].as_str().expect("type") {
+                "Encapsulation" => run_encapsulation(test, &keys, &m),
+                "Decapsulation" => run_decapsulation(test, &keys, &ct, &ss),
+                other => panic!("Unknown test group {}", other),
+            }
+            count += 1;
+        }
+    }
+    assert_eq!(count, vectors["numberOfTests"], "numberOfTests");
+}
@@ -0,0 +1,308 @@
+//! All three Kyber security levels in a single wasm module.
+//!
+//! The main crate fixes its security level at compile time, this bundle links
+//! its sources once per level and exports each as a class of static methods,
+//! so clients can negotiate the level at runtime:
+//!
+//! ```js
+//! import { Kyber512, Kyber768, Kyber1024 } from "pqc-kyber-bundle";
+//!
+//! const kyber = { 512: Kyber512, 768: Kyber768, 1024: Kyber1024 }[level];
+//! const keys = kyber.keypair();
+//! const kex = kyber.encapsulate(keys.pubkey);
+//! const sharedSecret = kyber.decapsulate(kex.ciphertext, keys.secret);
+//! ```
+//!
+//! Keys and ciphertexts are plain byte arrays, passing one to the wrong level
+//! throws with its expected size from that level's `Params`.
+//!
+//! The key exchange classes are exported per level as `Kyber512Uake`,
+//! `Kyber512Ake` and so on. The WebCrypto-style API is only in the single
+//! level package.
+#![allow(non_snake_case)]
+
+use rand::rngs::OsRng;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct Keys {
+    pubkey: Box<[u8]>,
+    secret: Box<[u8]>,
+}
+
+#[wasm_bindgen]
+impl Keys {
+    #[wasm_bindgen(getter)]
+    pub fn pubkey(&self) -> Box<[u8]> {
+        self.pubkey.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn secret(&self) -> Box<[u8]> {
+        self.secret.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Kex {
+    ciphertext: Box<[u8]>,
+    sharedSecret: Box<[u8]>,
+}
+
+#[wasm_bindgen]
+impl Kex {
+    #[wasm_bindgen(getter)]
+    pub fn ciphertext(&self) -> Box<[u8]> {
+        self.ciphertext.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sharedSecret(&self) -> Box<[u8]> {
+        self.sharedSecret.clone()
+    }
+}
+
+/// Sizes in bytes for one security level
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Params {
+    /// 512, 768 or 1024
+    #[wasm_bindgen(readonly)]
+    pub level: usize,
+    #[wasm_bindgen(readonly)]
+    pub publicKeyBytes: usize,
+    #[wasm_bindgen(readonly)]
+    pub secretKeyBytes: usize,
+    #[wasm_bindgen(readonly)]
+    pub ciphertextBytes: usize,
+    #[wasm_bindgen(readonly)]
+    pub sharedSecretBytes: usize,
+}
+
+// Checks the length of a JS byte array against the level's size
+fn check(bytes: &[u8], len: usize, name: &str) -> Result<(), JsError> {
+    if bytes.len() != len {
+        return Err(JsError::new(&format!(
+            "{} must be {} bytes, found {}",
+            name,
+            len,
+            bytes.len()
+        )));
+    }
+    Ok(())
+}
+
+// Copies a JS byte array into the level's fixed size array
+fn array<const N: usize>(bytes: &[u8], name: &str) -> Result<[u8; N], JsError> {
+    check(bytes, N, name)?;
+    let mut out = [0u8; N];
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+macro_rules! level {
+    ($doc:literal, $name:ident, $kyber:ident, $level:literal) => {
+        #[doc = $doc]
+        #[wasm_bindgen]
+        pub struct $name {}
+
+        #[wasm_bindgen]
+        impl $name {
+            #[wasm_bindgen(getter = Params)]
+            pub fn params() -> Params {
+                Params {
+                    level: $level,
+                    publicKeyBytes: $kyber::KYBER_PUBLICKEYBYTES,
+                    secretKeyBytes: $kyber::KYBER_SECRETKEYBYTES,
+                    ciphertextBytes: $kyber::KYBER_CIPHERTEXTBYTES,
+                    sharedSecretBytes: $kyber::KYBER_SSBYTES,
+                }
+            }
+
+            pub fn keypair() -> Result<Keys, JsError> {
+                let keys = $kyber::keypair(&mut OsRng).map_err(error)?;
+                Ok(Keys {
+                    pubkey: Box::new(keys.public),
+                    secret: Box::new(keys.secret),
+                })
+            }
+
+            /// Deterministically derives a keypair from a 64 byte seed
+            pub fn derive(seed: Box<[u8]>) -> Result<Keys, JsError> {
+                check(&seed, 64, "Seed")?;
+                let keys = $kyber::derive(&seed).map_err(error)?;
+                Ok(Keys {
+                    pubkey: Box::new(keys.public),
+                    secret: Box::new(keys.secret),
+                })
+            }
+
+            /// Extracts the public key from a secret key
+            #[wasm_bindgen(js_name = publicKey)]
+            pub fn public_key(sk: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+                check(&sk, $kyber::KYBER_SECRETKEYBYTES, "Secret key")?;
+                Ok(Box::new($kyber::public(&sk)))
+            }
+
+            pub fn encapsulate(pk: Box<[u8]>) -> Result<Kex, JsError> {
+                check(&pk, $kyber::KYBER_PUBLICKEYBYTES, "Public key")?;
+                let (ciphertext, shared_secret) =
+                    $kyber::encapsulate(&pk, &mut OsRng).map_err(error)?;
+                Ok(Kex {
+                    ciphertext: Box::new(ciphertext),
+                    sharedSecret: Box::new(shared_secret),
+                })
+            }
+
+            pub fn decapsulate(ct: Box<[u8]>, sk: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+                check(&ct, $kyber::KYBER_CIPHERTEXTBYTES, "Ciphertext")?;
+                check(&sk, $kyber::KYBER_SECRETKEYBYTES, "Secret key")?;
+                let ss = $kyber::decapsulate(&ct, &sk).map_err(error)?;
+                Ok(Box::new(ss))
+            }
+        }
+    };
+}
+
+// The key exchanges of one level, as in the single level package
+macro_rules! kex {
+    ($uake_doc:literal, $uake:ident, $ake_doc:literal, $ake:ident, $kyber:ident) => {
+        #[doc = $uake_doc]
+        #[wasm_bindgen]
+        #[derive(Default)]
+        pub struct $uake($kyber::Uake);
+
+        #[wasm_bindgen]
+        impl $uake {
+            #[wasm_bindgen(constructor)]
+            pub fn new() -> $uake {
+                $uake::default()
+            }
+
+            /// The shared secret, set once the exchange completes
+            #[wasm_bindgen(getter)]
+            pub fn sharedSecret(&self) -> Box<[u8]> {
+                Box::new(self.0.shared_secret)
+            }
+
+            /// Starts the exchange with the server's public key
+            pub fn clientInit(&mut self, serverPublicKey: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+                let pk = array(&serverPublicKey, "Server public key")?;
+                let init = self.0.client_init(&pk, &mut OsRng).map_err(error)?;
+                Ok(Box::new(init))
+            }
+
+            /// Handles the client's initiation with the server's secret key
+            pub fn serverReceive(
+                &mut self,
+                clientInit: Box<[u8]>,
+                serverSecretKey: Box<[u8]>,
+            ) -> Result<Box<[u8]>, JsError> {
+                let init = array(&clientInit, "Client init")?;
+                let sk = array(&serverSecretKey, "Server secret key")?;
+                let response = self
+                    .0
+                    .server_receive(init, &sk, &mut OsRng)
+                    .map_err(error)?;
+                Ok(Box::new(response))
+            }
+
+            /// Completes the exchange with the server's response
+            pub fn clientConfirm(&mut self, serverResponse: Box<[u8]>) -> Result<(), JsError> {
+                let response = array(&serverResponse, "Server response")?;
+                self.0.client_confirm(response).map_err(error)
+            }
+        }
+
+        #[doc = $ake_doc]
+        #[wasm_bindgen]
+        #[derive(Default)]
+        pub struct $ake($kyber::Ake);
+
+        #[wasm_bindgen]
+        impl $ake {
+            #[wasm_bindgen(constructor)]
+            pub fn new() -> $ake {
+                $ake::default()
+            }
+
+            /// The shared secret, set once the exchange completes
+            #[wasm_bindgen(getter)]
+            pub fn sharedSecret(&self) -> Box<[u8]> {
+                Box::new(self.0.shared_secret)
+            }
+
+            /// Starts the exchange with the server's public key
+            pub fn clientInit(&mut self, serverPublicKey: Box<[u8]>) -> Result<Box<[u8]>, JsError> {
+                let pk = array(&serverPublicKey, "Server public key")?;
+                let init = self.0.client_init(&pk, &mut OsRng).map_err(error)?;
+                Ok(Box::new(init))
+            }
+
+            /// Handles the client's initiation with the client's public key
+            /// and the server's secret key
+            pub fn serverReceive(
+                &mut self,
+                clientInit: Box<[u8]>,
+                clientPublicKey: Box<[u8]>,
+                serverSecretKey: Box<[u8]>,
+            ) -> Result<Box<[u8]>, JsError> {
+                let init = array(&clientInit, "Client init")?;
+                let pk = array(&clientPublicKey, "Client public key")?;
+                let sk = array(&serverSecretKey, "Server secret key")?;
+                let response = self
+                    .0
+                    .server_receive(init, &pk, &sk, &mut OsRng)
+                    .map_err(error)?;
+                Ok(Box::new(response))
+            }
+
+            /// Completes the exchange with the server's response and the
+            /// client's secret key
+            pub fn clientConfirm(
+                &mut self,
+                serverResponse: Box<[u8]>,
+                clientSecretKey: Box<[u8]>,
+            ) -> Result<(), JsError> {
+                let response = array(&serverResponse, "Server response")?;
+                let sk = array(&clientSecretKey, "Client secret key")?;
+                self.0.client_confirm(response, &sk).map_err(error)
+            }
+        }
+    };
+}
+
+// Each level has its own error type, they share the messages
+fn error<E: core::fmt::Display>(err: E) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+level!("Kyber512 key encapsulation", Kyber512, pqc_kyber512, 512);
+level!("Kyber768 key encapsulation", Kyber768, pqc_kyber768, 768);
+level!(
+    "Kyber1024 key encapsulation",
+    Kyber1024,
+    pqc_kyber1024,
+    1024
+);
+kex!(
+    "Kyber512 unilaterally authenticated key exchange",
+    Kyber512Uake,
+    "Kyber512 mutually authenticated key exchange",
+    Kyber512Ake,
+    pqc_kyber512
+);
+kex!(
+    "Kyber768 unilaterally authenticated key exchange",
+    Kyber768Uake,
+    "Kyber768 mutually authenticated key exchange",
+    Kyber768Ake,
+    pqc_kyber768
+);
+kex!(
+    "Kyber1024 unilaterally authenticated key exchange",
+    Kyber1024Uake,
+    "Kyber1024 mutually authenticated key exchange",
+    Kyber1024Ake,
+    pqc_kyber1024
+);
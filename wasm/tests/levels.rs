@@ -0,0 +1,70 @@
+use pqc_kyber_bundle::*;
+
+// Round trips each level and checks the reported sizes
+macro_rules! roundtrip {
+    ($test:ident, $kyber:ident, $level:literal, $pk:literal) => {
+        #[test]
+        fn $test() {
+            let params = $kyber::params();
+            assert_eq!(params.level, $level);
+            assert_eq!(params.publicKeyBytes, $pk);
+
+            let keys = $kyber::keypair().unwrap();
+            assert_eq!(keys.pubkey().len(), params.publicKeyBytes);
+            assert_eq!(keys.secret().len(), params.secretKeyBytes);
+            assert_eq!($kyber::public_key(keys.secret()).unwrap(), keys.pubkey());
+
+            let kex = $kyber::encapsulate(keys.pubkey()).unwrap();
+            assert_eq!(kex.ciphertext().len(), params.ciphertextBytes);
+            let ss = $kyber::decapsulate(kex.ciphertext(), keys.secret()).unwrap();
+            assert_eq!(ss, kex.sharedSecret());
+        }
+    };
+}
+
+roundtrip!(kyber512_roundtrip, Kyber512, 512, 800);
+roundtrip!(kyber768_roundtrip, Kyber768, 768, 1184);
+roundtrip!(kyber1024_roundtrip, Kyber1024, 1024, 1568);
+
+// The same seed gives different keys at each level
+#[test]
+fn derive_per_level() {
+    let seed = vec![7u8; 64].into_boxed_slice();
+    let a = Kyber512::derive(seed.clone()).unwrap();
+    let b = Kyber768::derive(seed.clone()).unwrap();
+    let c = Kyber1024::derive(seed).unwrap();
+    assert_eq!(a.pubkey().len(), 800);
+    assert_eq!(b.pubkey().len(), 1184);
+    assert_eq!(c.pubkey().len(), 1568);
+    assert_ne!(a.pubkey()[..800], b.pubkey()[..800]);
+    assert_ne!(b.pubkey()[..800], c.pubkey()[..800]);
+}
+
+// Both key exchanges agree at each level
+macro_rules! kex {
+    ($test:ident, $kyber:ident, $uake:ident, $ake:ident) => {
+        #[test]
+        fn $test() {
+            let server = $kyber::keypair().unwrap();
+            let client = $kyber::keypair().unwrap();
+
+            let (mut alice, mut bob) = ($uake::new(), $uake::new());
+            let init = alice.clientInit(server.pubkey()).unwrap();
+            let response = bob.serverReceive(init, server.secret()).unwrap();
+            alice.clientConfirm(response).unwrap();
+            assert_eq!(alice.sharedSecret(), bob.sharedSecret());
+
+            let (mut alice, mut bob) = ($ake::new(), $ake::new());
+            let init = alice.clientInit(server.pubkey()).unwrap();
+            let response = bob
+                .serverReceive(init, client.pubkey(), server.secret())
+                .unwrap();
+            alice.clientConfirm(response, client.secret()).unwrap();
+            assert_eq!(alice.sharedSecret(), bob.sharedSecret());
+        }
+    };
+}
+
+kex!(kyber512_kex, Kyber512, Kyber512Uake, Kyber512Ake);
+kex!(kyber768_kex, Kyber768, Kyber768Uake, Kyber768Ake);
+kex!(kyber1024_kex, Kyber1024, Kyber1024Uake, Kyber1024Ake);
@@ -0,0 +1,46 @@
+// The level packages compile the main crate's sources, so their
+// dependencies and features have to stay in step with the main manifest
+use std::fs;
+
+const LEVELS: [&str; 3] = ["kyber512", "kyber768", "kyber1024"];
+
+// Trimmed lines of a manifest section, without comments or blank lines
+fn section(manifest: &str, name: &str) -> Vec<String> {
+    manifest
+        .lines()
+        .skip_while(|l| l.trim() != name)
+        .skip(1)
+        .take_while(|l| !l.starts_with('['))
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn levels_match_main_manifest() {
+    let main = fs::read_to_string("../Cargo.toml").unwrap();
+    let dependencies = section(&main, "[dependencies]");
+    let features = section(&main, "[features]");
+    for level in LEVELS.iter() {
+        let manifest = fs::read_to_string(format!("levels/{}/Cargo.toml", level)).unwrap();
+        for dependency in section(&manifest, "[dependencies]") {
+            assert!(
+                dependencies.contains(&dependency),
+                "{}: {} differs from the main crate",
+                level,
+                dependency
+            );
+        }
+        for feature in section(&manifest, "[features]") {
+            if !feature.starts_with("default") {
+                assert!(
+                    features.contains(&feature),
+                    "{}: {} differs from the main crate",
+                    level,
+                    feature
+                );
+            }
+        }
+    }
+}
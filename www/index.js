@@ -17,6 +17,7 @@ const cipherTextBox2 = document.getElementById("ciphertext2");
 const sharedKeyBox = document.getElementById("sharedkey");
 const sharedKeyBox2 = document.getElementById("sharedkey2");
 
+document.getElementById('algorithm').innerHTML = kyber.Params.algorithm;
 document.getElementById('pkbytes').innerHTML = kyber.Params.publicKeyBytes;
 document.getElementById('skbytes').innerHTML = kyber.Params.secretKeyBytes;
 document.getElementById('ctbytes').innerHTML = kyber.Params.ciphertextBytes;
@@ -29,13 +30,17 @@ clearButton.addEventListener("click", event => {
     }
 });
 
-generateKeyButton.addEventListener("click", event => {
-    let keys = kyber.keypair();
-    const pubKey = keys.pubkey;
-    const privKey = keys.secret;
+const algorithm = { name: kyber.Params.algorithm };
 
-    pubKeyBox.value = toHexString(pubKey);
-    privKeyBox.value = toHexString(privKey);
+generateKeyButton.addEventListener("click", async event => {
+    const keys = await kyber.generateKey(
+        algorithm, true, ["encapsulateBits", "decapsulateBits"]
+    );
+    const pubKey = await kyber.exportKey("raw", keys.publicKey);
+    const privKey = await kyber.exportKey("jwk", keys.privateKey);
+
+    pubKeyBox.value = toHexString(new Uint8Array(pubKey));
+    privKeyBox.value = JSON.stringify(privKey);
 
     pubKeyBox2.value = pubKeyBox.value;
     privKeyBox2.value = privKeyBox.value;
@@ -43,28 +48,33 @@ generateKeyButton.addEventListener("click", event => {
     // pubKeyBox.value = Buffer.from(pubKey).toString('base64');    
 });
     
-encapButton.addEventListener("click", event => {
+encapButton.addEventListener("click", async event => {
     try {
-        let encapsulated = kyber.encapsulate(hexToBytes(pubKeyBox2.value));
-        cipherTextBox.value = toHexString(encapsulated.ciphertext);
-        sharedKeyBox.value = toHexString(encapsulated.sharedSecret);
+        const publicKey = await kyber.importKey(
+            "raw", hexToBytes(pubKeyBox2.value), algorithm, true, ["encapsulateBits"]
+        );
+        const { sharedKey, ciphertext } = await kyber.encapsulateBits(algorithm, publicKey);
+        cipherTextBox.value = toHexString(new Uint8Array(ciphertext));
+        sharedKeyBox.value = toHexString(new Uint8Array(sharedKey));
         cipherTextBox2.value = cipherTextBox.value;
     }
     catch(err) {
-        alert("Error Encapsulating");
+        alert("Error Encapsulating: " + err.message);
     }
 });
     
-decapButton.addEventListener("click", event => {
+decapButton.addEventListener("click", async event => {
     try {
-        let decapsulated = kyber.decapsulate(
-            hexToBytes(cipherTextBox2.value), 
-            hexToBytes(privKeyBox2.value)
+        const privateKey = await kyber.importKey(
+            "jwk", JSON.parse(privKeyBox2.value), algorithm, false, ["decapsulateBits"]
+        );
+        const sharedKey = await kyber.decapsulateBits(
+            algorithm, privateKey, hexToBytes(cipherTextBox2.value)
         );
-        sharedKeyBox2.value = toHexString(decapsulated);
+        sharedKeyBox2.value = toHexString(new Uint8Array(sharedKey));
     }
     catch(err) {
-        alert("Error Decapsulating");
+        alert("Error Decapsulating: " + err.message);
     }
 });
 
@@ -98,7 +108,7 @@ function toHexString(bytes) {
 function hexToBytes(hex) {
     for (var bytes = [], c = 0; c < hex.length; c += 2)
     bytes.push(parseInt(hex.substr(c, 2), 16));
-    return bytes;
+    return new Uint8Array(bytes);
 }
 
 